@@ -0,0 +1,121 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::configs::centrality::Centrality;
+use crate::configs::CnfFormula;
+use crate::constraints::CompileErrorGuard;
+use crate::errors::{Result, RustyExError};
+use crate::types::{
+    ArtifactsTree, CfgKind, FeaturesGraph, SimpleArtifactKey, SimpleTermKey, TermsTree,
+};
+use crate::{PrintAstArgs, PrintAstCallbacks};
+
+/// Everything extracted from a crate, as returned by `analyze`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AnalysisResult {
+    /// Relationships between all terms (all pieces of code annotated or not)
+    pub terms_tree: TermsTree<SimpleTermKey>,
+    /// Multigraph storing relationships between features (with the dummy centrality node)
+    pub features_graph: FeaturesGraph,
+    /// Relationships between all artifacts (terms nodes with features)
+    pub artifacts_tree: ArtifactsTree<SimpleArtifactKey>,
+    /// Centrality measures of the features, by CNF variable
+    pub centrality: Centrality<u32>,
+    /// CNF formula of the features graph
+    pub cnf: CnfFormula<u32>,
+    /// CNF variable of each feature
+    pub cnf_mapping: HashMap<String, u32>,
+    /// Configurations generated from the CNF formula (a value for each variable), covering the
+    /// most central features first (see `--config-budget`)
+    pub configs: CnfFormula<u32>,
+    /// Kind of `cfg` option of each feature name
+    pub cfg_kinds: HashMap<String, CfgKind>,
+    /// `compile_error!`s annotated by a `cfg`, whose constraints are part of the CNF formula
+    pub compile_error_guards: Vec<CompileErrorGuard>,
+    /// Errors found during the analysis (not fatal)
+    pub errors: Vec<RustyExError>,
+}
+
+/// Analyse a crate in-process and return the extracted data, instead of printing it.
+///
+/// `crate_root` is either the root file of the crate (e.g., `src/lib.rs`) or the directory of
+/// a package, whose library (or first binary) target is analysed. The printing options are
+/// ignored. The crate is compiled without `cargo`, so the items of its dependencies
+/// cannot be resolved and their weights are recovered (see `--recovery-weight`)
+pub fn analyze(crate_root: &Path, options: PrintAstArgs) -> Result<AnalysisResult> {
+    let compiler_args = compiler_args(crate_root)?;
+    let mut callbacks = PrintAstCallbacks::new(options, &compiler_args);
+    callbacks.keep_result = true;
+
+    // the compilation stops after the expansion, errors in the code do not matter
+    let _ = rustc_driver::catch_fatal_errors(|| {
+        rustc_driver::run_compiler(&compiler_args, &mut callbacks)
+    });
+    callbacks.result.ok_or(RustyExError::InvalidCrate {
+        path: crate_root.display().to_string(),
+        message: "the crate cannot be expanded".to_string(),
+    })
+}
+
+/// Arguments of the compiler to analyse the crate root file, or the main target of a package
+fn compiler_args(crate_root: &Path) -> Result<Vec<String>> {
+    let invalid_crate = |message: String| RustyExError::InvalidCrate {
+        path: crate_root.display().to_string(),
+        message,
+    };
+
+    let (src_path, crate_type, edition) = if crate_root.is_dir() {
+        let manifest_path = crate_root
+            .join("Cargo.toml")
+            .canonicalize()
+            .map_err(|error| invalid_crate(error.to_string()))?;
+        let metadata = cargo_metadata::MetadataCommand::new()
+            .manifest_path(&manifest_path)
+            .no_deps()
+            .other_options(["--offline".to_string()])
+            .exec()
+            .map_err(|error| invalid_crate(error.to_string()))?;
+        // the package of the directory (the metadata lists all the packages of the workspace)
+        let targets = &metadata
+            .packages
+            .iter()
+            .find(|package| package.manifest_path == manifest_path)
+            .ok_or_else(|| invalid_crate("not a package".to_string()))?
+            .targets;
+        let target = ["lib", "bin"]
+            .iter()
+            .find_map(|kind| targets.iter().find(|t| t.kind.iter().any(|k| k == kind)))
+            .ok_or_else(|| invalid_crate("no library or binary target".to_string()))?;
+        let crate_type = if target.kind.iter().any(|kind| kind == "bin") {
+            "bin"
+        } else {
+            "lib"
+        };
+        (
+            target.src_path.to_string(),
+            crate_type,
+            target.edition.clone(),
+        )
+    } else if crate_root.is_file() {
+        let crate_type = if crate_root.file_name().is_some_and(|name| name == "main.rs") {
+            "bin"
+        } else {
+            "lib"
+        };
+        (
+            crate_root.display().to_string(),
+            crate_type,
+            "2021".to_string(),
+        )
+    } else {
+        return Err(invalid_crate("no such file or directory".to_string()));
+    };
+
+    Ok(vec![
+        "rustc".to_string(),
+        src_path,
+        format!("--crate-type={crate_type}"),
+        format!("--edition={edition}"),
+    ])
+}
@@ -36,6 +36,7 @@ impl SuperCollector {
                 krate: GLOBAL_FEATURE_NAME.to_string(),
             },
             ident.clone(),
+            None,
             features.clone(),
             TermWeightKind::Children("Global".to_string()),
             TermWeight::ToBeCalculated,
@@ -65,6 +66,7 @@ impl SuperCollector {
                 krate: GLOBAL_FEATURE_NAME.to_string(),
             },
             ident,
+            None,
             ComplexFeature::Simple(feature.clone()),
             TermWeight::ToBeCalculated,
         );
@@ -126,10 +128,16 @@ impl SuperCollector {
                     krate: crate_name.clone(),
                 },
                 old_node.ident.clone(),
+                old_node.span.clone(),
                 old_node.features.clone(),
                 old_node.weight_kind.clone(),
                 old_node.weight.clone(),
             );
+            let new_node = &mut self.terms_tree.graph[new_node_index];
+            new_node.def_path = old_node.def_path.clone();
+            new_node.target = old_node.target.clone();
+            new_node.recovered = old_node.recovered;
+            new_node.estimated_weight = old_node.estimated_weight;
             index_map.insert(old_node_index, new_node_index);
         }
 
@@ -258,9 +266,11 @@ impl SuperCollector {
                     krate: crate_name.clone(),
                 },
                 old_node.ident.clone(),
+                old_node.span.clone(),
                 old_node.complex_feature.clone(),
                 old_node.weight.clone(),
             );
+            self.artifacts_tree.graph[new_node_index].estimated_weight = old_node.estimated_weight;
             index_map.insert(old_node_index, new_node_index);
         }
 
@@ -1,19 +1,28 @@
-use std::{collections::HashMap, fmt::Debug};
+use std::{cmp::Ordering, collections::HashMap, fmt::Debug};
 
 use petgraph::visit::EdgeRef;
+use petgraph::Direction;
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    types::{FeatureIndex, FeaturesGraph},
+    errors::RustyExError,
+    types::{CfgKind, Feature, FeatureIndex, FeaturesGraph},
     GLOBAL_DUMMY_INDEX, GLOBAL_NODE_INDEX,
 };
 
 /// The method to be selected for the centrality computation.
 /// It is meant to be used user-side.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub enum CentralityMethod {
+    #[default]
     Katz,
     Closeness,
     Eigenvector,
+    PageRank,
+    Betweenness,
+    InDegree,
+    OutDegree,
+    Harmonic,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -23,8 +32,69 @@ pub enum CentralityKind {
     Katz,
     Closeness,
     Eigenvector,
+    PageRank,
+    Betweenness,
+    InDegree,
+    OutDegree,
+    Harmonic,
+}
+
+/// Variant of the closeness centrality to be computed.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub enum ClosenessKind {
+    /// Newman's weighted closeness, using the edges weight as distance
+    #[default]
+    Weighted,
+    /// Closeness ignoring the edges weight
+    Unweighted,
+}
+
+/// Parameters of the centrality measures.
+///
+/// The `None` values fall back to the defaults of `rustworkx_core`.
+/// PageRank is not provided by `rustworkx_core`, so its parameters are always set.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CentralityParams {
+    /// Attenuation factor of Katz centrality
+    pub katz_alpha: Option<f64>,
+    /// Weight given to the immediate neighbourhood in Katz centrality
+    pub katz_beta: Option<f64>,
+    /// Maximum number of power iterations of Katz centrality
+    pub katz_max_iter: Option<usize>,
+    /// Error tolerance used to check the convergence of Katz centrality
+    pub katz_tolerance: Option<f64>,
+    /// Maximum number of power iterations of Eigenvector centrality
+    pub eigenvector_max_iter: Option<usize>,
+    /// Error tolerance used to check the convergence of Eigenvector centrality
+    pub eigenvector_tolerance: Option<f64>,
+    /// Variant of the closeness centrality
+    pub closeness: ClosenessKind,
+    /// Damping factor of PageRank (probability of following an edge)
+    pub pagerank_damping: f64,
+    /// Maximum number of power iterations of PageRank
+    pub pagerank_max_iter: usize,
+    /// Error tolerance used to check the convergence of PageRank
+    pub pagerank_tolerance: f64,
 }
 
+impl Default for CentralityParams {
+    fn default() -> Self {
+        Self {
+            katz_alpha: None,
+            katz_beta: None,
+            katz_max_iter: None,
+            katz_tolerance: None,
+            eigenvector_max_iter: None,
+            eigenvector_tolerance: Some(1e-2),
+            closeness: ClosenessKind::default(),
+            pagerank_damping: 0.85,
+            pagerank_max_iter: 100,
+            pagerank_tolerance: 1e-6,
+        }
+    }
+}
+
+/// Centrality measures of the features, each vector is ordered as `indices`
 #[derive(Default, Serialize, Deserialize, Debug)]
 pub struct Centrality<T>
 where
@@ -32,6 +102,53 @@ where
 {
     pub measures: CentralityMeasures,
     pub indices: Vec<T>,
+    /// Feature of each index
+    pub features: Vec<Feature>,
+    /// Weight of the artifacts of each feature, used to refine the measures
+    pub refined_weights: Vec<f64>,
+    /// Centrality of the Features Graph, where a real feature and its negation are distinct
+    /// nodes, used to split the measures of the Polarity Graph by polarity (only if asked)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub polarity_centrality: Option<Box<Centrality<T>>>,
+}
+
+/// Weight of the artifacts annotated by a real feature (positive) and by its negation (negative)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PolarityWeights {
+    pub positive: f64,
+    pub negative: f64,
+}
+
+/// Ranking measure of a real feature (positive) and of its negation (negative) in the Features
+/// Graph, where they are distinct nodes refined with the weights of their own artifacts. `None`
+/// if the crate does not use the polarity (or the measure has not been computed)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PolaritySplit {
+    pub positive: Option<f64>,
+    pub negative: Option<f64>,
+}
+
+/// Centrality measures of a single feature, with its refined weight and its rank
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeatureCentrality {
+    pub name: String,
+    pub not: bool,
+    /// Kind of `cfg` option, `None` if unknown (e.g., merged graphs)
+    pub cfg_kind: Option<CfgKind>,
+    pub katz: Option<f64>,
+    pub closeness: Option<f64>,
+    pub eigenvector: Option<f64>,
+    pub pagerank: Option<f64>,
+    pub betweenness: f64,
+    pub in_degree: f64,
+    pub out_degree: f64,
+    pub harmonic: f64,
+    pub refined_weight: f64,
+    /// Position (starting from 1) in the ranking by the selected measure
+    pub rank: usize,
+    /// Ranking measure split by polarity (only for the real features of the Polarity Graph)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub polarity: Option<PolaritySplit>,
 }
 
 #[derive(Default, Serialize, Deserialize, Debug)]
@@ -39,6 +156,27 @@ pub struct CentralityMeasures {
     pub katz: Option<Vec<f64>>,
     pub closeness: Vec<Option<f64>>,
     pub eigenvector: Option<Vec<f64>>,
+    pub pagerank: Option<Vec<f64>>,
+    /// Weighted betweenness, using the edges weight as distance
+    pub betweenness: Vec<f64>,
+    /// Sum of the weights of the incoming edges
+    pub in_degree: Vec<f64>,
+    /// Sum of the weights of the outgoing edges
+    pub out_degree: Vec<f64>,
+    /// Harmonic centrality, using the edges weight as distance
+    pub harmonic: Vec<f64>,
+}
+
+/// Result of a single source shortest paths visit (Dijkstra), as needed by Brandes algorithm
+struct ShortestPaths {
+    /// Distance from the source, `None` if not reachable
+    distances: Vec<Option<f64>>,
+    /// Number of shortest paths from the source
+    sigma: Vec<f64>,
+    /// Predecessors of each node on the shortest paths from the source
+    predecessors: Vec<Vec<usize>>,
+    /// Reached nodes, in non-decreasing distance from the source
+    order: Vec<usize>,
 }
 
 impl<T> Centrality<T>
@@ -46,19 +184,22 @@ where
     T: Clone,
 {
     /// Create a new Centrality struct with the centrality measures computed for the
-    /// FeaturesGraph. If remove_dummy is true, the dummy node is removed from the
-    /// centrality measures.
+    /// FeaturesGraph, using the given parameters. If remove_dummy is true, the dummy
+    /// node is removed from the centrality measures.
     ///
     /// NOTE: The dummy node is the node with the index GLOBAL_DUMMY_INDEX.
     /// If `remove_dummy_and_global` is false, you have to be sure when calling
     /// `refine` that the dummy node and the global node are not present in the
     /// refiner hashmap.
+    ///
+    /// The measures that cannot be computed are left empty, an error is returned for each of them.
     pub fn new(
         feat_graph: &FeaturesGraph,
         refiner_hm: &HashMap<FeatureIndex, f64>,
         cnf_mapping: &HashMap<String, T>,
         remove_dummy_and_global: bool,
-    ) -> Self {
+        params: &CentralityParams,
+    ) -> (Self, Vec<RustyExError>) {
         let node_indices = feat_graph.graph.node_indices();
         let feat_graph_indices = if remove_dummy_and_global {
             node_indices
@@ -71,18 +212,33 @@ where
             node_indices.collect::<Vec<FeatureIndex>>()
         };
 
+        let mut errors = Vec::new();
         let measures = CentralityMeasures {
-            katz: Centrality::<FeatureIndex>::compute_katz(feat_graph),
-            closeness: Centrality::<FeatureIndex>::compute_closeness(feat_graph),
-            eigenvector: Centrality::<FeatureIndex>::compute_eigenvector(feat_graph),
+            katz: Centrality::<FeatureIndex>::compute_katz(feat_graph, params)
+                .map_err(|e| errors.push(e))
+                .ok(),
+            closeness: Centrality::<FeatureIndex>::compute_closeness(feat_graph, params),
+            eigenvector: Centrality::<FeatureIndex>::compute_eigenvector(feat_graph, params)
+                .map_err(|e| errors.push(e))
+                .ok(),
+            pagerank: Centrality::<FeatureIndex>::compute_pagerank(feat_graph, params)
+                .map_err(|e| errors.push(e))
+                .ok(),
+            betweenness: Centrality::<FeatureIndex>::compute_betweenness(feat_graph),
+            in_degree: Centrality::<FeatureIndex>::compute_degree(feat_graph, Direction::Incoming),
+            out_degree: Centrality::<FeatureIndex>::compute_degree(feat_graph, Direction::Outgoing),
+            harmonic: Centrality::<FeatureIndex>::compute_harmonic(feat_graph),
         };
 
         let refined_centrality = Centrality::<FeatureIndex>::refine_with_art_map(
             &measures,
+            feat_graph,
             &feat_graph_indices,
             refiner_hm.clone(),
         );
-        Centrality::align_indices_with_cnf_map(refined_centrality, feat_graph, cnf_mapping)
+        let centrality =
+            Centrality::align_indices_with_cnf_map(refined_centrality, feat_graph, cnf_mapping);
+        (centrality, errors)
     }
 
     fn align_indices_with_cnf_map(
@@ -102,55 +258,63 @@ where
         Centrality {
             measures: centrality.measures,
             indices,
+            features: centrality.features,
+            refined_weights: centrality.refined_weights,
+            polarity_centrality: None,
         }
     }
 
     fn refine_with_art_map(
         calc_measures: &CentralityMeasures,
+        feat_graph: &FeaturesGraph,
         feat_graph_indices: &[FeatureIndex],
         refiner_hm: HashMap<FeatureIndex, f64>,
     ) -> Centrality<FeatureIndex> {
         let mut measures = CentralityMeasures::default();
 
         // They are ordered in the same way as the feat_graph_indices.
-        let refined_values: Vec<&f64> = feat_graph_indices
+        // Features without (resolved) artifacts have no weight.
+        let refined_values: Vec<f64> = feat_graph_indices
             .iter()
-            .map(|feature_index| refiner_hm.get(feature_index).unwrap())
+            .map(|feature_index| *refiner_hm.get(feature_index).unwrap_or(&0.0))
             .collect();
 
-        if let Some(katz) = &calc_measures.katz {
-            measures.katz = Some(
-                katz.iter()
-                    .zip(refined_values.iter())
-                    .map(|(katz, refined_value)| katz * *refined_value)
-                    .collect(),
-            );
-        }
+        // The computed measures contain all the nodes of the graph (global and dummy included),
+        // so they are accessed by index and not zipped with the selected indices
+        let refine = |values: &Vec<f64>| -> Vec<f64> {
+            feat_graph_indices
+                .iter()
+                .zip(refined_values.iter())
+                .map(|(index, refined_value)| values[index.index()] * refined_value)
+                .collect()
+        };
+
+        measures.katz = calc_measures.katz.as_ref().map(refine);
 
-        measures.closeness = calc_measures
-            .closeness
+        measures.closeness = feat_graph_indices
             .iter()
             .zip(refined_values.iter())
-            .map(|(closeness, refined_value)| {
-                closeness
-                    .as_ref()
-                    .map(|closeness| closeness * *refined_value)
+            .map(|(index, refined_value)| {
+                calc_measures.closeness[index.index()].map(|closeness| closeness * refined_value)
             })
             .collect();
 
-        if let Some(eigenvector) = &calc_measures.eigenvector {
-            measures.eigenvector = Some(
-                eigenvector
-                    .iter()
-                    .zip(refined_values.iter())
-                    .map(|(eigenvector, refined_value)| eigenvector * *refined_value)
-                    .collect(),
-            );
-        }
+        measures.eigenvector = calc_measures.eigenvector.as_ref().map(refine);
+        measures.pagerank = calc_measures.pagerank.as_ref().map(refine);
+        measures.betweenness = refine(&calc_measures.betweenness);
+        measures.in_degree = refine(&calc_measures.in_degree);
+        measures.out_degree = refine(&calc_measures.out_degree);
+        measures.harmonic = refine(&calc_measures.harmonic);
 
         Centrality {
             measures,
             indices: feat_graph_indices.to_vec(),
+            features: feat_graph_indices
+                .iter()
+                .map(|index| feat_graph.graph[*index].feature.0.clone())
+                .collect(),
+            refined_weights: refined_values,
+            polarity_centrality: None,
         }
     }
 
@@ -166,55 +330,457 @@ where
         self.measures.eigenvector.as_ref()
     }
 
-    fn compute_katz(feat_graph: &FeaturesGraph) -> Option<Vec<f64>> {
+    pub fn pagerank(&self) -> Option<&Vec<f64>> {
+        self.measures.pagerank.as_ref()
+    }
+
+    pub fn betweenness(&self) -> &Vec<f64> {
+        &self.measures.betweenness
+    }
+
+    pub fn in_degree(&self) -> &Vec<f64> {
+        &self.measures.in_degree
+    }
+
+    pub fn out_degree(&self) -> &Vec<f64> {
+        &self.measures.out_degree
+    }
+
+    pub fn harmonic(&self) -> &Vec<f64> {
+        &self.measures.harmonic
+    }
+
+    /// Values of the given measure, `None` if the measure (or the value) has not been computed
+    pub fn measure(&self, method: &CentralityMethod) -> Vec<Option<f64>> {
+        let values = match method {
+            CentralityMethod::Katz => self.katz(),
+            CentralityMethod::Closeness => return self.closeness().clone(),
+            CentralityMethod::Eigenvector => self.eigenvector(),
+            CentralityMethod::PageRank => self.pagerank(),
+            CentralityMethod::Betweenness => Some(self.betweenness()),
+            CentralityMethod::InDegree => Some(self.in_degree()),
+            CentralityMethod::OutDegree => Some(self.out_degree()),
+            CentralityMethod::Harmonic => Some(self.harmonic()),
+        };
+        match values {
+            Some(values) => values.iter().map(|value| Some(*value)).collect(),
+            None => vec![None; self.indices.len()],
+        }
+    }
+
+    /// Centrality of each feature, sorted by decreasing value of the given measure.
+    /// Features without a value for the measure are ranked last
+    pub fn ranked_features(
+        &self,
+        rank_by: &CentralityMethod,
+        cfg_kinds: &HashMap<String, CfgKind>,
+    ) -> Vec<FeatureCentrality> {
+        let value = |values: Option<&Vec<f64>>, i: usize| values.map(|values| values[i]);
+        let polarity_values = self
+            .polarity_centrality
+            .as_ref()
+            .map(|polarity_centrality| (polarity_centrality, polarity_centrality.measure(rank_by)));
+
+        let mut features: Vec<(Option<f64>, FeatureCentrality)> = self
+            .measure(rank_by)
+            .into_iter()
+            .enumerate()
+            .map(|(i, rank_value)| {
+                let feature = &self.features[i];
+                let centrality = FeatureCentrality {
+                    name: feature.name.clone(),
+                    not: feature.not,
+                    cfg_kind: cfg_kinds.get(&feature.name).cloned(),
+                    katz: value(self.katz(), i),
+                    closeness: self.closeness()[i],
+                    eigenvector: value(self.eigenvector(), i),
+                    pagerank: value(self.pagerank(), i),
+                    betweenness: self.betweenness()[i],
+                    in_degree: self.in_degree()[i],
+                    out_degree: self.out_degree()[i],
+                    harmonic: self.harmonic()[i],
+                    refined_weight: self.refined_weights[i],
+                    rank: 0, // set after sorting
+                    polarity: polarity_values
+                        .as_ref()
+                        .map(|(polarity_centrality, values)| {
+                            let polarity_value = |not: bool| {
+                                polarity_centrality
+                                    .features
+                                    .iter()
+                                    .position(|f| f.name == feature.name && f.not == not)
+                                    .and_then(|j| values[j])
+                            };
+                            PolaritySplit {
+                                positive: polarity_value(false),
+                                negative: polarity_value(true),
+                            }
+                        }),
+                };
+                (rank_value, centrality)
+            })
+            .collect();
+
+        features.sort_by(|(a, fa), (b, fb)| {
+            let by_value = match (a, b) {
+                (Some(a), Some(b)) => b.total_cmp(a),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            };
+            by_value
+                .then_with(|| fa.name.cmp(&fb.name))
+                .then_with(|| fa.not.cmp(&fb.not))
+        });
+
+        features
+            .into_iter()
+            .enumerate()
+            .map(|(i, (_, mut centrality))| {
+                centrality.rank = i + 1;
+                centrality
+            })
+            .collect()
+    }
+
+    fn compute_katz(
+        feat_graph: &FeaturesGraph,
+        params: &CentralityParams,
+    ) -> Result<Vec<f64>, RustyExError> {
         let katz: rustworkx_core::Result<Option<Vec<f64>>> =
             rustworkx_core::centrality::katz_centrality(
                 &feat_graph.graph,
                 |e| Ok(e.weight().weight),
+                params.katz_alpha,
                 None,
-                None,
-                None,
-                None,
-                None,
+                params.katz_beta,
+                params.katz_max_iter,
+                params.katz_tolerance,
             );
 
         match katz {
-            Ok(katz) => katz,
-            Err(e) => {
-                // TODO: Probabily we should return handle this error gracefully
-                panic!("Error computing katz centrality: {:?}", e);
-            }
+            Ok(Some(katz)) => Ok(katz),
+            Ok(None) => Err(RustyExError::Centrality {
+                measure: "katz".to_string(),
+                message: "power iteration did not converge".to_string(),
+            }),
+            Err(e) => Err(RustyExError::Centrality {
+                measure: "katz".to_string(),
+                message: format!("{:?}", e),
+            }),
         }
     }
 
     // The Option around the f64 is because the closeness centrality can fail
     // if the graph is not connected (i.e. there is a node that is not reachable
     // from all other nodes)
-    fn compute_closeness(feat_graph: &FeaturesGraph) -> Vec<Option<f64>> {
-        // The `closeness_centrality` does not work as expected when the network is weighted.
-        // rustworkx_core::centrality::closeness_centrality(&feat_graph.graph, true)
-        rustworkx_core::centrality::newman_weighted_closeness_centrality(
-            &feat_graph.graph,
-            true,
-            |e| e.weight().weight,
-        )
+    fn compute_closeness(
+        feat_graph: &FeaturesGraph,
+        params: &CentralityParams,
+    ) -> Vec<Option<f64>> {
+        match params.closeness {
+            ClosenessKind::Weighted => {
+                rustworkx_core::centrality::newman_weighted_closeness_centrality(
+                    &feat_graph.graph,
+                    true,
+                    |e| e.weight().weight,
+                )
+            }
+            // The `closeness_centrality` does not work as expected when the network is weighted,
+            // the weights are ignored.
+            ClosenessKind::Unweighted => {
+                rustworkx_core::centrality::closeness_centrality(&feat_graph.graph, true)
+            }
+        }
     }
 
-    fn compute_eigenvector(feat_graph: &FeaturesGraph) -> Option<Vec<f64>> {
+    fn compute_eigenvector(
+        feat_graph: &FeaturesGraph,
+        params: &CentralityParams,
+    ) -> Result<Vec<f64>, RustyExError> {
         let eigenvector: rustworkx_core::Result<Option<Vec<f64>>> =
             rustworkx_core::centrality::eigenvector_centrality(
                 &feat_graph.graph,
                 |e| Ok(e.weight().weight),
-                None,
-                Some(1e-2),
+                params.eigenvector_max_iter,
+                params.eigenvector_tolerance,
             );
 
         match eigenvector {
-            Ok(eigenvector) => eigenvector,
-            Err(e) => {
-                // TODO: Probabily we should return handle this error gracefully
-                panic!("Error computing eigenvector centrality: {:?}", e);
+            Ok(Some(eigenvector)) => Ok(eigenvector),
+            Ok(None) => Err(RustyExError::Centrality {
+                measure: "eigenvector".to_string(),
+                message: "power iteration did not converge".to_string(),
+            }),
+            Err(e) => Err(RustyExError::Centrality {
+                measure: "eigenvector".to_string(),
+                message: format!("{:?}", e),
+            }),
+        }
+    }
+
+    /// Weighted PageRank: the probability of following an outgoing edge is proportional to its weight.
+    /// Nodes without outgoing edges (the global feature) distribute their rank to all nodes
+    fn compute_pagerank(
+        feat_graph: &FeaturesGraph,
+        params: &CentralityParams,
+    ) -> Result<Vec<f64>, RustyExError> {
+        let graph = &feat_graph.graph;
+        let n = graph.node_count();
+        if n == 0 {
+            return Ok(Vec::new());
+        }
+
+        let out_weights: Vec<f64> = graph
+            .node_indices()
+            .map(|node| graph.edges(node).map(|e| e.weight().weight).sum())
+            .collect();
+
+        let damping = params.pagerank_damping;
+        let mut rank = vec![1.0 / n as f64; n];
+        for _ in 0..params.pagerank_max_iter {
+            let dangling: f64 = graph
+                .node_indices()
+                .filter(|node| out_weights[node.index()] == 0.0)
+                .map(|node| rank[node.index()])
+                .sum();
+
+            let mut new_rank = vec![(1.0 - damping + damping * dangling) / n as f64; n];
+            for edge in graph.edge_references() {
+                let source = edge.source().index();
+                // the rank of the nodes whose edges all weigh 0 is spread as a dangling node's
+                if out_weights[source] == 0.0 {
+                    continue;
+                }
+                new_rank[edge.target().index()] +=
+                    damping * rank[source] * edge.weight().weight / out_weights[source];
             }
+
+            let error: f64 = new_rank
+                .iter()
+                .zip(rank.iter())
+                .map(|(new, old)| (new - old).abs())
+                .sum();
+            rank = new_rank;
+            if error < n as f64 * params.pagerank_tolerance {
+                return Ok(rank);
+            }
+        }
+
+        Err(RustyExError::Centrality {
+            measure: "pagerank".to_string(),
+            message: "power iteration did not converge".to_string(),
+        })
+    }
+
+    /// Weighted betweenness (Brandes algorithm), normalized for directed graphs.
+    /// Parallel edges are collapsed keeping the lightest one
+    fn compute_betweenness(feat_graph: &FeaturesGraph) -> Vec<f64> {
+        let adjacency = Centrality::<FeatureIndex>::adjacency(feat_graph, Direction::Outgoing);
+        let n = adjacency.len();
+
+        let mut betweenness = vec![0.0; n];
+        for source in 0..n {
+            let paths = Centrality::<FeatureIndex>::shortest_paths(&adjacency, source);
+
+            let mut delta = vec![0.0; n];
+            for &node in paths.order.iter().rev() {
+                for &pred in &paths.predecessors[node] {
+                    delta[pred] += paths.sigma[pred] / paths.sigma[node] * (1.0 + delta[node]);
+                }
+                if node != source {
+                    betweenness[node] += delta[node];
+                }
+            }
+        }
+
+        if n > 2 {
+            let scale = 1.0 / ((n - 1) * (n - 2)) as f64;
+            betweenness.iter_mut().for_each(|b| *b *= scale);
         }
+        betweenness
+    }
+
+    /// Weighted degree: sum of the weights of the edges in the given direction
+    fn compute_degree(feat_graph: &FeaturesGraph, direction: Direction) -> Vec<f64> {
+        feat_graph
+            .graph
+            .node_indices()
+            .map(|node| {
+                feat_graph
+                    .graph
+                    .edges_directed(node, direction)
+                    .map(|e| e.weight().weight)
+                    .sum()
+            })
+            .collect()
+    }
+
+    /// Harmonic centrality: sum of the inverse distances from all the other nodes,
+    /// using the edges weight as distance
+    fn compute_harmonic(feat_graph: &FeaturesGraph) -> Vec<f64> {
+        // distances from all nodes to a node are the distances from the node in the reversed graph
+        let adjacency = Centrality::<FeatureIndex>::adjacency(feat_graph, Direction::Incoming);
+
+        (0..adjacency.len())
+            .map(|node| {
+                Centrality::<FeatureIndex>::shortest_paths(&adjacency, node)
+                    .distances
+                    .iter()
+                    .flatten()
+                    .filter(|distance| **distance > 0.0)
+                    .map(|distance| 1.0 / distance)
+                    .sum()
+            })
+            .collect()
+    }
+
+    /// Adjacency list of the graph (following the edges in the given direction),
+    /// parallel edges are collapsed keeping the lightest one
+    fn adjacency(feat_graph: &FeaturesGraph, direction: Direction) -> Vec<Vec<(usize, f64)>> {
+        let graph = &feat_graph.graph;
+        graph
+            .node_indices()
+            .map(|node| {
+                let mut neighbors: HashMap<usize, f64> = HashMap::new();
+                for edge in graph.edges_directed(node, direction) {
+                    let neighbor = match direction {
+                        Direction::Outgoing => edge.target(),
+                        Direction::Incoming => edge.source(),
+                    };
+                    let weight = neighbors.entry(neighbor.index()).or_insert(f64::INFINITY);
+                    *weight = weight.min(edge.weight().weight);
+                }
+                let mut neighbors: Vec<(usize, f64)> = neighbors.into_iter().collect();
+                neighbors.sort_by_key(|(neighbor, _)| *neighbor);
+                neighbors
+            })
+            .collect()
+    }
+
+    /// Single source shortest paths (Dijkstra), counting the number of shortest paths
+    fn shortest_paths(adjacency: &[Vec<(usize, f64)>], source: usize) -> ShortestPaths {
+        let n = adjacency.len();
+        let mut paths = ShortestPaths {
+            distances: vec![None; n],
+            sigma: vec![0.0; n],
+            predecessors: vec![Vec::new(); n],
+            order: Vec::new(),
+        };
+        paths.distances[source] = Some(0.0);
+        paths.sigma[source] = 1.0;
+
+        let mut visited = vec![false; n];
+        // the features graph is small, a linear search of the closest node is enough
+        while let Some((node, distance)) = paths
+            .distances
+            .iter()
+            .enumerate()
+            .filter(|(node, _)| !visited[*node])
+            .filter_map(|(node, distance)| distance.map(|d| (node, d)))
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        {
+            visited[node] = true;
+            paths.order.push(node);
+
+            for &(neighbor, weight) in &adjacency[node] {
+                let new_distance = distance + weight;
+                match paths.distances[neighbor] {
+                    Some(old_distance) if new_distance > old_distance => {}
+                    Some(old_distance) if new_distance == old_distance => {
+                        if !visited[neighbor] {
+                            paths.sigma[neighbor] += paths.sigma[node];
+                            paths.predecessors[neighbor].push(node);
+                        }
+                    }
+                    _ => {
+                        paths.distances[neighbor] = Some(new_distance);
+                        paths.sigma[neighbor] = paths.sigma[node];
+                        paths.predecessors[neighbor] = vec![node];
+                    }
+                }
+            }
+        }
+
+        paths
+    }
+}
+
+/// Human-readable table of the ranked features centrality, one feature per row
+pub fn ranked_table(features: &[FeatureCentrality]) -> String {
+    let value = |value: Option<f64>| value.map_or("-".to_string(), |v| format!("{:.4}", v));
+
+    let mut header = vec![
+        "rank",
+        "feature",
+        "kind",
+        "weight",
+        "katz",
+        "closeness",
+        "eigenvector",
+        "pagerank",
+        "betweenness",
+        "in-degree",
+        "out-degree",
+        "harmonic",
+    ];
+    let split = features.iter().any(|f| f.polarity.is_some());
+    if split {
+        header.extend(["positive", "negative"]);
+    }
+    let rows: Vec<Vec<String>> = features
+        .iter()
+        .map(|f| {
+            let mut row = vec![
+                f.rank.to_string(),
+                format!("{}{}", if f.not { "!" } else { "" }, f.name),
+                f.cfg_kind
+                    .as_ref()
+                    .map_or("-".to_string(), |kind| kind.to_string()),
+                value(Some(f.refined_weight)),
+                value(f.katz),
+                value(f.closeness),
+                value(f.eigenvector),
+                value(f.pagerank),
+                value(Some(f.betweenness)),
+                value(Some(f.in_degree)),
+                value(Some(f.out_degree)),
+                value(Some(f.harmonic)),
+            ];
+            if split {
+                let polarity = f.polarity.as_ref();
+                row.push(value(polarity.and_then(|polarity| polarity.positive)));
+                row.push(value(polarity.and_then(|polarity| polarity.negative)));
+            }
+            row
+        })
+        .collect();
+
+    let widths: Vec<usize> = header
+        .iter()
+        .enumerate()
+        .map(|(i, h)| {
+            rows.iter()
+                .map(|row| row[i].len())
+                .fold(h.len(), usize::max)
+        })
+        .collect();
+
+    let format_row = |cells: Vec<String>| -> String {
+        cells
+            .iter()
+            .zip(widths.iter())
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect::<Vec<String>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+
+    let mut table = format_row(header.iter().map(|h| h.to_string()).collect());
+    for row in rows {
+        table.push('\n');
+        table.push_str(&format_row(row));
     }
+    table
 }
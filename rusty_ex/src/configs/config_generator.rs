@@ -1,41 +1,115 @@
-use super::{config_solver::ConfigSolver, CnfFormula};
+use super::{config_solver::ConfigSolver, CnfClause, CnfFormula, CnfLit};
 use rustsat_minisat::core::Minisat;
+use std::collections::BTreeSet;
 
 /// This struct is used to generate the configurations for the given CNF formula.
 pub struct ConfigGenerator<S> {
-    /// The solver.
+    /// The solver, with the CNF formula loaded once.
     solver: ConfigSolver<S>,
-    /// The CNF formula.
-    cnf: CnfFormula<u32>,
-    /// Centrality indices.
-    indices: Vec<u32>,
-    /// The amount of configurations that must be generated.
-    amount: usize,
+    /// Literals of the features to enable (or disable, for negated features) with their
+    /// centrality, the most central first.
+    targets: Vec<(CnfLit<u32>, f64)>,
+    /// The variables assigned by each configuration: the ones of the formula and of the targets.
+    vars: BTreeSet<u32>,
+    /// The maximum number of configurations that must be generated.
+    budget: usize,
+    /// Whether some query of the solver has been interrupted, the configurations are partial.
+    interrupted: bool,
 }
 
 impl ConfigGenerator<Minisat> {
-    pub fn new(cnf: CnfFormula<u32>, indices: &[u32], amount: usize) -> Self {
-        let solver = ConfigSolver::default();
+    /// The `targets` are the literals of the features with their centrality (`None` if it has
+    /// not been computed), in any order: they are sorted by decreasing centrality.
+    pub fn new(
+        cnf: CnfFormula<u32>,
+        targets: &[(CnfLit<u32>, Option<f64>)],
+        budget: usize,
+    ) -> Self {
+        // features without a value for the measure are the last ones, in the original order
+        let mut targets = targets.to_vec();
+        targets.sort_by(|(_, a), (_, b)| match (a, b) {
+            (Some(a), Some(b)) => b.total_cmp(a),
+            (Some(_), None) => std::cmp::Ordering::Less,
+            (None, Some(_)) => std::cmp::Ordering::Greater,
+            (None, None) => std::cmp::Ordering::Equal,
+        });
+
+        let vars = cnf
+            .iter()
+            .flatten()
+            .chain(targets.iter().map(|(lit, _)| lit))
+            .map(|(var, _)| *var)
+            .collect();
+        let mut solver = ConfigSolver::default();
+        solver.add_cnf(cnf);
         Self {
             solver,
-            cnf,
-            indices: indices.to_vec(),
-            amount,
+            vars,
+            targets: targets
+                .into_iter()
+                .map(|(lit, value)| (lit, value.unwrap_or(0.0)))
+                .collect(),
+            budget,
+            interrupted: false,
         }
     }
 
-    /// Generate the configurations.
-    pub fn generate(&mut self) -> Vec<CnfFormula<u32>> {
+    /// Whether some query of the last `generate` has been interrupted: the features it checked
+    /// are considered not coverable.
+    pub fn interrupted(&self) -> bool {
+        self.interrupted
+    }
+
+    /// Generate at most `budget` configurations, greedily maximising the centrality of the
+    /// covered features (enabled, or disabled if negated, by the configurations).
+    ///
+    /// Each configuration covers the most central uncovered feature and then as many of the
+    /// others as the formula allows, from the most central one. Features that no configuration
+    /// can cover are skipped. Each configuration assigns every variable of the CNF formula, as
+    /// `(var, true)` if enabled and `(var, false)` otherwise.
+    ///
+    /// The features are solved under assumptions, so the formula is never reloaded and the
+    /// queries do not affect each other (nor the next calls).
+    pub fn generate(&mut self) -> CnfFormula<u32> {
+        self.interrupted = false;
+        let mut uncovered = Vec::new();
+        for target in self.targets.clone() {
+            if self.is_satisfiable_assuming(&[target.0]) {
+                uncovered.push(target);
+            }
+        }
+
         let mut configs = Vec::new();
-        for index in &self.indices {
-            if configs.len() >= self.amount {
+        while configs.len() < self.budget && !uncovered.is_empty() {
+            let mut assumptions = Vec::new();
+            for (lit, _) in &uncovered {
+                assumptions.push(*lit);
+                if !self.is_satisfiable_assuming(&assumptions) {
+                    assumptions.pop();
+                }
+            }
+            // solved again for the model, the last query may have been unsatisfiable
+            if assumptions.is_empty() || !self.is_satisfiable_assuming(&assumptions) {
                 break;
             }
-            let var = (*index, true);
-            self.solver.add_cnf(self.cnf.clone());
-            let sol: CnfFormula<u32> = self.solver.all_configs_given_a_var(vec![var]);
-            configs.push(sol);
+
+            let true_vars = self.solver.true_vars();
+            let config = self
+                .vars
+                .iter()
+                .map(|var| (*var, true_vars.contains(var)))
+                .collect::<CnfClause<u32>>();
+            // the features covered by chance are covered too
+            uncovered.retain(|(lit, _)| !config.contains(lit));
+            configs.push(config);
         }
         configs
     }
+
+    /// Whether the formula can be satisfied with the assumptions, `false` if interrupted.
+    fn is_satisfiable_assuming(&mut self, assumptions: &[CnfLit<u32>]) -> bool {
+        let satisfiable = self.solver.is_satisfiable_assuming(assumptions);
+        self.interrupted |= satisfiable.is_none();
+        satisfiable.unwrap_or(false)
+    }
 }
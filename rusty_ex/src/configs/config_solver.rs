@@ -1,13 +1,20 @@
 use rustsat::{
-    solvers::{Solve, SolveStats, SolverResult},
+    solvers::{ControlSignal, Solve, SolveIncremental, SolveStats, SolverResult, Terminate},
     types::{Clause, Lit, TernaryVal},
 };
 use rustsat_minisat::core::Minisat;
+use std::collections::HashSet;
+use std::time::Instant;
 
 use super::{CnfClause, CnfFormula, CnfLit};
 
 pub struct ConfigSolver<S> {
     solver: S,
+    /// First variable not used by the clauses and the assumptions so far.
+    next_var: u32,
+    /// Variables activating the blocking clauses of the past enumerations, they are not part of
+    /// the configurations.
+    activations: HashSet<u32>,
 }
 
 impl Default for ConfigSolver<Minisat> {
@@ -21,7 +28,11 @@ where
     S: Solve + SolveStats,
 {
     fn new(solver: S) -> Self {
-        Self { solver }
+        Self {
+            solver,
+            next_var: 0,
+            activations: HashSet::new(),
+        }
     }
 
     /// Add a clause to the solver.
@@ -32,6 +43,7 @@ where
     fn add_clause(&mut self, clause: CnfClause<u32>) -> &mut Self {
         let mut c = Clause::new();
         for (var, neg) in clause {
+            self.next_var = self.next_var.max(var + 1);
             c.add(Lit::new(var, neg));
         }
         self.solver.add_clause(c).unwrap();
@@ -48,6 +60,48 @@ where
             self.add_clause(clause);
         }
     }
+
+    /// Whether the clauses added so far can be satisfied by some configuration: `None` if the
+    /// solver is interrupted before the answer.
+    pub fn is_satisfiable(&mut self) -> Option<bool> {
+        match self.solver.solve().unwrap() {
+            SolverResult::Sat => Some(true),
+            SolverResult::Unsat => Some(false),
+            SolverResult::Interrupted => None,
+        }
+    }
+
+    /// Variables assigned to true by the last solution, i.e., `x` for which the literal `(x, true)`
+    /// holds. To be called after a satisfiable solve.
+    pub fn true_vars(&mut self) -> HashSet<u32> {
+        let sol = self.solver.full_solution().unwrap();
+        sol.iter()
+            .map(|lit| lit.vidx32())
+            .filter(|var| !self.activations.contains(var))
+            .filter(|var| sol.lit_value(Lit::new(*var, true)) == TernaryVal::True)
+            .collect()
+    }
+
+    /// The last solution as a configuration (without the activation variables).
+    /// To be called after a satisfiable solve.
+    fn solution(&mut self) -> CnfClause<u32> {
+        let sol = self.solver.full_solution().unwrap();
+        sol.iter()
+            .filter(|lit| !self.activations.contains(&lit.vidx32()))
+            .map(|lit| match sol[lit.var()] {
+                TernaryVal::True => (lit.vidx32(), true),
+                TernaryVal::False => (lit.vidx32(), false),
+                TernaryVal::DontCare => {
+                    // The formula is satisfied.
+                    // For now, we don't handle this case because
+                    // we are only interested in the configurations that satisfy the
+                    // given variables.
+                    panic!("Unexpected DontCare")
+                }
+            })
+            .collect()
+    }
+
     /// This function finds all the configurations that satisfy a set of variables.
     ///
     /// For instance:
@@ -55,6 +109,9 @@ where
     /// all the configurations that satisfy `x0`.
     /// [(0, false), (1, true)] is the variable `x0` that must be false and `x1` that must be true,
     /// the function fill return all the configurations that satisfy `!x0 & x1`.
+    ///
+    /// The variables and the blocking clauses are added to the solver for good, so it cannot be
+    /// used for other queries (see `all_configs_assuming`).
     pub fn all_configs_given_a_var(&mut self, vars: Vec<CnfLit<u32>>) -> CnfFormula<u32> {
         // Set the variable to the given value.
         for var in vars {
@@ -67,21 +124,7 @@ where
         loop {
             match self.solver.solve().unwrap() {
                 SolverResult::Sat => {
-                    let sol = self.solver.full_solution().unwrap();
-                    let config = sol
-                        .iter()
-                        .map(|lit| match sol[lit.var()] {
-                            TernaryVal::True => (lit.vidx32(), true),
-                            TernaryVal::False => (lit.vidx32(), false),
-                            TernaryVal::DontCare => {
-                                // The formula is satisfied.
-                                // For now, we don't handle this case because
-                                // we are only interested in the configurations that satisfy the
-                                // given variables.
-                                panic!("Unexpected DontCare")
-                            }
-                        })
-                        .collect::<Vec<(u32, bool)>>();
+                    let config = self.solution();
                     all_configs.push(config.clone());
 
                     // Add the negation of the current configuration.
@@ -101,6 +144,81 @@ where
     }
 }
 
+impl<S> ConfigSolver<S>
+where
+    S: SolveIncremental + SolveStats,
+{
+    /// Whether the clauses added so far can be satisfied together with the given literals, which
+    /// are not added to the solver (e.g., `[(0, true)]` checks the clauses with `x0`): `None` if
+    /// the solver is interrupted before the answer.
+    pub fn is_satisfiable_assuming(&mut self, assumptions: &[CnfLit<u32>]) -> Option<bool> {
+        let assumptions = assumptions
+            .iter()
+            .map(|(var, neg)| {
+                self.next_var = self.next_var.max(var + 1);
+                Lit::new(*var, *neg)
+            })
+            .collect::<Vec<_>>();
+        match self.solver.solve_assumps(&assumptions).unwrap() {
+            SolverResult::Sat => Some(true),
+            SolverResult::Unsat => Some(false),
+            SolverResult::Interrupted => None,
+        }
+    }
+
+    /// Like `all_configs_given_a_var`, but the given literals are assumed and the blocking clauses
+    /// only hold during the enumeration: the other queries on the solver are not affected.
+    ///
+    /// The blocking clauses are activated by a new variable, which must not be used by the
+    /// clauses added later.
+    pub fn all_configs_assuming(&mut self, assumptions: &[CnfLit<u32>]) -> CnfFormula<u32> {
+        let mut assumptions = assumptions.to_vec();
+        self.next_var = assumptions
+            .iter()
+            .fold(self.next_var, |next_var, (var, _)| next_var.max(var + 1));
+        let activation = self.next_var;
+        self.activations.insert(activation);
+        assumptions.push((activation, true));
+
+        // An interrupted query ends the enumeration, the configurations found so far are kept.
+        let mut all_configs = Vec::new();
+        while self.is_satisfiable_assuming(&assumptions) == Some(true) {
+            let config = self.solution();
+            // Block the current configuration, while the activation variable is assumed.
+            let mut blocking = config.clone();
+            blocking.push((activation, false));
+            self.add_clause(blocking);
+            all_configs.push(config);
+        }
+
+        // Disable the blocking clauses for good.
+        self.add_clause(vec![(activation, false)]);
+        all_configs
+    }
+}
+
+impl<S> ConfigSolver<S>
+where
+    S: Solve + SolveStats + Terminate<'static>,
+{
+    /// Like `is_satisfiable`, but the solver is interrupted at the deadline: `None` if it is
+    /// interrupted before the answer.
+    pub fn is_satisfiable_before(&mut self, deadline: Instant) -> Option<bool> {
+        self.solver
+            .attach_terminator(move || match Instant::now() < deadline {
+                true => ControlSignal::Continue,
+                false => ControlSignal::Terminate,
+            });
+        let result = self.solver.solve().unwrap();
+        self.solver.detach_terminator();
+        match result {
+            SolverResult::Sat => Some(true),
+            SolverResult::Unsat => Some(false),
+            SolverResult::Interrupted => None,
+        }
+    }
+}
+
 /// ZST for ConfigGenerator
 ///
 /// We need this struct to implement the static function for the `ConfigGenerator`.
@@ -2,6 +2,7 @@ pub mod centrality;
 pub mod config_generator;
 pub mod config_solver;
 pub mod prop_formula;
+pub mod prop_formula_syntax;
 
 pub type CnfLit<T> = (T, bool);
 pub type CnfClause<T> = Vec<CnfLit<T>>;
@@ -235,6 +235,8 @@ impl<T: Clone + Debug + Eq + Hash> PropFormula<T> {
                                 new_v.push(v.clone())
                             },
                             Var(_) => new_v.push(vec![f.clone()]),
+                            // a nested disjunction is a single disjunct (see `flatten`)
+                            Or(_) => new_v.push(vec![f.clone()]),
                                 Not(v) => {
                                     assert!(matches!(**v, Var(_)));
                                     new_v.push(vec![f.clone()]);
@@ -282,6 +284,45 @@ impl<T: Clone + Debug + Eq + Hash> PropFormula<T> {
         }
     }
 
+    /// Merge nested conjunctions and nested disjunctions.
+    ///
+    /// For instance:
+    /// P & (Q & R) is equivalent to P & Q & R
+    /// P | (Q | R) is equivalent to P | Q | R
+    pub fn flatten(&mut self) {
+        use PropFormula::*;
+        match self {
+            Not(p) => p.flatten(),
+            And(v) => {
+                let mut flat = Vec::new();
+                for mut f in std::mem::take(v) {
+                    f.flatten();
+                    match f {
+                        And(inner) => flat.extend(inner),
+                        f => flat.push(f),
+                    }
+                }
+                *v = flat;
+            }
+            Or(v) => {
+                let mut flat = Vec::new();
+                for mut f in std::mem::take(v) {
+                    f.flatten();
+                    match f {
+                        Or(inner) => flat.extend(inner),
+                        f => flat.push(f),
+                    }
+                }
+                *v = flat;
+            }
+            Implies(p, q) | Iff(p, q) => {
+                p.flatten();
+                q.flatten();
+            }
+            Var(_) | None => {}
+        }
+    }
+
     /// Convert the propositional formula to CNF.
     pub fn to_cnf(&mut self) {
         self.eliminate_iff();
@@ -290,6 +331,214 @@ impl<T: Clone + Debug + Eq + Hash> PropFormula<T> {
         self.distribute_disjunction_over_conjunction();
     }
 
+    /// Convert the propositional formula to CNF, flattening nested conjunctions and disjunctions
+    /// (e.g., `all(a, all(b, c))`), so that the result is a conjunction of disjunctions of literals.
+    pub fn to_flat_cnf(&mut self) {
+        self.eliminate_iff();
+        self.eliminate_implies();
+        self.push_negation_inwards();
+        loop {
+            let prev = self.clone();
+            self.flatten();
+            self.distribute_disjunction_over_conjunction();
+            if prev == *self {
+                break;
+            }
+        }
+    }
+
+    /// Number of connectives and variables of the formula.
+    pub fn size(&self) -> usize {
+        use PropFormula::*;
+        match self {
+            Var(_) | None => 1,
+            Not(p) => 1 + p.size(),
+            And(v) | Or(v) => 1 + v.iter().map(|f| f.size()).sum::<usize>(),
+            Implies(p, q) | Iff(p, q) => 1 + p.size() + q.size(),
+        }
+    }
+
+    /// Variables of the formula, in order of first occurrence.
+    pub fn vars(&self) -> Vec<T> {
+        fn inner<T: Clone + PartialEq>(f: &PropFormula<T>, vars: &mut Vec<T>) {
+            use PropFormula::*;
+            match f {
+                Var(var) => {
+                    if !vars.contains(var) {
+                        vars.push(var.clone());
+                    }
+                }
+                Not(p) => inner(p, vars),
+                And(v) | Or(v) => {
+                    for f in v {
+                        inner(f, vars);
+                    }
+                }
+                Implies(p, q) | Iff(p, q) => {
+                    inner(p, vars);
+                    inner(q, vars);
+                }
+                None => {}
+            }
+        }
+
+        let mut vars = Vec::new();
+        inner(self, &mut vars);
+        vars
+    }
+
+    /// Fold the constants, merge nested conjunctions and disjunctions and remove duplicated
+    /// operands. The empty conjunction is `true`, the empty disjunction is `false`.
+    ///
+    /// For instance:
+    /// P & (Q & true) & P is equivalent to P & Q
+    /// P | (Q & false) is equivalent to P
+    /// !false is equivalent to true
+    pub fn fold_constants(&mut self) {
+        fn is_true<T>(f: &PropFormula<T>) -> bool {
+            matches!(f, PropFormula::And(v) if v.is_empty())
+        }
+        fn is_false<T>(f: &PropFormula<T>) -> bool {
+            matches!(f, PropFormula::Or(v) if v.is_empty())
+        }
+        // Operands of a conjunction (`absorbing` is false) or of a disjunction (`absorbing` is true)
+        fn fold<T: Clone + PartialEq>(
+            v: Vec<PropFormula<T>>,
+            absorbing: fn(&PropFormula<T>) -> bool,
+            neutral: fn(&PropFormula<T>) -> bool,
+        ) -> Option<Vec<PropFormula<T>>> {
+            let mut folded = Vec::new();
+            for mut f in v {
+                inner(&mut f);
+                if absorbing(&f) {
+                    return Option::None;
+                }
+                if !neutral(&f) && !folded.contains(&f) {
+                    folded.push(f);
+                }
+            }
+            Some(folded)
+        }
+        fn inner<T: Clone + PartialEq>(f: &mut PropFormula<T>) {
+            use PropFormula::*;
+            match f {
+                Var(_) | None => {}
+                Not(p) => {
+                    inner(p);
+                    if is_true(p) {
+                        *f = Or(vec![]);
+                    } else if is_false(p) {
+                        *f = And(vec![]);
+                    }
+                }
+                And(v) => match fold(std::mem::take(v), is_false, is_true) {
+                    Some(mut v) if v.len() == 1 => *f = v.remove(0),
+                    Some(v) => *f = And(v),
+                    Option::None => *f = Or(vec![]),
+                },
+                Or(v) => match fold(std::mem::take(v), is_true, is_false) {
+                    Some(mut v) if v.len() == 1 => *f = v.remove(0),
+                    Some(v) => *f = Or(v),
+                    Option::None => *f = And(vec![]),
+                },
+                Implies(p, q) => {
+                    inner(p);
+                    inner(q);
+                    if is_false(p) || is_true(q) {
+                        *f = And(vec![]);
+                    } else if is_true(p) {
+                        *f = *q.clone();
+                    }
+                }
+                Iff(p, q) => {
+                    inner(p);
+                    inner(q);
+                }
+            }
+        }
+
+        loop {
+            let prev = self.clone();
+            self.flatten();
+            inner(self);
+            if prev == *self {
+                break;
+            }
+        }
+    }
+
+    /// Simplify the formula (see `fold_constants`). If the formula is in CNF, it also removes the
+    /// tautologies and the duplicated and subsumed clauses and applies unit propagation (the
+    /// fixed literals are kept as unit clauses). The simplified formula has the same models as
+    /// the original one, so it can be used to enumerate the configurations. A CNF stays a
+    /// conjunction of clauses (`to_cnf_repr` expects it); the variables of the removed clauses
+    /// may not appear anymore.
+    ///
+    /// For instance:
+    /// P & (P | Q) & (!P | R) is simplified to P & R
+    pub fn simplify(&mut self) {
+        self.simplify_with(false);
+    }
+
+    /// Simplify the formula (see `simplify`) and fix the pure literals (a variable with a single
+    /// polarity satisfies its clauses). Every model of the simplified formula is a model of the
+    /// original one, and the simplified formula is satisfiable if and only if the original one
+    /// is, but some models are lost: use it only to check the satisfiability.
+    ///
+    /// For instance:
+    /// (P | Q) & (!P | Q) is simplified to Q
+    pub fn eliminate_pure_literals(&mut self) {
+        self.simplify_with(true);
+    }
+
+    fn simplify_with(&mut self, pure_literals: bool) {
+        self.fold_constants();
+        let Some(mut clauses) = self.clauses() else {
+            return;
+        };
+        simplify_clauses(&mut clauses, pure_literals);
+
+        let literal = |(var, positive): (T, bool)| match positive {
+            true => PropFormula::Var(var),
+            false => PropFormula::Not(bx!(PropFormula::Var(var))),
+        };
+        *self = PropFormula::And(
+            clauses
+                .into_iter()
+                .map(|mut clause| match clause.len() {
+                    1 => literal(clause.remove(0)),
+                    _ => PropFormula::Or(clause.into_iter().map(literal).collect()),
+                })
+                .collect(),
+        );
+    }
+
+    /// Clauses of the formula, if it is in CNF: `(x, true)` is `x`, `(x, false)` is `!x`.
+    fn clauses(&self) -> Option<Vec<Vec<(T, bool)>>> {
+        use PropFormula::*;
+        fn literal<T: Clone>(f: &PropFormula<T>) -> Option<(T, bool)> {
+            match f {
+                Var(var) => Some((var.clone(), true)),
+                Not(p) => match &**p {
+                    Var(var) => Some((var.clone(), false)),
+                    _ => Option::None,
+                },
+                _ => Option::None,
+            }
+        }
+        fn clause<T: Clone>(f: &PropFormula<T>) -> Option<Vec<(T, bool)>> {
+            match f {
+                Or(v) => v.iter().map(literal).collect(),
+                f => literal(f).map(|literal| vec![literal]),
+            }
+        }
+
+        match self {
+            And(v) => v.iter().map(clause).collect(),
+            f => clause(f).map(|clause| vec![clause]),
+        }
+    }
+
     /// Convert the propositional formula to CNF representation.
     ///
     /// It calls the `to_cnf` function first. So, it is safe to call this function directly.
@@ -400,3 +649,92 @@ impl<T: Clone + Debug + Eq + Hash> PropFormula<T> {
         }
     }
 }
+
+/// Simplify the clauses of a CNF until a fixed point, fixing the pure literals only if
+/// `pure_literals` (see `PropFormula::simplify`). An unsatisfiable CNF becomes the empty clause.
+fn simplify_clauses<T: Clone + Eq + Hash>(clauses: &mut Vec<Vec<(T, bool)>>, pure_literals: bool) {
+    let negated = |(var, positive): &(T, bool)| (var.clone(), !positive);
+
+    loop {
+        let prev = clauses.clone();
+
+        // duplicated literals and tautologies
+        for clause in clauses.iter_mut() {
+            let mut literals = Vec::new();
+            for literal in clause.drain(..) {
+                if !literals.contains(&literal) {
+                    literals.push(literal);
+                }
+            }
+            *clause = literals;
+        }
+        clauses.retain(|clause| !clause.iter().any(|l| clause.contains(&negated(l))));
+
+        // unit propagation, the unit clauses are kept
+        let units = clauses
+            .iter()
+            .filter(|clause| clause.len() == 1)
+            .map(|clause| clause[0].clone())
+            .collect::<Vec<_>>();
+        for unit in &units {
+            let (unit_clause, negated_unit) = (vec![unit.clone()], negated(unit));
+            clauses.retain(|clause| *clause == unit_clause || !clause.contains(unit));
+            for clause in clauses.iter_mut() {
+                clause.retain(|literal| *literal != negated_unit);
+            }
+        }
+
+        // duplicated and subsumed clauses, the shortest are kept
+        let mut by_length = (0..clauses.len()).collect::<Vec<_>>();
+        by_length.sort_by_key(|i| clauses[*i].len());
+        let mut kept: Vec<usize> = Vec::new();
+        for i in by_length {
+            let subsumed = kept.iter().any(|k| {
+                clauses[*k]
+                    .iter()
+                    .all(|literal| clauses[i].contains(literal))
+            });
+            if !subsumed {
+                kept.push(i);
+            }
+        }
+        kept.sort();
+        *clauses = kept.into_iter().map(|i| clauses[i].clone()).collect();
+
+        // pure literals in clauses that are not units (models are lost)
+        if pure_literals {
+            fix_pure_literal(clauses);
+        }
+
+        if clauses.iter().any(|clause| clause.is_empty()) {
+            *clauses = vec![vec![]];
+            break;
+        }
+        if prev == *clauses {
+            break;
+        }
+    }
+}
+
+/// Fix a pure literal of the clauses that are not units (a variable with a single polarity):
+/// it satisfies its clauses, which are replaced by the unit clause
+fn fix_pure_literal<T: Clone + Eq + Hash>(clauses: &mut Vec<Vec<(T, bool)>>) {
+    let mut polarities: HashMap<T, (bool, bool)> = HashMap::new();
+    for (var, positive) in clauses.iter().flatten() {
+        let polarity = polarities.entry(var.clone()).or_default();
+        match positive {
+            true => polarity.0 = true,
+            false => polarity.1 = true,
+        }
+    }
+    let pure = clauses
+        .iter()
+        .filter(|clause| clause.len() > 1)
+        .flatten()
+        .find(|(var, _)| !matches!(polarities[var], (true, true)))
+        .cloned();
+    if let Some(pure) = pure {
+        clauses.retain(|clause| !clause.contains(&pure));
+        clauses.push(vec![pure]);
+    }
+}
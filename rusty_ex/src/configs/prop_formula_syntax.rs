@@ -0,0 +1,380 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use super::prop_formula::PropFormula;
+use crate::utils::bx;
+
+/// Token of the textual syntax of propositional formulas
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Str(String),
+    Not,
+    And,
+    Or,
+    Implies,
+    Iff,
+    LParen,
+    RParen,
+    Comma,
+    Eq,
+}
+
+impl Display for Token {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Ident(ident) => write!(f, "{}", ident),
+            Token::Str(value) => write!(f, "\"{}\"", escape(value)),
+            Token::Not => write!(f, "!"),
+            Token::And => write!(f, "&"),
+            Token::Or => write!(f, "|"),
+            Token::Implies => write!(f, "->"),
+            Token::Iff => write!(f, "<->"),
+            Token::LParen => write!(f, "("),
+            Token::RParen => write!(f, ")"),
+            Token::Comma => write!(f, ","),
+            Token::Eq => write!(f, "="),
+        }
+    }
+}
+
+fn tokenize(s: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        let token = match c {
+            c if c.is_whitespace() => continue,
+            '!' => Token::Not,
+            '&' => Token::And,
+            '|' => Token::Or,
+            '(' => Token::LParen,
+            ')' => Token::RParen,
+            ',' => Token::Comma,
+            '=' => Token::Eq,
+            '-' if chars.next_if_eq(&'>').is_some() => Token::Implies,
+            '<' if chars.next_if_eq(&'-').is_some() && chars.next_if_eq(&'>').is_some() => {
+                Token::Iff
+            }
+            '"' => {
+                let mut value = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => value.extend(chars.next()),
+                        Some(c) => value.push(c),
+                        None => return Err(format!("unterminated string \"{}", value)),
+                    }
+                }
+                Token::Str(value)
+            }
+            c if is_ident_char(c) => {
+                let mut ident = c.to_string();
+                while let Some(c) = chars.peek().copied() {
+                    // `a->b` is an implication
+                    if !is_ident_char(c) || (c == '-' && chars.clone().nth(1) == Some('>')) {
+                        break;
+                    }
+                    ident.push(c);
+                    chars.next();
+                }
+                Token::Ident(ident)
+            }
+            c => return Err(format!("unexpected character `{}`", c)),
+        };
+        tokens.push(token);
+    }
+    Ok(tokens)
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '-'
+}
+
+/// Whether the name can be written without quotes
+fn is_ident(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_alphabetic() || c == '_')
+        && chars.all(is_ident_char)
+        && !name.contains("->")
+        && !name.ends_with('-')
+        && !["true", "false"].contains(&name)
+}
+
+fn escape(name: &str) -> String {
+    name.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Recursive descent parser, from the operator with the lowest precedence
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Result<Token, String> {
+        let token = self
+            .tokens
+            .get(self.pos)
+            .cloned()
+            .ok_or("unexpected end of formula".to_string())?;
+        self.pos += 1;
+        Ok(token)
+    }
+
+    fn eat(&mut self, token: &Token) -> bool {
+        let found = self.peek() == Some(token);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn expect(&mut self, expected: &Token) -> Result<(), String> {
+        match self.next()? {
+            token if token == *expected => Ok(()),
+            token => Err(format!("expected `{}`, found `{}`", expected, token)),
+        }
+    }
+
+    /// `p <-> q`, left associative
+    fn iff(&mut self) -> Result<PropFormula<String>, String> {
+        let mut formula = self.implies()?;
+        while self.eat(&Token::Iff) {
+            formula = PropFormula::Iff(bx!(formula), bx!(self.implies()?));
+        }
+        Ok(formula)
+    }
+
+    /// `p -> q`, right associative
+    fn implies(&mut self) -> Result<PropFormula<String>, String> {
+        let formula = self.or()?;
+        match self.eat(&Token::Implies) {
+            true => Ok(PropFormula::Implies(bx!(formula), bx!(self.implies()?))),
+            false => Ok(formula),
+        }
+    }
+
+    fn or(&mut self) -> Result<PropFormula<String>, String> {
+        let mut operands = vec![self.and()?];
+        while self.eat(&Token::Or) {
+            operands.push(self.and()?);
+        }
+        Ok(match operands.len() {
+            1 => operands.remove(0),
+            _ => PropFormula::Or(operands),
+        })
+    }
+
+    fn and(&mut self) -> Result<PropFormula<String>, String> {
+        let mut operands = vec![self.not()?];
+        while self.eat(&Token::And) {
+            operands.push(self.not()?);
+        }
+        Ok(match operands.len() {
+            1 => operands.remove(0),
+            _ => PropFormula::And(operands),
+        })
+    }
+
+    fn not(&mut self) -> Result<PropFormula<String>, String> {
+        match self.eat(&Token::Not) {
+            true => Ok(PropFormula::Not(bx!(self.not()?))),
+            false => self.atom(),
+        }
+    }
+
+    fn atom(&mut self) -> Result<PropFormula<String>, String> {
+        match self.next()? {
+            Token::LParen => {
+                let formula = self.iff()?;
+                self.expect(&Token::RParen)?;
+                Ok(formula)
+            }
+            Token::Str(name) => Ok(PropFormula::Var(name)),
+            Token::Ident(ident) if self.peek() == Some(&Token::LParen) => {
+                self.pos += 1;
+                let mut operands = Vec::new();
+                while !self.eat(&Token::RParen) {
+                    operands.push(self.iff()?);
+                    if !self.eat(&Token::Comma) {
+                        self.expect(&Token::RParen)?;
+                        break;
+                    }
+                }
+                match ident.as_str() {
+                    "all" => Ok(PropFormula::And(operands)),
+                    "any" => Ok(PropFormula::Or(operands)),
+                    "not" if operands.len() == 1 => Ok(PropFormula::Not(bx!(operands.remove(0)))),
+                    "not" => Err("`not` takes a single operand".to_string()),
+                    _ => Err(format!("unknown predicate `{}`", ident)),
+                }
+            }
+            // `feature = "a"` is the feature `a`, only the name of the other options is kept
+            // (e.g., `target_os` for `target_os = "linux"`), as in the analysed `cfg`s
+            Token::Ident(ident) if self.eat(&Token::Eq) => match self.next()? {
+                Token::Str(value) if ident == "feature" => Ok(PropFormula::Var(value)),
+                Token::Str(_) => Ok(PropFormula::Var(ident)),
+                token => Err(format!("expected a string after `=`, found `{}`", token)),
+            },
+            Token::Ident(ident) if ident == "true" => Ok(PropFormula::And(vec![])),
+            Token::Ident(ident) if ident == "false" => Ok(PropFormula::Or(vec![])),
+            Token::Ident(ident) => Ok(PropFormula::Var(ident)),
+            token => Err(format!("unexpected `{}`", token)),
+        }
+    }
+}
+
+impl FromStr for PropFormula<String> {
+    type Err = String;
+
+    /// Parse a formula written in infix syntax (`a & !b -> c`), in `cfg` syntax
+    /// (`all(a, not(b), any(feature = "c", d))`) or mixing them.
+    ///
+    /// Operators, from the lowest precedence: `<->`, `->` (right associative), `|`, `&` and `!`.
+    /// `true` and `false` (or `all()` and `any()`) are the constants, names that are not
+    /// identifiers are quoted (`"a.b"`)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser {
+            tokens: tokenize(s)?,
+            pos: 0,
+        };
+        let formula = parser.iff()?;
+        match parser.peek() {
+            Some(token) => Err(format!("unexpected `{}`", token)),
+            None => Ok(formula),
+        }
+    }
+}
+
+/// Precedence of the operator of the formula, as written in infix syntax
+fn precedence<T>(formula: &PropFormula<T>) -> u8 {
+    use PropFormula::*;
+    match formula {
+        Iff(_, _) => 1,
+        Implies(_, _) => 2,
+        Or(v) if v.len() > 1 => 3,
+        And(v) if v.len() > 1 => 4,
+        Or(v) | And(v) if v.len() == 1 => precedence(&v[0]),
+        Not(_) => 5,
+        _ => 6,
+    }
+}
+
+/// Write the formula in infix syntax, in parentheses if its operator binds less than `min`
+fn write_infix<T: Display>(
+    f: &mut Formatter<'_>,
+    formula: &PropFormula<T>,
+    min: u8,
+) -> std::fmt::Result {
+    use PropFormula::*;
+    if precedence(formula) < min {
+        write!(f, "(")?;
+        write_infix(f, formula, 0)?;
+        return write!(f, ")");
+    }
+
+    let write_all = |f: &mut Formatter<'_>, v: &[PropFormula<T>], op: &str, min: u8| {
+        for (i, formula) in v.iter().enumerate() {
+            if i > 0 {
+                write!(f, " {} ", op)?;
+            }
+            write_infix(f, formula, min)?;
+        }
+        Ok(())
+    };
+    match formula {
+        Var(var) => {
+            let name = var.to_string();
+            match is_ident(&name) {
+                true => write!(f, "{}", name),
+                false => write!(f, "\"{}\"", escape(&name)),
+            }
+        }
+        Not(p) => {
+            write!(f, "!")?;
+            write_infix(f, p, 5)
+        }
+        And(v) if v.is_empty() => write!(f, "true"),
+        Or(v) if v.is_empty() => write!(f, "false"),
+        // a single operand is written as it is
+        And(v) | Or(v) if v.len() == 1 => write_infix(f, &v[0], min),
+        And(v) => write_all(f, v, "&", 5),
+        Or(v) => write_all(f, v, "|", 4),
+        Implies(p, q) => {
+            write_infix(f, p, 3)?;
+            write!(f, " -> ")?;
+            write_infix(f, q, 2)
+        }
+        Iff(p, q) => {
+            write_infix(f, p, 1)?;
+            write!(f, " <-> ")?;
+            write_infix(f, q, 2)
+        }
+        None => write!(f, "<invalid>"),
+    }
+}
+
+impl<T: Display> Display for PropFormula<T> {
+    /// Formula in infix syntax with the minimum parentheses, e.g., `a & (b | !c) -> d`. It is
+    /// parsed back to the same formula, but conjunctions and disjunctions of a single operand,
+    /// which are written as the operand
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write_infix(f, self, 0)
+    }
+}
+
+/// Formula in `cfg` syntax, see `PropFormula::cfg`
+pub struct CfgSyntax<'a, T>(&'a PropFormula<T>);
+
+impl<T> PropFormula<T> {
+    /// Display the formula in `cfg` syntax, e.g., `all(a, any(b, not(c)))`. Implications are
+    /// written as disjunctions (`p -> q` is `any(not(p), q)`) and names that are not identifiers
+    /// as features (`feature = "a.b"`)
+    pub fn cfg(&self) -> CfgSyntax<'_, T> {
+        CfgSyntax(self)
+    }
+}
+
+impl<T: Display> Display for CfgSyntax<'_, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        use PropFormula::*;
+        let write_all = |f: &mut Formatter<'_>, predicate: &str, v: &[&PropFormula<T>]| {
+            write!(f, "{}(", predicate)?;
+            for (i, formula) in v.iter().enumerate() {
+                if i > 0 {
+                    write!(f, ", ")?;
+                }
+                write!(f, "{}", formula.cfg())?;
+            }
+            write!(f, ")")
+        };
+        let not = |p: &PropFormula<T>| format!("not({})", p.cfg());
+
+        match self.0 {
+            Var(var) => {
+                let name = var.to_string();
+                match is_ident(&name) {
+                    true => write!(f, "{}", name),
+                    false => write!(f, "feature = \"{}\"", escape(&name)),
+                }
+            }
+            Not(p) => write!(f, "{}", not(p)),
+            And(v) => write_all(f, "all", &v.iter().collect::<Vec<_>>()),
+            Or(v) => write_all(f, "any", &v.iter().collect::<Vec<_>>()),
+            Implies(p, q) => write!(f, "any({}, {})", not(p), q.cfg()),
+            Iff(p, q) => write!(
+                f,
+                "all(any({}, {}), any({}, {}))",
+                not(p),
+                q.cfg(),
+                not(q),
+                p.cfg()
+            ),
+            None => write!(f, "<invalid>"),
+        }
+    }
+}
@@ -0,0 +1,31 @@
+use serde::{Deserialize, Serialize};
+
+use crate::configs::prop_formula::PropFormula;
+use crate::types::{ComplexFeature, Feature, SourceSpan};
+
+/// `compile_error!` annotated by a `cfg`, e.g., `#[cfg(all(feature = "a", feature = "b"))]`:
+/// the crate explicitly rejects the configurations enabling it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompileErrorGuard {
+    /// Complex feature annotating the `compile_error!`
+    pub complex_feature: ComplexFeature<Feature>,
+    /// Complex features of the code enclosing the `compile_error!`, from the outermost one
+    pub enclosing: Vec<ComplexFeature<Feature>>,
+    /// Message of the `compile_error!`, if it is a string literal
+    pub message: Option<String>,
+    pub span: Option<SourceSpan>,
+}
+
+impl CompileErrorGuard {
+    /// Constraint excluding the rejected configurations: `!(a & b)` for `all(a, b)` (mutual
+    /// exclusion), `a | b` for `not(any(a, b))` (at least one of the features)
+    pub fn to_prop_formula(&self) -> PropFormula<String> {
+        PropFormula::Not(Box::new(PropFormula::And(
+            self.enclosing
+                .iter()
+                .chain(std::iter::once(&self.complex_feature))
+                .map(|complex_feature| complex_feature.to_prop_formula())
+                .collect(),
+        )))
+    }
+}
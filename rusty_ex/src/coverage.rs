@@ -0,0 +1,237 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fs;
+
+use crate::errors::{Result, RustyExError};
+use crate::manifest::ManifestFeatures;
+use crate::types::{ArtifactIndex, ArtifactKey, ArtifactsTree, SourceSpan};
+use crate::GLOBAL_NODE_INDEX;
+
+/// Configuration to compile the crate with: the enabled features (and other `cfg` options, e.g.,
+/// `unix`), everything else is disabled
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Configuration {
+    /// As given by the user: the cargo arguments, or the features separated by `,`
+    pub name: String,
+    pub enabled: BTreeSet<String>,
+}
+
+/// Configuration in a configurations file
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ConfigurationEntry {
+    /// Exactly the enabled features (the `default` feature is not implied)
+    Features(Vec<String>),
+    /// Arguments of cargo, e.g., `--no-default-features --features a,b`
+    CargoArgs(String),
+}
+
+impl Configuration {
+    /// Read a JSON list of configurations, each one either a list of features or a string of
+    /// cargo arguments (`--features`, `-F`, `--all-features` and `--no-default-features`).
+    ///
+    /// With the manifest, the features enabled by the enabled ones (and the `default` feature,
+    /// unless disabled) are enabled too, and `--all-features` enables the declared features.
+    /// Without it, `--all-features` enables the features used in the `cfg`s (`used`)
+    pub fn from_file(
+        path: &str,
+        manifest: Option<&ManifestFeatures>,
+        used: &BTreeSet<String>,
+    ) -> Result<Vec<Configuration>> {
+        let invalid_configurations = |message: String| RustyExError::InvalidConfigurations {
+            path: path.to_string(),
+            message,
+        };
+        let content =
+            fs::read_to_string(path).map_err(|error| invalid_configurations(error.to_string()))?;
+        let entries = serde_json::from_str::<Vec<ConfigurationEntry>>(&content)
+            .map_err(|error| invalid_configurations(error.to_string()))?;
+
+        entries
+            .into_iter()
+            .map(|entry| match entry {
+                ConfigurationEntry::Features(features) => Ok(Configuration {
+                    name: features.join(","),
+                    enabled: match manifest {
+                        Some(manifest) => manifest.enabled_features(features, false),
+                        None => features.into_iter().collect(),
+                    },
+                }),
+                ConfigurationEntry::CargoArgs(args) => {
+                    Configuration::from_cargo_args(&args, manifest, used)
+                        .map_err(invalid_configurations)
+                }
+            })
+            .collect()
+    }
+
+    /// Configuration of the features requested by the arguments of cargo
+    fn from_cargo_args(
+        args: &str,
+        manifest: Option<&ManifestFeatures>,
+        used: &BTreeSet<String>,
+    ) -> std::result::Result<Configuration, String> {
+        let mut requested = Vec::new();
+        let (mut all_features, mut default) = (false, true);
+
+        let mut tokens = args.split_whitespace();
+        while let Some(token) = tokens.next() {
+            let features = match token {
+                "--all-features" => {
+                    all_features = true;
+                    continue;
+                }
+                "--no-default-features" => {
+                    default = false;
+                    continue;
+                }
+                "--features" | "-F" => tokens
+                    .next()
+                    .ok_or(format!("`{}` without features in `{}`", token, args))?,
+                _ => match token.strip_prefix("--features=") {
+                    Some(features) => features,
+                    None => continue, // other arguments do not change the features
+                },
+            };
+            requested.extend(
+                features
+                    .trim_matches(|c| c == '"' || c == '\'')
+                    .split(',')
+                    .filter(|feature| !feature.is_empty())
+                    .map(|feature| feature.to_string()),
+            );
+        }
+
+        if all_features {
+            match manifest {
+                Some(manifest) => requested.extend(manifest.features.keys().cloned()),
+                None => requested.extend(used.iter().cloned()),
+            }
+        }
+        Ok(Configuration {
+            name: args.to_string(),
+            enabled: match manifest {
+                Some(manifest) => manifest.enabled_features(requested, default),
+                None => requested.into_iter().collect(),
+            },
+        })
+    }
+}
+
+/// Artifacts compiled by a configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigurationCoverage {
+    pub name: String,
+    pub enabled: BTreeSet<String>,
+    /// Number of artifacts compiled
+    pub artifacts: usize,
+    /// Total weight of the artifacts compiled
+    pub weight: f64,
+}
+
+/// Artifact with the configurations compiling it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CoverageArtifact {
+    pub ident: Option<String>,
+    pub span: Option<SourceSpan>,
+    /// Complex feature annotating the artifact
+    pub complex_feature: String,
+    /// Weight of the artifact, the artifacts nested in it excluded
+    pub weight: f64,
+    /// Indices of the configurations compiling the artifact
+    pub configurations: Vec<usize>,
+}
+
+/// Artifacts exercised by a set of configurations (see `--coverage`). An artifact is compiled
+/// by a configuration if its complex feature and the ones of all its ancestors are enabled
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Coverage {
+    pub configurations: Vec<ConfigurationCoverage>,
+    /// Number of artifacts, the global one excluded
+    pub artifacts: usize,
+    /// Number of artifacts compiled by at least one configuration
+    pub covered_artifacts: usize,
+    /// Total weight of the artifacts
+    pub weight: f64,
+    /// Total weight of the artifacts compiled by at least one configuration
+    pub covered_weight: f64,
+    /// Fraction of the weight of the artifacts that is covered (1 if there is no weight)
+    pub covered_fraction: f64,
+    pub covered: Vec<CoverageArtifact>,
+    /// Artifacts not compiled by any configuration, the heaviest first
+    pub uncovered: Vec<CoverageArtifact>,
+}
+
+impl Coverage {
+    pub fn new<Key: ArtifactKey>(
+        artifacts_tree: &ArtifactsTree<Key>,
+        configurations: &[Configuration],
+    ) -> Self {
+        let mut coverage = Coverage {
+            configurations: configurations
+                .iter()
+                .map(|configuration| ConfigurationCoverage {
+                    name: configuration.name.clone(),
+                    enabled: configuration.enabled.clone(),
+                    artifacts: 0,
+                    weight: 0.0,
+                })
+                .collect(),
+            ..Default::default()
+        };
+
+        for index in artifacts_tree.graph.node_indices() {
+            if index == ArtifactIndex::new(GLOBAL_NODE_INDEX) {
+                continue;
+            }
+            let artifact_node = &artifacts_tree.graph[index];
+            // the nested artifacts may not be compiled with it
+            let weight = artifacts_tree.exclusive_weight(index);
+            let chain = artifacts_tree.ancestors_chain(index);
+            let compiled_by = configurations
+                .iter()
+                .enumerate()
+                .filter(|(_, configuration)| {
+                    chain.iter().all(|index| {
+                        artifacts_tree.graph[*index]
+                            .complex_feature
+                            .is_enabled(&configuration.enabled)
+                    })
+                })
+                .map(|(i, _)| i)
+                .collect::<Vec<_>>();
+
+            for i in &compiled_by {
+                coverage.configurations[*i].artifacts += 1;
+                coverage.configurations[*i].weight += weight;
+            }
+            coverage.artifacts += 1;
+            coverage.weight += weight;
+
+            let artifact = CoverageArtifact {
+                ident: artifact_node.ident.clone(),
+                span: artifact_node.span.clone(),
+                complex_feature: artifact_node.complex_feature.to_string(),
+                weight,
+                configurations: compiled_by,
+            };
+            match artifact.configurations.is_empty() {
+                true => coverage.uncovered.push(artifact),
+                false => {
+                    coverage.covered_artifacts += 1;
+                    coverage.covered_weight += weight;
+                    coverage.covered.push(artifact);
+                }
+            }
+        }
+
+        coverage.covered_fraction = match coverage.weight > 0.0 {
+            true => coverage.covered_weight / coverage.weight,
+            false => 1.0,
+        };
+        coverage
+            .uncovered
+            .sort_by(|a, b| b.weight.total_cmp(&a.weight));
+        coverage
+    }
+}
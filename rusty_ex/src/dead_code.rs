@@ -0,0 +1,126 @@
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+
+use crate::configs::config_solver::ConfigSolver;
+use crate::configs::prop_formula::PropFormula;
+use crate::errors::RustyExError;
+use crate::types::{
+    ArtifactIndex, ArtifactKey, ArtifactsTree, ComplexFeature, Feature, SourceSpan,
+};
+use crate::GLOBAL_NODE_INDEX;
+
+/// Artifact that can never be compiled: the `cfg`s of the artifact and of the code enclosing it
+/// cannot be satisfied together (e.g., `a` inside `not(a)`, or `all(a, not(a))`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeadArtifact {
+    pub ident: Option<String>,
+    pub span: Option<SourceSpan>,
+    /// Complex feature annotating the artifact
+    pub complex_feature: String,
+    /// Minimal set of conflicting `cfg`s, from the outermost one
+    pub conflict: Vec<String>,
+    /// Whether the conflict needs the feature constraints of the manifest
+    pub manifest: bool,
+}
+
+/// Find the artifacts whose `cfg`s, together with the ones of their annotated ancestors, are
+/// unsatisfiable, optionally under some constraints (e.g., the features of the manifest).
+///
+/// Artifacts whose check is interrupted are not reported as dead, and the interruption is pushed
+/// to `errors`.
+pub fn dead_artifacts<Key: ArtifactKey>(
+    artifacts_tree: &ArtifactsTree<Key>,
+    constraints: Option<&PropFormula<String>>,
+    errors: &mut Vec<RustyExError>,
+) -> Vec<DeadArtifact> {
+    let global = ArtifactIndex::new(GLOBAL_NODE_INDEX);
+    let mut dead = Vec::new();
+
+    for index in artifacts_tree.graph.node_indices() {
+        if index == global {
+            continue;
+        }
+
+        let cfgs = artifacts_tree
+            .ancestors_chain(index)
+            .iter()
+            .map(|index| &artifacts_tree.graph[*index].complex_feature)
+            .filter(|complex_feature| !matches!(complex_feature, ComplexFeature::None))
+            .collect::<Vec<_>>();
+
+        match satisfiable(&cfgs, constraints) {
+            Some(false) => {}
+            Some(true) => continue,
+            None => {
+                errors.push(RustyExError::SolverInterrupted {
+                    task: "looking for dead code".to_string(),
+                });
+                continue;
+            }
+        }
+
+        // drop the `cfg`s not needed for the conflict, one at a time (kept if interrupted)
+        let mut conflict = cfgs;
+        let mut i = 0;
+        while i < conflict.len() {
+            let mut without = conflict.clone();
+            without.remove(i);
+            if satisfiable(&without, constraints) != Some(false) {
+                i += 1;
+            } else {
+                conflict = without;
+            }
+        }
+
+        let artifact_node = &artifacts_tree.graph[index];
+        dead.push(DeadArtifact {
+            ident: artifact_node.ident.clone(),
+            span: artifact_node.span.clone(),
+            complex_feature: artifact_node.complex_feature.to_string(),
+            manifest: constraints.is_some() && satisfiable(&conflict, None) == Some(true),
+            conflict: conflict.iter().map(|cfg| cfg.to_string()).collect(),
+        });
+    }
+
+    dead
+}
+
+/// Whether some configuration enables all the `cfg`s and satisfies the constraints: `None` if the
+/// solver is interrupted
+fn satisfiable(
+    cfgs: &[&ComplexFeature<Feature>],
+    constraints: Option<&PropFormula<String>>,
+) -> Option<bool> {
+    let mut formula = cfgs
+        .iter()
+        .map(|cfg| cfg.to_prop_formula())
+        .collect::<Vec<_>>();
+    formula.extend(constraints.cloned());
+    // `cfg`s are nested in the chain (and in the constraints), the formula must be flattened
+    let mut formula = PropFormula::And(formula);
+    formula.to_flat_cnf();
+    let (cnf, _) = formula.to_cnf_repr::<u32>(true);
+
+    let mut solver = ConfigSolver::default();
+    solver.add_cnf(cnf);
+    solver.is_satisfiable()
+}
+
+impl Display for DeadArtifact {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "'{}' [{}] at {}: conflicting cfgs: {}",
+            self.ident.clone().unwrap_or("-".to_string()),
+            self.complex_feature,
+            self.span
+                .as_ref()
+                .map_or("-".to_string(), |span| span.to_string()),
+            self.conflict.join(", ")
+        )?;
+        if self.manifest {
+            write!(f, " (with the features of the manifest)")?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,157 @@
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+
+use crate::types::SourceSpan;
+
+/// Result type used across the analysis
+pub type Result<T> = std::result::Result<T, RustyExError>;
+
+/// Errors found while analysing a crate.
+///
+/// Errors are not fatal: they are collected while the analysis goes on, so that a single
+/// malformed piece of code does not prevent extracting the graphs of the whole crate.
+/// The affected part is ignored (or approximated) and the error is reported as a diagnostic
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum RustyExError {
+    /// A `cfg` attribute that cannot be parsed, the attribute is ignored
+    MalformedCfg {
+        message: String,
+        span: Option<SourceSpan>,
+        snippet: Option<String>,
+    },
+    /// A centrality measure that cannot be computed, the measure is left empty
+    Centrality { measure: String, message: String },
+    /// An artifact whose weight is not resolved, the artifact is ignored while refining centrality
+    UnresolvedWeight {
+        ident: Option<String>,
+        span: Option<SourceSpan>,
+    },
+    /// A weight model file that cannot be read, the default weight model is used
+    InvalidWeightModel { path: String, message: String },
+    /// A dependency weights file that cannot be read, the recovery weight is used
+    InvalidDependencyWeights { path: String, message: String },
+    /// The MIR of the crate cannot be built (the crate does not compile), the AST-based weights are used
+    MirUnavailable,
+    /// A crate that cannot be analysed in-process (see `analysis::analyze`), nothing is extracted
+    InvalidCrate { path: String, message: String },
+    /// A feature that does not appear in the crate, it cannot be explained
+    UnknownFeature { name: String },
+    /// A manifest whose features cannot be read, the analyses that need them are skipped or
+    /// done without them
+    InvalidManifest { path: String, message: String },
+    /// `compile_error!` guards that together reject every configuration, no configuration is
+    /// generated
+    UnsatisfiableGuards { guards: usize },
+    /// A configurations file that cannot be read, the coverage is not computed
+    InvalidConfigurations { path: String, message: String },
+    /// A SAT query interrupted before its answer, the result of the task is partial
+    SolverInterrupted { task: String },
+}
+
+impl RustyExError {
+    /// Span of the code that caused the error, if any
+    pub fn span(&self) -> Option<&SourceSpan> {
+        match self {
+            RustyExError::MalformedCfg { span, .. } => span.as_ref(),
+            RustyExError::Centrality { .. } => None,
+            RustyExError::UnresolvedWeight { span, .. } => span.as_ref(),
+            RustyExError::InvalidWeightModel { .. } => None,
+            RustyExError::InvalidDependencyWeights { .. } => None,
+            RustyExError::MirUnavailable => None,
+            RustyExError::InvalidCrate { .. } => None,
+            RustyExError::UnknownFeature { .. } => None,
+            RustyExError::InvalidManifest { .. } => None,
+            RustyExError::UnsatisfiableGuards { .. } => None,
+            RustyExError::InvalidConfigurations { .. } => None,
+            RustyExError::SolverInterrupted { .. } => None,
+        }
+    }
+
+    /// Render the error as a human-readable diagnostic, pointing at the offending code
+    pub fn to_diagnostic(&self) -> String {
+        let mut diagnostic = format!("warning: {}", self);
+        if let Some(span) = self.span() {
+            diagnostic.push_str(&format!("\n  --> {}", span));
+        }
+        if let RustyExError::MalformedCfg {
+            snippet: Some(snippet),
+            ..
+        } = self
+        {
+            // show the original code, not the one rewritten by the file loader
+            let snippet = crate::utils::restore_cfg(snippet);
+            diagnostic.push_str("\n   |");
+            for line in snippet.lines() {
+                diagnostic.push_str(&format!("\n   | {}", line));
+            }
+        }
+        diagnostic
+    }
+}
+
+impl Display for RustyExError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RustyExError::MalformedCfg { message, .. } => {
+                write!(f, "malformed `cfg` attribute ignored: {}", message)
+            }
+            RustyExError::Centrality { measure, message } => {
+                write!(f, "cannot compute {} centrality: {}", measure, message)
+            }
+            RustyExError::UnresolvedWeight { ident, .. } => write!(
+                f,
+                "weight of artifact '{}' not resolved, artifact ignored",
+                ident.clone().unwrap_or("-".to_string())
+            ),
+            RustyExError::InvalidWeightModel { path, message } => write!(
+                f,
+                "cannot read weight model '{}', default model used: {}",
+                path, message
+            ),
+            RustyExError::InvalidDependencyWeights { path, message } => write!(
+                f,
+                "cannot read dependency weights '{}', recovery weight used: {}",
+                path, message
+            ),
+            RustyExError::MirUnavailable => write!(
+                f,
+                "cannot build the MIR of the crate, AST-based weights used"
+            ),
+            RustyExError::InvalidCrate { path, message } => {
+                write!(f, "cannot analyse crate '{}': {}", path, message)
+            }
+            RustyExError::UnknownFeature { name } => {
+                write!(f, "feature '{}' not found, cannot explain it", name)
+            }
+            RustyExError::InvalidManifest { path, message } => write!(
+                f,
+                "cannot read the features of manifest '{}': {}",
+                path, message
+            ),
+            RustyExError::UnsatisfiableGuards { guards } => write!(
+                f,
+                "the {} `compile_error!` guards reject every configuration, no configuration generated",
+                guards
+            ),
+            RustyExError::InvalidConfigurations { path, message } => write!(
+                f,
+                "cannot read configurations '{}', coverage skipped: {}",
+                path, message
+            ),
+            RustyExError::SolverInterrupted { task } => write!(
+                f,
+                "the SAT solver was interrupted while {}, the result is partial",
+                task
+            ),
+        }
+    }
+}
+
+impl std::error::Error for RustyExError {}
+
+/// Print all the errors as diagnostics on `stderr`
+pub fn print_diagnostics(errors: &[RustyExError]) {
+    for error in errors {
+        eprintln!("{}\n", error.to_diagnostic());
+    }
+}
@@ -0,0 +1,218 @@
+use clap::ValueEnum;
+use rustworkx_core::petgraph::visit::EdgeRef;
+use rustworkx_core::petgraph::Direction;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt::{Display, Formatter};
+
+use crate::configs::centrality::{Centrality, CentralityMethod};
+use crate::types::{
+    ArtifactKey, ArtifactsTree, CfgKind, Feature, FeatureIndex, FeatureKey, FeaturesGraph,
+    SourceSpan, TermWeight,
+};
+use crate::GLOBAL_DUMMY_INDEX;
+
+/// Artifact annotated by the explained feature
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnnotatedArtifact {
+    pub ident: Option<String>,
+    pub span: Option<SourceSpan>,
+    /// Complex feature annotating the artifact
+    pub complex_feature: String,
+    pub weight: TermWeight,
+}
+
+/// Feature linked to the explained feature by an edge of the (squashed) Features Graph
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LinkedFeature {
+    pub feature: Feature,
+    pub weight: f64,
+}
+
+/// Everything known about a single feature (see `--explain-feature`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeatureExplanation {
+    pub feature: Feature,
+    /// Kind of `cfg` option, `None` if unknown
+    pub cfg_kind: Option<CfgKind>,
+    pub artifacts: Vec<AnnotatedArtifact>,
+    /// Complex features (`cfg` expressions) in which the feature appears
+    pub complex_features: Vec<String>,
+    /// Features of the code enclosing the feature, with the edge weight
+    pub parents: Vec<LinkedFeature>,
+    /// Features of the code directly enclosed by the feature, with the edge weight
+    pub children: Vec<LinkedFeature>,
+    /// Features of the code enclosed by the feature, at any depth
+    pub gated: Vec<Feature>,
+    /// Weight of the artifacts of the feature, `None` if the feature has no centrality
+    pub refined_weight: Option<f64>,
+    /// Rank (starting from 1) of the feature by each centrality measure
+    pub ranks: BTreeMap<String, usize>,
+}
+
+impl FeatureExplanation {
+    /// Explain a feature using the squashed Features Graph, `None` if the feature does not exist
+    pub fn new<Key: ArtifactKey>(
+        feature: &Feature,
+        features_graph: &FeaturesGraph,
+        artifacts_tree: &ArtifactsTree<Key>,
+        centrality: &Centrality<u32>,
+        cfg_kinds: &HashMap<String, CfgKind>,
+    ) -> Option<Self> {
+        let feature_index = *features_graph.nodes.get(&FeatureKey::from(feature))?;
+        let feature_node = &features_graph.graph[feature_index];
+
+        let artifacts = artifacts_tree
+            .graph
+            .node_weights()
+            .filter(|artifact_node| artifact_node.complex_feature.contains(feature))
+            .map(|artifact_node| AnnotatedArtifact {
+                ident: artifact_node.ident.clone(),
+                span: artifact_node.span.clone(),
+                complex_feature: artifact_node.complex_feature.to_string(),
+                weight: artifact_node.weight.clone(),
+            })
+            .collect();
+
+        let complex_features = feature_node
+            .complex_feature
+            .iter()
+            .map(|complex_feature| complex_feature.to_string())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+
+        // edges go from the enclosed feature to the enclosing one (self loops are skipped)
+        let linked = |direction: Direction| {
+            let mut linked = features_graph
+                .graph
+                .edges_directed(feature_index, direction)
+                .map(|edge| match direction {
+                    Direction::Outgoing => (edge.target(), edge.weight().weight),
+                    Direction::Incoming => (edge.source(), edge.weight().weight),
+                })
+                .filter(|(index, _)| {
+                    *index != FeatureIndex::new(GLOBAL_DUMMY_INDEX) && *index != feature_index
+                })
+                .map(|(index, weight)| LinkedFeature {
+                    feature: features_graph.graph[index].feature.0.clone(),
+                    weight,
+                })
+                .collect::<Vec<_>>();
+            linked.sort_by_key(|linked| linked.feature.to_string());
+            linked
+        };
+
+        let mut gated = BTreeSet::new();
+        let mut stack = vec![feature_index];
+        while let Some(index) = stack.pop() {
+            for child in features_graph
+                .graph
+                .neighbors_directed(index, Direction::Incoming)
+            {
+                if child != FeatureIndex::new(GLOBAL_DUMMY_INDEX)
+                    && child != feature_index
+                    && gated.insert(child)
+                {
+                    stack.push(child);
+                }
+            }
+        }
+        let mut gated = gated
+            .into_iter()
+            .map(|index| features_graph.graph[index].feature.0.clone())
+            .collect::<Vec<_>>();
+        gated.sort_by_key(|feature| feature.to_string());
+
+        let position = centrality.features.iter().position(|f| f == feature);
+        let ranks = match position {
+            Some(_) => CentralityMethod::value_variants()
+                .iter()
+                .filter_map(|method| {
+                    let rank = centrality
+                        .ranked_features(method, cfg_kinds)
+                        .iter()
+                        .find(|f| f.name == feature.name && f.not == feature.not)?
+                        .rank;
+                    Some((method.to_possible_value()?.get_name().to_string(), rank))
+                })
+                .collect(),
+            None => BTreeMap::new(),
+        };
+
+        Some(FeatureExplanation {
+            feature: feature.clone(),
+            cfg_kind: cfg_kinds.get(&feature.name).cloned(),
+            artifacts,
+            complex_features,
+            parents: linked(Direction::Outgoing),
+            children: linked(Direction::Incoming),
+            gated,
+            refined_weight: position.map(|i| centrality.refined_weights[i]),
+            ranks,
+        })
+    }
+}
+
+impl Display for FeatureExplanation {
+    /// Human-readable explanation, one section per line
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let linked = |linked: &[LinkedFeature]| {
+            linked
+                .iter()
+                .map(|linked| format!("{} ({:.2})", linked.feature, linked.weight))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+
+        writeln!(
+            f,
+            "feature: {} ({})",
+            self.feature,
+            self.cfg_kind
+                .as_ref()
+                .map_or("-".to_string(), |kind| kind.to_string())
+        )?;
+        writeln!(f, "artifacts: {}", self.artifacts.len())?;
+        for artifact in &self.artifacts {
+            writeln!(
+                f,
+                "  '{}' [{}] {} at {}",
+                artifact.ident.clone().unwrap_or("-".to_string()),
+                artifact.complex_feature,
+                artifact.weight,
+                artifact
+                    .span
+                    .as_ref()
+                    .map_or("-".to_string(), |span| span.to_string())
+            )?;
+        }
+        writeln!(f, "complex features: {}", self.complex_features.join(", "))?;
+        writeln!(f, "parents: {}", linked(&self.parents))?;
+        writeln!(f, "children: {}", linked(&self.children))?;
+        writeln!(
+            f,
+            "gated features: {}",
+            self.gated
+                .iter()
+                .map(|feature| feature.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )?;
+        writeln!(
+            f,
+            "refined weight: {}",
+            self.refined_weight
+                .map_or("-".to_string(), |weight| format!("{:.2}", weight))
+        )?;
+        write!(
+            f,
+            "ranks: {}",
+            self.ranks
+                .iter()
+                .map(|(measure, rank)| format!("{} {}", measure, rank))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    }
+}
@@ -0,0 +1,529 @@
+use rustworkx_core::petgraph::graph::DiGraph;
+use rustworkx_core::petgraph::visit::EdgeRef;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+
+use crate::configs::centrality::FeatureCentrality;
+use crate::types::{
+    ArtifactKey, ArtifactsTree, Edge, FeaturesGraph, SourceSpan, TermKey, TermWeight,
+    TermWeightKind, TermsTree,
+};
+use crate::utils::xml_escape;
+
+/// Format of the printed graphs
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub enum GraphFormat {
+    #[default]
+    Dot,
+    /// GraphML (e.g., yEd, networkx)
+    GraphMl,
+    /// GEXF 1.3 (e.g., Gephi)
+    Gexf,
+    /// networkx node-link JSON
+    NodeLink,
+}
+
+impl GraphFormat {
+    /// Extension of the files in this format
+    pub fn extension(&self) -> &str {
+        match self {
+            GraphFormat::Dot => "dot",
+            GraphFormat::GraphMl => "graphml",
+            GraphFormat::Gexf => "gexf",
+            GraphFormat::NodeLink => "json",
+        }
+    }
+
+    /// Render a graph in this format, `dot` builds the DOT output and `export` the typed graph
+    pub fn render(
+        &self,
+        dot: impl FnOnce() -> String,
+        export: impl FnOnce() -> ExportGraph,
+    ) -> String {
+        match self {
+            GraphFormat::Dot => dot(),
+            GraphFormat::GraphMl => export().to_graphml(),
+            GraphFormat::Gexf => export().to_gexf(),
+            GraphFormat::NodeLink => export().to_node_link(),
+        }
+    }
+}
+
+/// Type of an attribute of the nodes or edges
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AttrType {
+    String,
+    Double,
+    Int,
+    Bool,
+}
+
+impl AttrType {
+    /// Name of the type in GraphML and GEXF
+    fn xml_name(&self) -> &str {
+        match self {
+            AttrType::String => "string",
+            AttrType::Double => "double",
+            AttrType::Int => "long",
+            AttrType::Bool => "boolean",
+        }
+    }
+}
+
+/// Value of an attribute, `None` if missing (e.g., a weight not yet calculated)
+#[derive(Debug, Clone, PartialEq)]
+pub enum AttrValue {
+    String(String),
+    Double(f64),
+    Int(i64),
+    Bool(bool),
+    None,
+}
+
+impl From<Option<String>> for AttrValue {
+    fn from(value: Option<String>) -> Self {
+        value.map_or(AttrValue::None, AttrValue::String)
+    }
+}
+
+impl From<Option<f64>> for AttrValue {
+    fn from(value: Option<f64>) -> Self {
+        value.map_or(AttrValue::None, AttrValue::Double)
+    }
+}
+
+impl AttrValue {
+    fn to_json(&self) -> Value {
+        match self {
+            AttrValue::String(value) => json!(value),
+            AttrValue::Double(value) => json!(value),
+            AttrValue::Int(value) => json!(value),
+            AttrValue::Bool(value) => json!(value),
+            AttrValue::None => Value::Null,
+        }
+    }
+
+    /// Value as written in the XML formats, `None` if missing
+    fn to_xml(&self) -> Option<String> {
+        match self {
+            AttrValue::String(value) => Some(xml_escape(value)),
+            AttrValue::Double(value) => Some(value.to_string()),
+            AttrValue::Int(value) => Some(value.to_string()),
+            AttrValue::Bool(value) => Some(value.to_string()),
+            AttrValue::None => None,
+        }
+    }
+}
+
+/// Node of an exported graph, the attributes are ordered as the declared ones
+#[derive(Debug, Clone)]
+pub struct ExportNode {
+    /// Index of the node in the graph
+    pub id: usize,
+    pub label: String,
+    pub attrs: Vec<AttrValue>,
+}
+
+/// Edge of an exported graph, the attributes are ordered as the declared ones
+#[derive(Debug, Clone)]
+pub struct ExportEdge {
+    pub source: usize,
+    pub target: usize,
+    pub attrs: Vec<AttrValue>,
+}
+
+/// Graph with typed attributes, independent of the export format
+#[derive(Debug, Clone)]
+pub struct ExportGraph {
+    pub multigraph: bool,
+    pub node_attrs: Vec<(&'static str, AttrType)>,
+    pub edge_attrs: Vec<(&'static str, AttrType)>,
+    pub nodes: Vec<ExportNode>,
+    pub edges: Vec<ExportEdge>,
+}
+
+/// Attributes of a span, all missing if there is no span
+const SPAN_ATTRS: [(&str, AttrType); 5] = [
+    ("span_file", AttrType::String),
+    ("span_lo_line", AttrType::Int),
+    ("span_lo_col", AttrType::Int),
+    ("span_hi_line", AttrType::Int),
+    ("span_hi_col", AttrType::Int),
+];
+
+fn span_attrs(span: &Option<SourceSpan>) -> Vec<AttrValue> {
+    match span {
+        Some(span) => vec![
+            AttrValue::String(span.file.clone()),
+            AttrValue::Int(span.lo_line as i64),
+            AttrValue::Int(span.lo_col as i64),
+            AttrValue::Int(span.hi_line as i64),
+            AttrValue::Int(span.hi_col as i64),
+        ],
+        None => vec![AttrValue::None; SPAN_ATTRS.len()],
+    }
+}
+
+fn weight_attr(weight: &TermWeight) -> AttrValue {
+    match weight {
+        TermWeight::Weight(weight) => AttrValue::Double(*weight),
+        _ => AttrValue::None,
+    }
+}
+
+impl<Key: TermKey> TermsTree<Key> {
+    /// Terms Tree with typed attributes, to be exported
+    pub fn export_graph(&self) -> ExportGraph {
+        let mut node_attrs = vec![
+            ("node_id", AttrType::String),
+            ("ident", AttrType::String),
+            ("kind", AttrType::String),
+            ("weight_kind", AttrType::String),
+            ("reference", AttrType::String),
+            ("features", AttrType::String),
+            ("weight", AttrType::Double),
+            ("estimated_weight", AttrType::Double),
+            ("recovered", AttrType::Bool),
+            ("def_path", AttrType::String),
+            ("target", AttrType::String),
+        ];
+        node_attrs.extend(SPAN_ATTRS);
+
+        let nodes = self
+            .graph
+            .node_indices()
+            .map(|index| {
+                let term_node = &self.graph[index];
+                let (weight_kind, reference) = match &term_node.weight_kind {
+                    TermWeightKind::Intrinsic(_) => ("intrinsic", None),
+                    TermWeightKind::Children(_) => ("children", None),
+                    TermWeightKind::Reference(_, reference) => ("reference", reference.clone()),
+                    TermWeightKind::No(_) => ("no", None),
+                };
+                let mut attrs = vec![
+                    AttrValue::String(term_node.node_id.to_string()),
+                    term_node.ident.clone().into(),
+                    AttrValue::String(term_node.weight_kind.kind_name().to_string()),
+                    AttrValue::String(weight_kind.to_string()),
+                    reference.into(),
+                    AttrValue::String(term_node.features.to_string()),
+                    weight_attr(&term_node.weight),
+                    AttrValue::Double(term_node.estimated_weight),
+                    AttrValue::Bool(term_node.recovered),
+                    term_node.def_path.clone().into(),
+                    term_node.target.clone().into(),
+                ];
+                attrs.extend(span_attrs(&term_node.span));
+                ExportNode {
+                    id: index.index(),
+                    label: term_node.ident.clone().unwrap_or("-".to_string()),
+                    attrs,
+                }
+            })
+            .collect();
+
+        ExportGraph {
+            multigraph: false,
+            node_attrs,
+            edge_attrs: vec![],
+            nodes,
+            edges: edges(&self.graph, |_| vec![]),
+        }
+    }
+}
+
+impl FeaturesGraph {
+    /// Features Graph with typed attributes (and the centrality of the features), to be exported
+    pub fn export_graph(&self, multigraph: bool, centrality: &[FeatureCentrality]) -> ExportGraph {
+        let node_attrs = vec![
+            ("name", AttrType::String),
+            ("not", AttrType::Bool),
+            ("complex_features", AttrType::String),
+            ("weight", AttrType::Double),
+            ("cfg_kind", AttrType::String),
+            ("refined_weight", AttrType::Double),
+            ("rank", AttrType::Int),
+            ("katz", AttrType::Double),
+            ("closeness", AttrType::Double),
+            ("eigenvector", AttrType::Double),
+            ("pagerank", AttrType::Double),
+            ("betweenness", AttrType::Double),
+            ("in_degree", AttrType::Double),
+            ("out_degree", AttrType::Double),
+            ("harmonic", AttrType::Double),
+        ];
+        let centrality = centrality
+            .iter()
+            .map(|f| ((f.name.as_str(), f.not), f))
+            .collect::<HashMap<_, _>>();
+
+        let nodes = self
+            .graph
+            .node_indices()
+            .map(|index| {
+                let feature_node = &self.graph[index];
+                let feature = &feature_node.feature.0;
+                let mut complex_features = feature_node
+                    .complex_feature
+                    .iter()
+                    .map(|complex_feature| complex_feature.to_string())
+                    .collect::<Vec<_>>();
+                complex_features.sort();
+
+                let mut attrs = vec![
+                    AttrValue::String(feature.name.clone()),
+                    AttrValue::Bool(feature.not),
+                    AttrValue::String(complex_features.join("; ")),
+                    feature_node.weight.into(),
+                ];
+                match centrality.get(&(feature.name.as_str(), feature.not)) {
+                    Some(f) => attrs.extend([
+                        f.cfg_kind.as_ref().map(|kind| kind.to_string()).into(),
+                        AttrValue::Double(f.refined_weight),
+                        AttrValue::Int(f.rank as i64),
+                        f.katz.into(),
+                        f.closeness.into(),
+                        f.eigenvector.into(),
+                        f.pagerank.into(),
+                        AttrValue::Double(f.betweenness),
+                        AttrValue::Double(f.in_degree),
+                        AttrValue::Double(f.out_degree),
+                        AttrValue::Double(f.harmonic),
+                    ]),
+                    None => attrs.resize(node_attrs.len(), AttrValue::None),
+                }
+                ExportNode {
+                    id: index.index(),
+                    label: feature.to_string(),
+                    attrs,
+                }
+            })
+            .collect();
+
+        ExportGraph {
+            multigraph,
+            node_attrs,
+            edge_attrs: vec![("weight", AttrType::Double)],
+            nodes,
+            edges: edges(&self.graph, |edge| vec![AttrValue::Double(edge.weight)]),
+        }
+    }
+}
+
+impl<Key: ArtifactKey> ArtifactsTree<Key> {
+    /// Artifacts Tree with typed attributes, to be exported
+    pub fn export_graph(&self) -> ExportGraph {
+        let mut node_attrs = vec![
+            ("artifact", AttrType::String),
+            ("ident", AttrType::String),
+            ("complex_feature", AttrType::String),
+            ("weight", AttrType::Double),
+            ("estimated_weight", AttrType::Double),
+        ];
+        node_attrs.extend(SPAN_ATTRS);
+
+        let nodes = self
+            .graph
+            .node_indices()
+            .map(|index| {
+                let artifact_node = &self.graph[index];
+                let mut attrs = vec![
+                    AttrValue::String(artifact_node.artifact.to_string()),
+                    artifact_node.ident.clone().into(),
+                    AttrValue::String(artifact_node.complex_feature.to_string()),
+                    weight_attr(&artifact_node.weight),
+                    AttrValue::Double(artifact_node.estimated_weight),
+                ];
+                attrs.extend(span_attrs(&artifact_node.span));
+                ExportNode {
+                    id: index.index(),
+                    label: artifact_node.ident.clone().unwrap_or("-".to_string()),
+                    attrs,
+                }
+            })
+            .collect();
+
+        ExportGraph {
+            multigraph: false,
+            node_attrs,
+            edge_attrs: vec![],
+            nodes,
+            edges: edges(&self.graph, |_| vec![]),
+        }
+    }
+}
+
+/// Edges of a graph, with the attributes given by `attrs`
+fn edges<N>(graph: &DiGraph<N, Edge>, attrs: impl Fn(&Edge) -> Vec<AttrValue>) -> Vec<ExportEdge> {
+    graph
+        .edge_references()
+        .map(|edge| ExportEdge {
+            source: edge.source().index(),
+            target: edge.target().index(),
+            attrs: attrs(edge.weight()),
+        })
+        .collect()
+}
+
+impl ExportGraph {
+    /// GraphML document, attributes are `<key>`s (`d0`, `d1`, ... for nodes, `e0`, ... for edges)
+    pub fn to_graphml(&self) -> String {
+        let data = |prefix: &str, attrs: &[AttrValue]| {
+            attrs
+                .iter()
+                .enumerate()
+                .filter_map(|(i, value)| {
+                    value
+                        .to_xml()
+                        .map(|value| format!("<data key=\"{}{}\">{}</data>", prefix, i, value))
+                })
+                .collect::<String>()
+        };
+
+        let mut xml = String::from(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n",
+        );
+        for (prefix, domain, attrs) in [
+            ("d", "node", &self.node_attrs),
+            ("e", "edge", &self.edge_attrs),
+        ] {
+            for (i, (name, kind)) in attrs.iter().enumerate() {
+                xml.push_str(&format!(
+                    "  <key id=\"{}{}\" for=\"{}\" attr.name=\"{}\" attr.type=\"{}\"/>\n",
+                    prefix,
+                    i,
+                    domain,
+                    name,
+                    kind.xml_name()
+                ));
+            }
+        }
+        xml.push_str("  <graph id=\"G\" edgedefault=\"directed\">\n");
+        for node in &self.nodes {
+            xml.push_str(&format!(
+                "    <node id=\"n{}\">{}</node>\n",
+                node.id,
+                data("d", &node.attrs)
+            ));
+        }
+        for (i, edge) in self.edges.iter().enumerate() {
+            xml.push_str(&format!(
+                "    <edge id=\"e{}\" source=\"n{}\" target=\"n{}\">{}</edge>\n",
+                i,
+                edge.source,
+                edge.target,
+                data("e", &edge.attrs)
+            ));
+        }
+        xml.push_str("  </graph>\n</graphml>");
+        xml
+    }
+
+    /// GEXF 1.3 document, the `weight` edge attribute is also the native edge weight
+    pub fn to_gexf(&self) -> String {
+        let attvalues = |attrs: &[AttrValue]| {
+            let values = attrs
+                .iter()
+                .enumerate()
+                .filter_map(|(i, value)| {
+                    value
+                        .to_xml()
+                        .map(|value| format!("<attvalue for=\"{}\" value=\"{}\"/>", i, value))
+                })
+                .collect::<String>();
+            match values.is_empty() {
+                true => values,
+                false => format!("<attvalues>{}</attvalues>", values),
+            }
+        };
+
+        let mut xml = String::from(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <gexf xmlns=\"http://gexf.net/1.3\" version=\"1.3\">\n  \
+             <graph defaultedgetype=\"directed\" mode=\"static\">\n",
+        );
+        for (class, attrs) in [("node", &self.node_attrs), ("edge", &self.edge_attrs)] {
+            xml.push_str(&format!("    <attributes class=\"{}\">\n", class));
+            for (i, (name, kind)) in attrs.iter().enumerate() {
+                xml.push_str(&format!(
+                    "      <attribute id=\"{}\" title=\"{}\" type=\"{}\"/>\n",
+                    i,
+                    name,
+                    kind.xml_name()
+                ));
+            }
+            xml.push_str("    </attributes>\n");
+        }
+        xml.push_str("    <nodes>\n");
+        for node in &self.nodes {
+            xml.push_str(&format!(
+                "      <node id=\"{}\" label=\"{}\">{}</node>\n",
+                node.id,
+                xml_escape(&node.label),
+                attvalues(&node.attrs)
+            ));
+        }
+        xml.push_str("    </nodes>\n    <edges>\n");
+        let weight_index = self
+            .edge_attrs
+            .iter()
+            .position(|(name, _)| *name == "weight");
+        for (i, edge) in self.edges.iter().enumerate() {
+            let weight = weight_index
+                .and_then(|index| edge.attrs[index].to_xml())
+                .map_or(String::new(), |weight| format!(" weight=\"{}\"", weight));
+            xml.push_str(&format!(
+                "      <edge id=\"{}\" source=\"{}\" target=\"{}\"{}>{}</edge>\n",
+                i,
+                edge.source,
+                edge.target,
+                weight,
+                attvalues(&edge.attrs)
+            ));
+        }
+        xml.push_str("    </edges>\n  </graph>\n</gexf>");
+        xml
+    }
+
+    /// networkx node-link JSON (`networkx.node_link_graph(data, edges="links")`)
+    pub fn to_node_link(&self) -> String {
+        let element = |id: Vec<(&str, Value)>, names: &[(&str, AttrType)], attrs: &[AttrValue]| {
+            let mut object = id
+                .into_iter()
+                .map(|(key, value)| (key.to_string(), value))
+                .collect::<Map<_, _>>();
+            for ((name, _), value) in names.iter().zip(attrs) {
+                object.insert(name.to_string(), value.to_json());
+            }
+            Value::Object(object)
+        };
+
+        let nodes = self
+            .nodes
+            .iter()
+            .map(|node| element(vec![("id", json!(node.id))], &self.node_attrs, &node.attrs))
+            .collect::<Vec<_>>();
+        let links = self
+            .edges
+            .iter()
+            .map(|edge| {
+                let id = vec![
+                    ("source", json!(edge.source)),
+                    ("target", json!(edge.target)),
+                ];
+                element(id, &self.edge_attrs, &edge.attrs)
+            })
+            .collect::<Vec<_>>();
+
+        serde_json::to_string(&json!({
+            "directed": true,
+            "multigraph": self.multigraph,
+            "graph": {},
+            "nodes": nodes,
+            "links": links,
+        }))
+        .expect("Error: cannot serialize data")
+    }
+}
@@ -0,0 +1,101 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+
+use crate::manifest::ManifestFeatures;
+use crate::types::{ArtifactKey, ArtifactsTree, CfgKind, Feature, SourceSpan};
+
+/// Feature used in a `cfg` but not declared in the manifest: the annotated code is never compiled
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UndeclaredFeature {
+    pub name: String,
+    /// Spans of the artifacts annotated by the feature
+    pub spans: Vec<SourceSpan>,
+}
+
+/// Feature declared in the manifest and not used in any `cfg`, that only enables other features
+/// or dependencies
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ForwardingFeature {
+    pub name: String,
+    pub enables: Vec<String>,
+}
+
+/// Cross-check of the features used in the `cfg`s and the ones declared in the manifest
+/// (see `--lint-features`). The `default` feature is never reported
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FeatureLints {
+    /// Whether there are no undeclared or unused features (forwarding ones are fine)
+    pub clean: bool,
+    /// Used in a `cfg`, not declared
+    pub undeclared: Vec<UndeclaredFeature>,
+    /// Declared, not used in any `cfg` and enabling nothing
+    pub unused: Vec<String>,
+    /// Declared, not used in any `cfg`, only enabling other features or dependencies
+    pub forwarding: Vec<ForwardingFeature>,
+}
+
+impl FeatureLints {
+    pub fn new<Key: ArtifactKey>(
+        manifest: &ManifestFeatures,
+        cfg_kinds: &HashMap<String, CfgKind>,
+        artifacts_tree: &ArtifactsTree<Key>,
+    ) -> Self {
+        let used = cfg_kinds
+            .iter()
+            .filter(|(_, kind)| **kind == CfgKind::Feature)
+            .map(|(name, _)| name.clone())
+            .collect::<BTreeSet<_>>();
+
+        let undeclared = used
+            .iter()
+            .filter(|name| !manifest.features.contains_key(*name))
+            .map(|name| {
+                let (feature, negated) = (
+                    Feature {
+                        name: name.clone(),
+                        not: false,
+                    },
+                    Feature {
+                        name: name.clone(),
+                        not: true,
+                    },
+                );
+                let spans = artifacts_tree
+                    .graph
+                    .node_weights()
+                    .filter(|artifact_node| {
+                        artifact_node.complex_feature.contains(&feature)
+                            || artifact_node.complex_feature.contains(&negated)
+                    })
+                    .filter_map(|artifact_node| artifact_node.span.clone())
+                    .collect();
+                UndeclaredFeature {
+                    name: name.clone(),
+                    spans,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let mut unused = Vec::new();
+        let mut forwarding = Vec::new();
+        for (name, enables) in &manifest.features {
+            if name == "default" || used.contains(name) {
+                continue;
+            }
+            match enables.is_empty() {
+                true => unused.push(name.clone()),
+                false => forwarding.push(ForwardingFeature {
+                    name: name.clone(),
+                    enables: enables.clone(),
+                }),
+            }
+        }
+
+        FeatureLints {
+            clean: undeclared.is_empty() && unused.is_empty(),
+            undeclared,
+            unused,
+            forwarding,
+        }
+    }
+}
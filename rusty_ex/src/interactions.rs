@@ -0,0 +1,250 @@
+use rustworkx_core::petgraph::Direction;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::types::{
+    ArtifactIndex, ArtifactKey, ArtifactNode, ArtifactsTree, ComplexFeature, Feature, SourceSpan,
+    TermWeight,
+};
+use crate::GLOBAL_NODE_INDEX;
+
+/// How the features of an interaction are combined
+#[derive(Debug, Clone, Eq, Hash, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum InteractionKind {
+    /// Features in the same `all(..)`, all of them must be enabled
+    Conjunction,
+    /// Features in the same `any(..)`, at least one of them must be enabled
+    Disjunction,
+    /// Feature (first) enclosing the code annotated by another feature (second)
+    Nesting,
+    /// Feature combined (by any of the above) with its own negation
+    Negation,
+}
+
+/// Artifact in which an interaction occurs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InteractionOccurrence {
+    pub ident: Option<String>,
+    pub span: Option<SourceSpan>,
+    /// Complex feature annotating the artifact
+    pub complex_feature: String,
+    pub weight: f64,
+}
+
+/// Pair or t-tuple of interacting features, with all the artifacts in which it occurs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Interaction {
+    pub kind: InteractionKind,
+    /// Sorted by name, except for nesting (enclosing feature first)
+    pub features: Vec<Feature>,
+    /// Total weight of the artifacts in which the interaction occurs
+    pub weight: f64,
+    pub occurrences: Vec<InteractionOccurrence>,
+}
+
+/// Every interaction between features of a crate (see `--print-interactions`)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Interactions {
+    pub interactions: Vec<Interaction>,
+    /// Number of artifacts annotated by each feature (`!name` if negated)
+    pub annotated: BTreeMap<String, usize>,
+}
+
+impl Interactions {
+    /// Extract the interactions from the complex feature of each artifact (conjunctions and
+    /// disjunctions) and from the chain of its annotated ancestors (nestings)
+    pub fn new<Key: ArtifactKey>(artifacts_tree: &ArtifactsTree<Key>) -> Self {
+        let mut found = BTreeMap::<(InteractionKind, Vec<Feature>), Vec<ArtifactIndex>>::new();
+        let mut annotated = BTreeMap::new();
+
+        for index in artifacts_tree.graph.node_indices() {
+            if index == ArtifactIndex::new(GLOBAL_NODE_INDEX) {
+                continue;
+            }
+            let complex_feature = &artifacts_tree.graph[index].complex_feature;
+
+            let mut tuples = Vec::new();
+            groups(complex_feature, &mut tuples);
+
+            let features = leaves(complex_feature);
+            for feature in &features {
+                *annotated.entry(feature.to_string()).or_insert(0) += 1;
+            }
+
+            // edges go from the enclosed artifact to the enclosing one
+            let mut parent = artifacts_tree
+                .graph
+                .neighbors_directed(index, Direction::Outgoing)
+                .next();
+            while let Some(ancestor) = parent {
+                if ancestor == ArtifactIndex::new(GLOBAL_NODE_INDEX) {
+                    break;
+                }
+                for outer in leaves(&artifacts_tree.graph[ancestor].complex_feature) {
+                    for inner in features.iter().filter(|inner| **inner != outer) {
+                        tuples.push((InteractionKind::Nesting, vec![outer.clone(), inner.clone()]));
+                    }
+                }
+                parent = artifacts_tree
+                    .graph
+                    .neighbors_directed(ancestor, Direction::Outgoing)
+                    .next();
+            }
+
+            let negations = tuples
+                .iter()
+                .flat_map(|(_, features)| {
+                    features
+                        .iter()
+                        .filter(|feature| !feature.not)
+                        .filter(|feature| {
+                            features
+                                .iter()
+                                .any(|other| other.not && other.name == feature.name)
+                        })
+                        .map(|feature| {
+                            let negated = Feature {
+                                name: feature.name.clone(),
+                                not: true,
+                            };
+                            (InteractionKind::Negation, vec![feature.clone(), negated])
+                        })
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>();
+            tuples.extend(negations);
+
+            // the same interaction occurs at most once per artifact
+            for tuple in tuples.into_iter().collect::<BTreeSet<_>>() {
+                found.entry(tuple).or_default().push(index);
+            }
+        }
+
+        let interactions = found
+            .into_iter()
+            .map(|((kind, features), indices)| {
+                let occurrences = indices
+                    .into_iter()
+                    .map(|index| occurrence(&artifacts_tree.graph[index]))
+                    .collect::<Vec<_>>();
+                Interaction {
+                    kind,
+                    features,
+                    weight: occurrences.iter().map(|occurrence| occurrence.weight).sum(),
+                    occurrences,
+                }
+            })
+            .collect();
+
+        Interactions {
+            interactions,
+            annotated,
+        }
+    }
+
+    /// Symmetric co-occurrence matrix in CSV format: the number of artifacts in which two features
+    /// interact, the diagonal is the number of artifacts annotated by the feature (negations are
+    /// not counted twice, they are already a conjunction, disjunction or nesting)
+    pub fn to_csv(&self) -> String {
+        let mut features = self.annotated.keys().cloned().collect::<BTreeSet<_>>();
+        let mut counts = BTreeMap::<(String, String), usize>::new();
+        for interaction in self
+            .interactions
+            .iter()
+            .filter(|interaction| interaction.kind != InteractionKind::Negation)
+        {
+            let names = interaction
+                .features
+                .iter()
+                .map(|feature| feature.to_string())
+                .collect::<Vec<_>>();
+            for (i, a) in names.iter().enumerate() {
+                for b in names[i + 1..].iter().filter(|b| *b != a) {
+                    *counts.entry((a.clone(), b.clone())).or_insert(0) +=
+                        interaction.occurrences.len();
+                    *counts.entry((b.clone(), a.clone())).or_insert(0) +=
+                        interaction.occurrences.len();
+                }
+            }
+            features.extend(names);
+        }
+
+        let mut csv = std::iter::once(String::new())
+            .chain(features.iter().map(|feature| csv_field(feature)))
+            .collect::<Vec<_>>()
+            .join(",");
+        for a in &features {
+            csv.push('\n');
+            csv.push_str(&csv_field(a));
+            for b in &features {
+                let count = match a == b {
+                    true => self.annotated.get(a),
+                    false => counts.get(&(a.clone(), b.clone())),
+                };
+                csv.push_str(&format!(",{}", count.copied().unwrap_or(0)));
+            }
+        }
+        csv
+    }
+}
+
+/// Features (simple) appearing in a complex feature, without duplicates
+fn leaves(complex_feature: &ComplexFeature<Feature>) -> Vec<Feature> {
+    match complex_feature {
+        ComplexFeature::None => vec![],
+        ComplexFeature::Simple(feature) => vec![feature.clone()],
+        ComplexFeature::All(features) | ComplexFeature::Any(features) => features
+            .iter()
+            .flat_map(leaves)
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect(),
+    }
+}
+
+/// Conjunctions and disjunctions of (at least two) features in a complex feature, at any depth.
+/// Each one is made of its simple operands, the nested conjunctions and disjunctions are groups
+/// of their own (e.g., `all(a, any(b, c))` is only the disjunction of `b` and `c`)
+fn groups(
+    complex_feature: &ComplexFeature<Feature>,
+    tuples: &mut Vec<(InteractionKind, Vec<Feature>)>,
+) {
+    let (kind, features) = match complex_feature {
+        ComplexFeature::None | ComplexFeature::Simple(_) => return,
+        ComplexFeature::All(features) => (InteractionKind::Conjunction, features),
+        ComplexFeature::Any(features) => (InteractionKind::Disjunction, features),
+    };
+    let operands = features
+        .iter()
+        .filter_map(|feature| match feature {
+            ComplexFeature::Simple(feature) => Some(feature.clone()),
+            _ => None,
+        })
+        .collect::<BTreeSet<_>>();
+    if operands.len() > 1 {
+        tuples.push((kind, operands.into_iter().collect()));
+    }
+    for feature in features {
+        groups(feature, tuples);
+    }
+}
+
+fn occurrence<Key: ArtifactKey>(artifact_node: &ArtifactNode<Key>) -> InteractionOccurrence {
+    InteractionOccurrence {
+        ident: artifact_node.ident.clone(),
+        span: artifact_node.span.clone(),
+        complex_feature: artifact_node.complex_feature.to_string(),
+        weight: match artifact_node.weight {
+            TermWeight::Weight(weight) => weight,
+            _ => 0.0,
+        },
+    }
+}
+
+/// Quote a CSV field if needed
+fn csv_field(field: &str) -> String {
+    match field.contains([',', '"', '\n']) {
+        true => format!("\"{}\"", field.replace('"', "\"\"")),
+        false => field.to_string(),
+    }
+}
@@ -1,35 +1,82 @@
 #![feature(rustc_private)]
 
+pub mod analysis;
 pub mod configs;
+pub mod constraints;
+pub mod coverage;
+pub mod dead_code;
+pub mod errors;
+pub mod explain;
+pub mod export;
+pub mod feature_lint;
 pub mod instrument;
+pub mod interactions;
+pub mod manifest;
+pub mod minimum_cover;
+pub mod mir;
+pub mod output;
+pub mod polarity;
+pub mod report;
+pub mod resolution;
 pub mod types;
 mod utils;
+pub mod weights;
 
 extern crate rustc_ast;
+extern crate rustc_data_structures;
 extern crate rustc_driver;
 extern crate rustc_errors;
+extern crate rustc_hir;
 extern crate rustc_interface;
+extern crate rustc_lexer;
 extern crate rustc_middle;
 extern crate rustc_session;
 extern crate rustc_span;
 
+use analysis::AnalysisResult;
 use clap::Parser;
-use configs::centrality::{Centrality, CentralityKind};
+use configs::centrality::{
+    ranked_table, Centrality, CentralityKind, CentralityMethod, CentralityParams, ClosenessKind,
+};
 use configs::config_generator::ConfigGenerator;
-use configs::prop_formula::{ConversionMethod, Ordinal, ToPropFormula};
+use configs::config_solver::ConfigSolver;
+use configs::prop_formula::{Ordinal, PropFormula};
 use configs::CnfFormula;
+use constraints::CompileErrorGuard;
+use coverage::{Configuration, Coverage};
+use dead_code::dead_artifacts;
+use errors::RustyExError;
+use explain::FeatureExplanation;
+use export::GraphFormat;
+use feature_lint::FeatureLints;
 use instrument::{CrateFilter, RustcPlugin, RustcPluginArgs, Utf8Path};
+use interactions::Interactions;
 use linked_hash_set::LinkedHashSet;
+use manifest::ManifestFeatures;
+use minimum_cover::minimum_cover;
+use output::Output;
+use polarity::PolarityGraph;
+use resolution::{MethodTargets, Resolutions};
 use rustc_ast::{ast::*, visit::*};
+use rustc_span::def_id::LOCAL_CRATE;
+use rustc_span::source_map::SourceMap;
 use rustc_span::symbol::*;
+use rustc_span::Span;
 use rustworkx_core::dag_algo::longest_path;
 use rustworkx_core::petgraph::graph::{DiGraph, NodeIndex};
+use rustworkx_core::petgraph::visit::IntoNodeReferences;
+use rustworkx_core::petgraph::Direction;
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet};
+use std::collections::{hash_map::Entry, HashMap, HashSet};
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 use std::{borrow::Cow, env};
 use std::{fs, io, panic};
 use types::*;
+use weights::{
+    DefaultWeightModel, DependencyWeights, RecoveryWeight, TomlWeightModel, WeightMode, WeightModel,
+};
 
 // This struct is the plugin provided to the rustc_plugin framework,
 // and it must be exported for use by the CLI/driver binaries.
@@ -55,6 +102,55 @@ pub struct PrintAstArgs {
     #[clap(long)]
     print_artifacts_tree: bool,
 
+    /// Pass --graph-format followed by the format of the printed graphs: dot, graphml, gexf or
+    /// json (networkx node-link) (default: dot)
+    #[clap(long, value_enum)]
+    graph_format: Option<GraphFormat>,
+
+    /// Pass --print-interactions to print every interaction between features (conjunction,
+    /// disjunction, nesting and negation), with the artifacts in which it occurs, in JSON format
+    #[clap(long)]
+    print_interactions: bool,
+
+    /// Pass --print-interactions-matrix to print the co-occurrence matrix of the features in CSV format
+    #[clap(long)]
+    print_interactions_matrix: bool,
+
+    /// Pass --print-dead-code to print the artifacts that can never be compiled, because their
+    /// `cfg`s conflict with the ones of the code enclosing them, with the minimal conflicting `cfg`s
+    #[clap(long)]
+    print_dead_code: bool,
+
+    /// Pass --manifest-constraints to consider the dependencies between the features declared in
+    /// the manifest (`[features]`) when looking for dead code and for the minimum configurations
+    #[clap(long)]
+    manifest_constraints: bool,
+
+    /// Pass --lint-features to print, in JSON format, the features used in a `cfg` but not declared
+    /// in the manifest, and the declared ones not used in any `cfg` (unused or only forwarding)
+    #[clap(long)]
+    lint_features: bool,
+
+    /// Pass --coverage followed by the path of a JSON file with a list of configurations (each one
+    /// a list of features or a string of cargo arguments) to print the artifacts they compile
+    #[clap(long)]
+    coverage: Option<String>,
+
+    /// Pass --min-configurations to print, in JSON format, the smallest set of valid configurations
+    /// (not enabling a `compile_error!`) compiling every artifact that can be compiled
+    #[clap(long)]
+    min_configurations: bool,
+
+    /// Pass --min-configurations-weighted to cover the heaviest artifacts first when looking for
+    /// the minimum configurations
+    #[clap(long, requires = "min_configurations")]
+    min_configurations_weighted: bool,
+
+    /// Pass --min-configurations-time-limit followed by the seconds to spend improving the
+    /// minimum configurations, after the first set is found (default: 10)
+    #[clap(long, requires = "min_configurations", value_parser = parse_time_limit)]
+    min_configurations_time_limit: Option<Duration>,
+
     /// Pass --print-crate to print the crate AST
     #[clap(long)]
     print_crate: bool,
@@ -63,23 +159,162 @@ pub struct PrintAstArgs {
     #[clap(long, value_enum)]
     serialized_centrality: Option<CentralityKind>,
 
+    /// Pass --print-centrality to print the features ranked by centrality in a table
+    #[clap(long)]
+    print_centrality: bool,
+
+    /// Pass --print-features-centrality to print the centrality of each feature in JSON format
+    #[clap(long)]
+    print_features_centrality: bool,
+
+    /// Pass --rank-by followed by the centrality measure used to rank the features (default: katz)
+    #[clap(long, value_enum)]
+    rank_by: Option<CentralityMethod>,
+
+    /// Pass --config-budget followed by the maximum number of configurations to generate, covering
+    /// the features ranked by --rank-by (default: 5)
+    #[clap(long)]
+    config_budget: Option<usize>,
+
+    /// Pass --unify-polarity to compute the centrality of each real feature, with a single node
+    /// for a feature and its negation (`a` and `!a`) and the polarity on the edges
+    #[clap(long)]
+    unify_polarity: bool,
+
+    /// Pass --polarity-split with --unify-polarity to split the centrality of each real feature
+    /// into the centrality of the feature and of its negation in the Features Graph
+    #[clap(long, requires = "unify_polarity")]
+    polarity_split: bool,
+
+    /// Pass --print-polarity-graph to print the Polarity Graph (one node for each real feature,
+    /// the polarity of the features is on the edges) in DOT format
+    #[clap(long)]
+    print_polarity_graph: bool,
+
+    /// Pass --explain-feature followed by a feature name (`!name` if negated) to print everything
+    /// known about it: artifacts, complex features, parents and children, gated features and ranks
+    #[clap(long)]
+    explain_feature: Option<Feature>,
+
+    /// Pass --resolve-calls to link calls to the exact item they target using name resolution
+    /// (the weight of the items with the same name is used only as a fallback)
+    #[clap(long)]
+    resolve_calls: bool,
+
+    /// Pass --weight-mode followed by the source of the weights of the artifacts (default: ast)
+    #[clap(long, value_enum)]
+    weight_mode: Option<WeightMode>,
+
+    /// Pass --weight-model followed by the path of a TOML file overriding the weight of the kinds of terms
+    #[clap(long)]
+    weight_model: Option<String>,
+
+    /// Pass --recovery-weight followed by the weight of the terms that cannot be resolved:
+    /// mean, median or p<percentile> of the weights of the resolved functions, or a non-negative number (default: 7.0)
+    #[clap(long)]
+    recovery_weight: Option<RecoveryWeight>,
+
+    /// Pass --dependency-weights followed by the path of a TOML file with the weights of the items of other crates
+    #[clap(long)]
+    dependency_weights: Option<String>,
+
+    /// Pass --print-estimated-weights to print the weight of each feature and how much of it is estimated in JSON format
+    #[clap(long)]
+    print_estimated_weights: bool,
+
     /// Pass --print-serialized-graphs to print all extracted graphs serialized
     #[clap(long)]
     print_serialized_graphs: bool,
 
+    /// Pass --print-html-report to print a self-contained HTML report of the features graph,
+    /// the artifacts tree and the centrality of the features
+    #[clap(long)]
+    print_html_report: bool,
+
     /// Pass --print-metadata to print the metadata of the extracted data
     #[clap(long)]
     print_metadata: bool,
 
+    /// Pass --print-configs to print the generated configurations in JSON format (always
+    /// written with --output-dir)
+    #[clap(long)]
+    print_configs: bool,
+
+    /// Pass --output-dir followed by a directory to write each output in its own file
+    /// (one subdirectory per crate, with an index) instead of printing it
+    #[clap(long)]
+    output_dir: Option<PathBuf>,
+
+    /// Pass --katz-alpha followed by the attenuation factor of Katz centrality
+    #[clap(long)]
+    katz_alpha: Option<f64>,
+
+    /// Pass --katz-beta followed by the immediate neighbourhood weight of Katz centrality
+    #[clap(long)]
+    katz_beta: Option<f64>,
+
+    /// Pass --katz-max-iter followed by the maximum number of iterations of Katz centrality
+    #[clap(long)]
+    katz_max_iter: Option<usize>,
+
+    /// Pass --katz-tolerance followed by the convergence tolerance of Katz centrality
+    #[clap(long)]
+    katz_tolerance: Option<f64>,
+
+    /// Pass --eigenvector-max-iter followed by the maximum number of iterations of Eigenvector centrality
+    #[clap(long)]
+    eigenvector_max_iter: Option<usize>,
+
+    /// Pass --eigenvector-tolerance followed by the convergence tolerance of Eigenvector centrality (default: 1e-2)
+    #[clap(long)]
+    eigenvector_tolerance: Option<f64>,
+
+    /// Pass --closeness followed by the variant of closeness centrality (default: weighted)
+    #[clap(long, value_enum)]
+    closeness: Option<ClosenessKind>,
+
+    /// Pass --pagerank-damping followed by the damping factor of PageRank (default: 0.85)
+    #[clap(long)]
+    pagerank_damping: Option<f64>,
+
+    /// Pass --pagerank-max-iter followed by the maximum number of iterations of PageRank (default: 100)
+    #[clap(long)]
+    pagerank_max_iter: Option<usize>,
+
+    /// Pass --pagerank-tolerance followed by the convergence tolerance of PageRank (default: 1e-6)
+    #[clap(long)]
+    pagerank_tolerance: Option<f64>,
+
     #[clap(last = true)]
     // mytool --allcaps -- some extra args here
     //                     ^^^^^^^^^^^^^^^^^^^^ these are cargo args
     cargo_args: Vec<String>,
 }
 
+/// Parse a time limit in seconds, not negative and representable as a `Duration` (e.g., `2.5`)
+fn parse_time_limit(s: &str) -> Result<Duration, String> {
+    s.parse::<f64>()
+        .ok()
+        .and_then(|seconds| Duration::try_from_secs_f64(seconds).ok())
+        .ok_or(format!(
+            "invalid time limit '{}', expected a non-negative number of seconds",
+            s
+        ))
+}
+
 impl clap::ValueEnum for CentralityKind {
     fn value_variants<'a>() -> &'a [Self] {
-        &[Self::All, Self::Katz, Self::Closeness, Self::Eigenvector]
+        &[
+            Self::All,
+            Self::Katz,
+            Self::Closeness,
+            Self::Eigenvector,
+            Self::PageRank,
+            Self::Betweenness,
+            Self::InDegree,
+            Self::OutDegree,
+            Self::Harmonic,
+        ]
     }
 
     fn to_possible_value(&self) -> Option<clap::builder::PossibleValue> {
@@ -88,6 +323,110 @@ impl clap::ValueEnum for CentralityKind {
             Self::Katz => Some(clap::builder::PossibleValue::new("katz")),
             Self::Closeness => Some(clap::builder::PossibleValue::new("closeness")),
             Self::Eigenvector => Some(clap::builder::PossibleValue::new("eigenvector")),
+            Self::PageRank => Some(clap::builder::PossibleValue::new("pagerank")),
+            Self::Betweenness => Some(clap::builder::PossibleValue::new("betweenness")),
+            Self::InDegree => Some(clap::builder::PossibleValue::new("in-degree")),
+            Self::OutDegree => Some(clap::builder::PossibleValue::new("out-degree")),
+            Self::Harmonic => Some(clap::builder::PossibleValue::new("harmonic")),
+        }
+    }
+}
+
+impl clap::ValueEnum for WeightMode {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[
+            Self::Ast,
+            Self::Mir,
+            Self::Lines,
+            Self::SourceLines,
+            Self::Tokens,
+        ]
+    }
+
+    fn to_possible_value(&self) -> Option<clap::builder::PossibleValue> {
+        match self {
+            Self::Ast => Some(clap::builder::PossibleValue::new("ast")),
+            Self::Mir => Some(clap::builder::PossibleValue::new("mir")),
+            Self::Lines => Some(clap::builder::PossibleValue::new("lines")),
+            Self::SourceLines => Some(clap::builder::PossibleValue::new("source-lines")),
+            Self::Tokens => Some(clap::builder::PossibleValue::new("tokens")),
+        }
+    }
+}
+
+impl clap::ValueEnum for CentralityMethod {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[
+            Self::Katz,
+            Self::Closeness,
+            Self::Eigenvector,
+            Self::PageRank,
+            Self::Betweenness,
+            Self::InDegree,
+            Self::OutDegree,
+            Self::Harmonic,
+        ]
+    }
+
+    fn to_possible_value(&self) -> Option<clap::builder::PossibleValue> {
+        match self {
+            Self::Katz => Some(clap::builder::PossibleValue::new("katz")),
+            Self::Closeness => Some(clap::builder::PossibleValue::new("closeness")),
+            Self::Eigenvector => Some(clap::builder::PossibleValue::new("eigenvector")),
+            Self::PageRank => Some(clap::builder::PossibleValue::new("pagerank")),
+            Self::Betweenness => Some(clap::builder::PossibleValue::new("betweenness")),
+            Self::InDegree => Some(clap::builder::PossibleValue::new("in-degree")),
+            Self::OutDegree => Some(clap::builder::PossibleValue::new("out-degree")),
+            Self::Harmonic => Some(clap::builder::PossibleValue::new("harmonic")),
+        }
+    }
+}
+
+impl clap::ValueEnum for GraphFormat {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[Self::Dot, Self::GraphMl, Self::Gexf, Self::NodeLink]
+    }
+
+    fn to_possible_value(&self) -> Option<clap::builder::PossibleValue> {
+        match self {
+            Self::Dot => Some(clap::builder::PossibleValue::new("dot")),
+            Self::GraphMl => Some(clap::builder::PossibleValue::new("graphml")),
+            Self::Gexf => Some(clap::builder::PossibleValue::new("gexf")),
+            Self::NodeLink => Some(clap::builder::PossibleValue::new("json")),
+        }
+    }
+}
+
+impl clap::ValueEnum for ClosenessKind {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[Self::Weighted, Self::Unweighted]
+    }
+
+    fn to_possible_value(&self) -> Option<clap::builder::PossibleValue> {
+        match self {
+            Self::Weighted => Some(clap::builder::PossibleValue::new("weighted")),
+            Self::Unweighted => Some(clap::builder::PossibleValue::new("unweighted")),
+        }
+    }
+}
+
+impl PrintAstArgs {
+    /// Parameters of the centrality measures, missing arguments fall back to the defaults
+    fn centrality_params(&self) -> CentralityParams {
+        let default = CentralityParams::default();
+        CentralityParams {
+            katz_alpha: self.katz_alpha.or(default.katz_alpha),
+            katz_beta: self.katz_beta.or(default.katz_beta),
+            katz_max_iter: self.katz_max_iter.or(default.katz_max_iter),
+            katz_tolerance: self.katz_tolerance.or(default.katz_tolerance),
+            eigenvector_max_iter: self.eigenvector_max_iter.or(default.eigenvector_max_iter),
+            eigenvector_tolerance: self.eigenvector_tolerance.or(default.eigenvector_tolerance),
+            closeness: self.closeness.clone().unwrap_or(default.closeness),
+            pagerank_damping: self.pagerank_damping.unwrap_or(default.pagerank_damping),
+            pagerank_max_iter: self.pagerank_max_iter.unwrap_or(default.pagerank_max_iter),
+            pagerank_tolerance: self
+                .pagerank_tolerance
+                .unwrap_or(default.pagerank_tolerance),
         }
     }
 }
@@ -131,6 +470,14 @@ impl RustcPlugin for RustcEx {
         #[cfg(not(feature = "test-mode"))]
         let args = PrintAstArgs::parse_from(env::args());
 
+        // the driver does not run in the current directory
+        let mut args = args;
+        args.output_dir = args.output_dir.map(|dir| {
+            env::current_dir()
+                .expect("Error: cannot get the current directory")
+                .join(dir)
+        });
+
         let filter = CrateFilter::AllCrates;
         RustcPluginArgs { args, filter }
     }
@@ -140,45 +487,287 @@ impl RustcPlugin for RustcEx {
     fn run(self, compiler_args: Vec<String>, plugin_args: Self::Args) {
         log::debug!("Running plugin with compiler args: {:?}", compiler_args);
         log::debug!("Running plugin with args: {:?}", plugin_args);
-        let mut callbacks = PrintAstCallbacks { args: plugin_args };
+        let mut callbacks = PrintAstCallbacks::new(plugin_args, &compiler_args);
         rustc_driver::run_compiler(&compiler_args, &mut callbacks)
     }
 }
 
 struct PrintAstCallbacks {
     args: PrintAstArgs,
+    /// MIR weight of each item by resolved path (only with `--weight-mode mir`)
+    mir_weights: Option<Result<HashMap<String, f64>, RustyExError>>,
+    /// Target of each method call (only with `--resolve-calls`)
+    method_targets: Option<MethodTargets>,
+    /// Keep the result of the analysis instead of printing it (see `analysis::analyze`)
+    keep_result: bool,
+    /// Result of the analysis, set after the expansion if `keep_result` is set
+    result: Option<AnalysisResult>,
 }
 
 impl PrintAstCallbacks {
+    fn new(args: PrintAstArgs, compiler_args: &[String]) -> Self {
+        // MIR-based weights need a compilation with the actual `cfg`s, see `mir::mir_weights`
+        let mir_weights = match args.weight_mode {
+            Some(WeightMode::Mir) => Some(mir::mir_weights(compiler_args)),
+            _ => None,
+        };
+        // methods are resolved during type checking, see `resolution::method_targets`
+        let method_targets = args
+            .resolve_calls
+            .then(|| resolution::method_targets(compiler_args));
+        PrintAstCallbacks {
+            args,
+            mir_weights,
+            method_targets,
+            keep_result: false,
+            result: None,
+        }
+    }
+
     fn process_cli_args(
         &self,
         collector: &CollectVisitor,
         krate: &Crate,
+        crate_name: &str,
         centrality: Centrality<u32>,
+        output: &mut Output,
     ) {
         if self.args.print_crate {
-            println!("{:#?}", krate);
+            output.emit("crate", "crate.txt", &format!("{:#?}", krate));
         }
+        let format = self.args.graph_format.clone().unwrap_or_default();
+        let rank_by = self.args.rank_by.clone().unwrap_or_default();
+        let features = centrality.ranked_features(&rank_by, &collector.cfg_kinds);
         if self.args.print_terms_tree {
-            collector.terms_tree.print_dot();
+            output.emit(
+                "terms_tree",
+                &format!("terms.{}", format.extension()),
+                &format.render(
+                    || collector.terms_tree.to_dot(),
+                    || collector.terms_tree.export_graph(),
+                ),
+            );
         }
         if self.args.print_features_graph {
-            collector.squash_feature_graph_edges().print_dot();
+            let features_graph = collector.squash_feature_graph_edges();
+            output.emit(
+                "features_graph",
+                &format!("features.{}", format.extension()),
+                &format.render(
+                    || features_graph.to_dot(),
+                    || features_graph.export_graph(false, &features),
+                ),
+            );
         }
         if self.args.print_features_multigraph {
-            collector.features_graph.print_dot();
+            output.emit(
+                "features_multigraph",
+                &format!("features_multigraph.{}", format.extension()),
+                &format.render(
+                    || collector.features_graph.to_dot(),
+                    || collector.features_graph.export_graph(true, &features),
+                ),
+            );
+        }
+        if self.args.print_polarity_graph {
+            let polarity_graph = PolarityGraph::new(&collector.features_graph);
+            // the centrality of the real features is only known with `--unify-polarity`
+            let centrality = match self.args.unify_polarity {
+                true => features.as_slice(),
+                false => &[],
+            };
+            output.emit(
+                "polarity_graph",
+                &format!("polarity.{}", format.extension()),
+                &format.render(
+                    || polarity_graph.to_dot(),
+                    || polarity_graph.export_graph(centrality),
+                ),
+            );
         }
         if self.args.print_artifacts_tree {
-            collector.artifacts_tree.print_dot();
+            output.emit(
+                "artifacts_tree",
+                &format!("artifacts.{}", format.extension()),
+                &format.render(
+                    || collector.artifacts_tree.to_dot(),
+                    || collector.artifacts_tree.export_graph(),
+                ),
+            );
+        }
+        if self.args.print_interactions || self.args.print_interactions_matrix {
+            let interactions = Interactions::new(&collector.artifacts_tree);
+            if self.args.print_interactions {
+                output.emit(
+                    "interactions",
+                    "interactions.json",
+                    &serde_json::to_string(&interactions).expect("Error: cannot serialize data"),
+                );
+            }
+            if self.args.print_interactions_matrix {
+                output.emit(
+                    "interactions_matrix",
+                    "interactions.csv",
+                    &interactions.to_csv(),
+                );
+            }
+        }
+        if self.args.print_dead_code {
+            let constraints = match self.args.manifest_constraints {
+                true => match ManifestFeatures::from_env() {
+                    Ok(manifest) => Some(manifest.to_prop_formula()),
+                    Err(error) => {
+                        errors::print_diagnostics(&[error]);
+                        None
+                    }
+                },
+                false => None,
+            };
+            let mut interrupted = Vec::new();
+            let dead = dead_artifacts(
+                &collector.artifacts_tree,
+                constraints.as_ref(),
+                &mut interrupted,
+            )
+            .iter()
+            .map(|dead| dead.to_string())
+            .collect::<Vec<_>>();
+            errors::print_diagnostics(&interrupted);
+            output.emit("dead_code", "dead_code.txt", &dead.join("\n"));
+        }
+        if self.args.lint_features {
+            match ManifestFeatures::from_env() {
+                Ok(manifest) => {
+                    let lints = FeatureLints::new(
+                        &manifest,
+                        &collector.cfg_kinds,
+                        &collector.artifacts_tree,
+                    );
+                    output.emit(
+                        "feature_lints",
+                        "feature_lints.json",
+                        &serde_json::to_string(&lints).expect("Error: cannot serialize data"),
+                    );
+                }
+                Err(error) => errors::print_diagnostics(&[error]),
+            }
+        }
+        if let Some(path) = &self.args.coverage {
+            let manifest = ManifestFeatures::from_env()
+                .map_err(|error| errors::print_diagnostics(&[error]))
+                .ok();
+            let used = collector
+                .cfg_kinds
+                .iter()
+                .filter(|(_, kind)| **kind == CfgKind::Feature)
+                .map(|(name, _)| name.clone())
+                .collect();
+            match Configuration::from_file(path, manifest.as_ref(), &used) {
+                Ok(configurations) => {
+                    let coverage = Coverage::new(&collector.artifacts_tree, &configurations);
+                    output.emit(
+                        "coverage",
+                        "coverage.json",
+                        &serde_json::to_string(&coverage).expect("Error: cannot serialize data"),
+                    );
+                }
+                Err(error) => errors::print_diagnostics(&[error]),
+            }
+        }
+        if self.args.min_configurations {
+            let mut constraints = collector
+                .compile_error_guards
+                .iter()
+                .map(|guard| guard.to_prop_formula())
+                .collect::<Vec<_>>();
+            if self.args.manifest_constraints {
+                match ManifestFeatures::from_env() {
+                    Ok(manifest) => constraints.push(manifest.to_prop_formula()),
+                    Err(error) => errors::print_diagnostics(&[error]),
+                }
+            }
+            let mut interrupted = Vec::new();
+            let cover = minimum_cover(
+                &collector.artifacts_tree,
+                &constraints,
+                &collector.cfg_kinds,
+                self.args.min_configurations_weighted,
+                self.args
+                    .min_configurations_time_limit
+                    .unwrap_or(Duration::from_secs(10)),
+                &mut interrupted,
+            );
+            errors::print_diagnostics(&interrupted);
+            output.emit(
+                "min_configurations",
+                "min_configurations.json",
+                &serde_json::to_string(&cover).expect("Error: cannot serialize data"),
+            );
+        }
+        if self.args.print_centrality
+            || self.args.print_features_centrality
+            || self.args.print_html_report
+        {
+            if self.args.print_centrality {
+                output.emit(
+                    "centrality_table",
+                    "centrality.txt",
+                    &ranked_table(&features),
+                );
+            }
+            if self.args.print_features_centrality {
+                output.emit(
+                    "centrality",
+                    "centrality.json",
+                    &serde_json::to_string(&features).expect("Error: cannot serialize data"),
+                );
+            }
+            if self.args.print_html_report {
+                let report = report::html_report(
+                    crate_name,
+                    &collector.squash_feature_graph_edges(),
+                    &collector.artifacts_tree,
+                    &features,
+                );
+                output.emit("html_report", "report.html", &report);
+            }
+        }
+        if let Some(feature) = &self.args.explain_feature {
+            let explanation = FeatureExplanation::new(
+                feature,
+                &collector.squash_feature_graph_edges(),
+                &collector.artifacts_tree,
+                &centrality,
+                &collector.cfg_kinds,
+            );
+            match explanation {
+                Some(explanation) => {
+                    output.emit("explain_feature", "explain.txt", &explanation.to_string())
+                }
+                None => errors::print_diagnostics(&[RustyExError::UnknownFeature {
+                    name: feature.to_string(),
+                }]),
+            }
         }
         if let Some(centrality_kind) = &self.args.serialized_centrality {
-            collector.serialized_centrality(centrality, centrality_kind);
+            output.emit(
+                "serialized_centrality",
+                "serialized_centrality.json",
+                &collector.serialized_centrality(centrality, centrality_kind),
+            );
+        }
+        if self.args.print_estimated_weights {
+            output.emit(
+                "estimated_weights",
+                "estimated_weights.json",
+                &collector.estimated_weights(),
+            );
         }
         if self.args.print_serialized_graphs {
-            collector.print_serialized_graphs();
+            output.emit("graphs", "graphs.json", &collector.serialized_graphs());
         }
         if self.args.print_metadata {
-            collector.print_metadata();
+            output.emit("metadata", "metadata.json", &collector.metadata());
         }
     }
 }
@@ -194,20 +783,22 @@ impl rustc_driver::Callbacks for PrintAstCallbacks {
             }
 
             fn read_file(&self, path: &std::path::Path) -> io::Result<String> {
-                let content = fs::read_to_string(path)?;
-                Ok(content
+                let content = fs::read_to_string(path)?
                     // Features are discarded before the `after_expansion` hook, so are lost.
                     // To avoid this, we replace all `cfg` directives with a custom config.
                     .replace("#[cfg(", "#[rustex_cfg(")
                     // The `cfg!` macro is evaluated before the `after_expansion` hook, so we replace it with a custom one.
                     // The replacement is not a macro because the macro would still be evaluated before the hook,
                     // giving an error in the AST.
-                    .replace("cfg!", "rustex_cfg"))
+                    .replace("cfg!", "rustex_cfg");
+                // The `compile_error!` macro is expanded (and removed) before the hook, but its
+                // `cfg` is a constraint between the features, so we replace it with a constant.
+                Ok(utils::rewrite_compile_error(&content))
             }
 
-            fn read_binary_file(&self, _path: &std::path::Path) -> io::Result<Arc<[u8]>> {
-                // TODO: fare il replace anche nella lettura di file binari
-                todo!()
+            fn read_binary_file(&self, path: &std::path::Path) -> io::Result<Arc<[u8]>> {
+                // Binary files (e.g., `include_bytes!`) cannot contain `cfg` directives
+                Ok(fs::read(path)?.into())
             }
         }
 
@@ -244,8 +835,27 @@ impl rustc_driver::Callbacks for PrintAstCallbacks {
         let resolver_and_krate = tcx.resolver_for_lowering().borrow();
         let krate = &*resolver_and_krate.1;
 
+        let mut errors = Vec::new();
+        let weight_model: Box<dyn WeightModel> = match &self.args.weight_model {
+            Some(path) => match TomlWeightModel::from_file(path) {
+                Ok(model) => Box::new(model),
+                Err(error) => {
+                    errors.push(error);
+                    Box::new(DefaultWeightModel)
+                }
+            },
+            None => Box::new(DefaultWeightModel),
+        };
+        let dependency_weights = match &self.args.dependency_weights {
+            Some(path) => DependencyWeights::from_file(path).unwrap_or_else(|error| {
+                errors.push(error);
+                DependencyWeights::default()
+            }),
+            None => DependencyWeights::default(),
+        };
+
         // visit AST
-        let collector = &mut CollectVisitor {
+        let mut collector = CollectVisitor {
             node_id_incr: 1, // 0 is reserved for global scope
             stack: Vec::new(),
 
@@ -254,7 +864,25 @@ impl rustc_driver::Callbacks for PrintAstCallbacks {
             artifacts_tree: ArtifactsTree::new(),
 
             idents_weights: HashMap::new(),
+            defs_weights: HashMap::new(),
+            defined_paths: HashSet::new(),
+            resolve_calls: self.args.resolve_calls,
+            resolutions: (self.args.resolve_calls || self.mir_weights.is_some()).then(|| {
+                let method_targets = self.method_targets.take().unwrap_or_default();
+                Resolutions::new(tcx, &resolver_and_krate.0, krate, method_targets)
+            }),
             weights_to_resolve: LinkedHashSet::new(),
+            recovery_weight: self.args.recovery_weight.clone().unwrap_or_default(),
+            recovered_weight: None,
+            recovered_deps: HashMap::new(),
+            dependency_weights,
+
+            source_map: tcx.sess.psess.clone_source_map(),
+            errors,
+            cfg_kinds: HashMap::new(),
+            compile_error_guards: Vec::new(),
+            formula_sizes: (0, 0),
+            weight_model,
         };
 
         // initialize global scope (global feature and artifact)
@@ -271,18 +899,103 @@ impl rustc_driver::Callbacks for PrintAstCallbacks {
         collector.terms_tree.graph.reverse(); // reverse graph
         collector.rec_weight_terms_tree(TermIndex::new(GLOBAL_NODE_INDEX));
         collector.resolve_weights_in_wait();
+        collector.estimate_weights();
         collector.terms_tree.graph.reverse(); // restore graph
 
+        // replace the weights of the artifacts with the MIR-based ones
+        match self.mir_weights.take() {
+            Some(Ok(mir_weights)) => collector.apply_mir_weights(&mir_weights),
+            Some(Err(error)) => collector.errors.push(error),
+            None => {}
+        }
+
+        // replace the weights of the artifacts with the ones measured on their source code
+        if let Some(mode) = self
+            .args
+            .weight_mode
+            .as_ref()
+            .filter(|mode| mode.is_source_based())
+        {
+            collector.apply_source_weights(mode);
+        }
+
         collector.add_dummy_centrality_node_edges();
 
         // Calculate centrality measures
-        let (cnf, mapping) = collector.get_fgraph_to_cnf::<u32>(ConversionMethod::Naive);
-        let centrality = collector.compute_centrality(&mapping);
-        let _configs = ConfigGenerator::new(cnf, &centrality.indices, 5).generate();
+        let (cnf, mapping) = collector.feature_model_cnf::<u32>();
+        let centrality = collector.compute_centrality(
+            &mapping,
+            &self.args.centrality_params(),
+            self.args.unify_polarity,
+            self.args.polarity_split,
+        );
+        let mut solver = ConfigSolver::default();
+        solver.add_cnf(cnf.clone());
+        if !collector.compile_error_guards.is_empty() {
+            match solver.is_satisfiable() {
+                Some(true) => {}
+                Some(false) => collector.errors.push(RustyExError::UnsatisfiableGuards {
+                    guards: collector.compile_error_guards.len(),
+                }),
+                None => collector.errors.push(RustyExError::SolverInterrupted {
+                    task: "checking the `compile_error!` guards".to_string(),
+                }),
+            }
+        }
+        // the features to cover, by the measure used to rank them
+        let targets = centrality
+            .indices
+            .iter()
+            .zip(&centrality.features)
+            .zip(centrality.measure(&self.args.rank_by.clone().unwrap_or_default()))
+            .map(|((var, feature), value)| ((*var, !feature.not), value))
+            .collect::<Vec<_>>();
+        let mut generator =
+            ConfigGenerator::new(cnf.clone(), &targets, self.args.config_budget.unwrap_or(5));
+        let configs = generator.generate();
+        if generator.interrupted() {
+            collector.errors.push(RustyExError::SolverInterrupted {
+                task: "generating the configurations".to_string(),
+            });
+        }
+
+        if self.keep_result {
+            self.result = Some(AnalysisResult {
+                terms_tree: collector.terms_tree,
+                features_graph: collector.features_graph,
+                artifacts_tree: collector.artifacts_tree,
+                centrality,
+                cnf,
+                cnf_mapping: mapping,
+                configs,
+                cfg_kinds: collector.cfg_kinds,
+                compile_error_guards: collector.compile_error_guards,
+                errors: collector.errors,
+            });
+            return rustc_driver::Compilation::Stop;
+        }
 
-        eprintln!("Configs: {:?}", _configs);
+        let crate_name = tcx.crate_name(LOCAL_CRATE).to_string();
+        let mut output = match &self.args.output_dir {
+            Some(output_dir) => {
+                Output::directory(output_dir, &crate_name, &tcx.crate_types()[0].to_string())
+            }
+            None => Output::stdout(),
+        };
 
-        self.process_cli_args(collector, krate, centrality);
+        // on `stdout` they are printed only if requested, not to mix them with the other outputs
+        if self.args.print_configs || output.is_directory() {
+            output.emit(
+                "configs",
+                "configs.json",
+                &serde_json::to_string(&configs).expect("Error: cannot serialize data"),
+            );
+        }
+
+        errors::print_diagnostics(&collector.errors);
+
+        self.process_cli_args(&collector, krate, &crate_name, centrality, &mut output);
+        output.finish();
 
         rustc_driver::Compilation::Stop
     }
@@ -301,9 +1014,6 @@ pub const GLOBAL_DUMMY_NAME: &str = "__DUMMY__";
 /// Index of the dummy feature node in the features graph
 pub const GLOBAL_DUMMY_INDEX: usize = 1;
 
-/// Weight assigned to Term nodes that cannot be resolved
-pub const RECOVERY_WEIGHT: TermWeight = TermWeight::Weight(7.0); // TODO: this should be the mean of all external function weights. This, of course, needs to be calculated a priori and passed as an argument. For now, we use `7.0` because of its intrinsic beauty.
-
 /// AST visitor to collect data to build the graphs
 pub struct CollectVisitor {
     /// NodeId in nodes are not resolved yet, so we need to increment it manually
@@ -322,9 +1032,40 @@ pub struct CollectVisitor {
     /// The weights for a single ident can be multiple, because a function can
     /// be defined multiple times (with different #[cfg] attributes)
     idents_weights: HashMap<String, Vec<f64>>,
+    /// Weights of the already weighted items, by resolved path (only with name resolution).
+    /// The weights can be multiple, as for `idents_weights`
+    defs_weights: HashMap<String, Vec<f64>>,
+    /// Resolved paths of the items defined in the Terms Tree
+    defined_paths: HashSet<String>,
+    /// Name resolution of the crate, `None` if calls are linked only by name
+    /// and the items are not linked to their MIR
+    resolutions: Option<Resolutions>,
+    /// Link calls to the exact item they target using `resolutions`
+    resolve_calls: bool,
+    /// Strategy to choose the weight of the Term nodes that cannot be resolved
+    recovery_weight: RecoveryWeight,
+    /// Weight of the Term nodes that cannot be resolved, computed at the first recovery
+    recovered_weight: Option<f64>,
+    /// Items that cannot be resolved, with the weight they were given
+    recovered_deps: HashMap<String, f64>,
+    /// Weights of the items of other crates, used before the recovery weight
+    dependency_weights: DependencyWeights,
     /// Terms that are waiting for something to be resolved.
     /// This needs to be a set, but with insertion order preserved (a "unique" queue)
     weights_to_resolve: LinkedHashSet<TermIndex>,
+
+    /// Source map of the compiler session, used to resolve the spans of the terms
+    source_map: Arc<SourceMap>,
+    /// Errors found during the analysis, reported as diagnostics at the end
+    errors: Vec<RustyExError>,
+    /// Kind of `cfg` option of each feature name
+    cfg_kinds: HashMap<String, CfgKind>,
+    /// `compile_error!`s annotated by a `cfg`, constraints between the features
+    compile_error_guards: Vec<CompileErrorGuard>,
+    /// Size of the features formula before and after its simplification
+    formula_sizes: (usize, usize),
+    /// Heuristic used to weight the terms
+    weight_model: Box<dyn WeightModel>,
 }
 
 impl CollectVisitor {
@@ -350,6 +1091,7 @@ impl CollectVisitor {
         let index = self.terms_tree.create_node(
             SimpleTermKey(node_id),
             ident.clone(),
+            None,
             features.clone(),
             TermWeightKind::Children("Global".to_string()),
             TermWeight::ToBeCalculated,
@@ -374,6 +1116,7 @@ impl CollectVisitor {
         let index = self.artifacts_tree.create_node(
             artifact,
             ident,
+            None,
             ComplexFeature::Simple(feature.clone()),
             TermWeight::ToBeCalculated,
         );
@@ -408,12 +1151,15 @@ impl CollectVisitor {
         );
     }
 
-    /// Recursively visit nested features (all, any, not), creating features nodes
+    /// Recursively visit nested features (all, any, not), without creating the features nodes.
+    /// The kind of each found feature is saved in `cfg_kinds`.
+    /// Return an error if the attribute is malformed
     fn rec_expand_features(
-        &mut self,
+        &self,
         nested_meta: Vec<MetaItemInner>,
         not: bool,
-    ) -> Vec<ComplexFeature<Feature>> {
+        cfg_kinds: &mut HashMap<String, CfgKind>,
+    ) -> errors::Result<Vec<ComplexFeature<Feature>>> {
         let mut features = Vec::new();
 
         for meta in nested_meta {
@@ -421,59 +1167,88 @@ impl CollectVisitor {
                 sym::feature => {
                     let name = meta
                         .value_str()
-                        .expect("Error: malformed feature without value `#[cfg(feature)]`")
+                        .ok_or_else(|| {
+                            self.malformed_cfg("feature without value `feature`", meta.span())
+                        })?
                         .to_string();
 
-                    let feature = Feature { name, not };
-                    self.features_graph.create_node(
-                        FeatureKey(feature.clone()),
-                        None,           // to be valued later
-                        HashSet::new(), // to be valued later
-                    );
-
-                    features.push(ComplexFeature::Simple(feature));
+                    CfgKind::record(cfg_kinds, &name, CfgKind::Feature);
+                    features.push(ComplexFeature::Simple(Feature { name, not }));
+                }
+                sym::not => features.extend(self.rec_expand_features(
+                    self.nested_meta(&meta)?,
+                    !not,
+                    cfg_kinds,
+                )?),
+                // De Morgan: `not(all(a, b))` is `any(not(a), not(b))` and vice versa
+                sym::all | sym::any => {
+                    let nested =
+                        self.rec_expand_features(self.nested_meta(&meta)?, not, cfg_kinds)?;
+                    features.push(match (meta.name_or_empty() == sym::all) != not {
+                        true => ComplexFeature::All(nested),
+                        false => ComplexFeature::Any(nested),
+                    });
+                }
+                kw::Empty => {
+                    return Err(self.malformed_cfg("literal or path as predicate", meta.span()))
                 }
-                sym::not => features.extend(
-                    self.rec_expand_features(
-                        meta.meta_item_list()
-                            .expect("Error: empty `not` feature attribute")
-                            .to_vec(),
-                        !not,
-                    ),
-                ),
-                sym::all => features.push(ComplexFeature::All(
-                    self.rec_expand_features(
-                        meta.meta_item_list()
-                            .expect("Error: empty `all` feature attribute")
-                            .to_vec(),
-                        not,
-                    ),
-                )),
-                sym::any => features.push(ComplexFeature::Any(
-                    self.rec_expand_features(
-                        meta.meta_item_list()
-                            .expect("Error: empty `any` feature attribute")
-                            .to_vec(),
-                        not,
-                    ),
-                )),
                 name => {
                     // Covering built-in cfg, e.g.,#[cfg(linux)]
                     let feature = Feature {
                         name: name.to_ident_string(),
                         not,
                     };
-                    self.features_graph.create_node(
-                        FeatureKey(feature.clone()),
-                        None,           // to be valued later
-                        HashSet::new(), // to be valued later
-                    );
+                    let kind = match meta.value_str() {
+                        Some(_) => CfgKind::NameValue,
+                        None => CfgKind::Name,
+                    };
+                    CfgKind::record(cfg_kinds, &feature.name, kind);
                     features.push(ComplexFeature::Simple(feature));
                 }
             }
         }
 
-        features
+        Ok(features)
+    }
+
+    /// Get the nested features of a `not`, `all` or `any` predicate
+    fn nested_meta(&self, meta: &MetaItemInner) -> errors::Result<Vec<MetaItemInner>> {
+        meta.meta_item_list()
+            .map(|list| list.to_vec())
+            .ok_or_else(|| {
+                self.malformed_cfg(
+                    &format!("`{}` without nested features", meta.name_or_empty()),
+                    meta.span(),
+                )
+            })
+    }
+
+    /// Recursively create the features nodes of parsed features
+    fn rec_create_feature_nodes(&mut self, features: &ComplexFeature<Feature>) {
+        match features {
+            ComplexFeature::None => {}
+            ComplexFeature::Simple(feature) => {
+                self.features_graph.create_node(
+                    FeatureKey(feature.clone()),
+                    None,           // to be valued later
+                    HashSet::new(), // to be valued later
+                );
+            }
+            ComplexFeature::All(nested) | ComplexFeature::Any(nested) => {
+                for features in nested {
+                    self.rec_create_feature_nodes(features);
+                }
+            }
+        }
+    }
+
+    /// Create a malformed `cfg` error pointing at the given span
+    fn malformed_cfg(&self, message: &str, span: Span) -> RustyExError {
+        RustyExError::MalformedCfg {
+            message: message.to_string(),
+            span: Some(SourceSpan::from_span(span, &self.source_map)),
+            snippet: self.source_map.span_to_snippet(span).ok(),
+        }
     }
 
     /// Weight features horizontally, considering only the "siblings"
@@ -534,10 +1309,18 @@ impl CollectVisitor {
     }
 
     /// Initialize a new Term node and update the stack
-    fn pre_walk(&mut self, kind: TermWeightKind, ident: Option<String>, node_id: NodeId) {
+    fn pre_walk(
+        &mut self,
+        kind: TermWeightKind,
+        ident: Option<String>,
+        node_id: NodeId,
+        span: Span,
+    ) {
+        let span = Some(SourceSpan::from_span(span, &self.source_map));
         let term_index = self.terms_tree.create_node(
             SimpleTermKey(node_id),
             ident,
+            span,
             ComplexFeature::None,
             kind,
             TermWeight::ToBeCalculated,
@@ -545,6 +1328,118 @@ impl CollectVisitor {
         self.stack.push((term_index, ComplexFeature::None));
     }
 
+    /// Save the resolved paths of the item defined and of the item referenced by the Term node
+    fn set_resolution(
+        &mut self,
+        node_id: NodeId,
+        def_path: Option<String>,
+        target: Option<String>,
+    ) {
+        let term_index = self.terms_tree.nodes[&SimpleTermKey(node_id)];
+        let term_node = &mut self.terms_tree.graph[term_index];
+        if let Some(def_path) = &def_path {
+            self.defined_paths.insert(def_path.clone());
+        }
+        term_node.def_path = def_path;
+        term_node.target = target;
+    }
+
+    /// Arguments of a `compile_error!` replaced by the file loader with a constant calling
+    /// `rustex_compile_error` (see `utils::rewrite_compile_error`), `None` for any other item
+    fn compile_error_guard_args(item: &Item) -> Option<&[rustc_ast::ptr::P<Expr>]> {
+        let ItemKind::Const(const_item) = &item.kind else {
+            return None;
+        };
+        match &const_item.expr.as_deref()?.kind {
+            ExprKind::Call(callee, args) => match &callee.kind {
+                ExprKind::Path(None, path)
+                    if *path == Symbol::intern(utils::COMPILE_ERROR_GUARD) =>
+                {
+                    Some(args)
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Save the kinds of the features of a well-formed attribute
+    fn record_cfg_kinds(&mut self, cfg_kinds: HashMap<String, CfgKind>) {
+        for (name, kind) in cfg_kinds {
+            CfgKind::record(&mut self.cfg_kinds, &name, kind);
+        }
+    }
+
+    /// Save a `compile_error!` annotated by a `cfg`, with the features of the enclosing code, as
+    /// a constraint. No Term node is created: the guarded code is never compiled
+    fn collect_compile_error_guard(&mut self, item: &Item) {
+        let mut cfg_kinds = HashMap::new();
+        let mut complex_features = Vec::new();
+        let mut malformed = false;
+        for attr in &item.attrs {
+            let Some(meta) = attr.meta() else {
+                continue;
+            };
+            let MetaItemKind::List(list) = &meta.kind else {
+                continue;
+            };
+            if meta.name_or_empty() != Symbol::intern("rustex_cfg") {
+                continue;
+            }
+            match self.rec_expand_features(list.to_vec(), false, &mut cfg_kinds) {
+                Ok(parsed_features) if parsed_features.len() > 1 => {
+                    let error = self.malformed_cfg("multiple (not nested) features", attr.span);
+                    self.errors.push(error);
+                    malformed = true;
+                }
+                Ok(parsed_features) => complex_features.extend(parsed_features),
+                Err(error) => {
+                    self.errors.push(error);
+                    malformed = true;
+                }
+            }
+        }
+        if !malformed {
+            self.record_cfg_kinds(cfg_kinds);
+        }
+
+        // a partially parsed guard would exclude too many configurations, and an unguarded
+        // `compile_error!` excludes all of them (the crate never compiles)
+        let complex_feature = match complex_features.len() {
+            _ if malformed => return,
+            0 => return,
+            1 => complex_features.remove(0),
+            _ => ComplexFeature::All(complex_features),
+        };
+        let enclosing = self
+            .stack
+            .iter()
+            .map(|(_, features)| features)
+            .filter(|features| **features != ComplexFeature::None)
+            .cloned()
+            .collect();
+        let message = CollectVisitor::compile_error_guard_args(item)
+            .and_then(|args| args.first())
+            .and_then(|arg| match &arg.kind {
+                ExprKind::Lit(lit)
+                    if matches!(
+                        lit.kind,
+                        rustc_ast::token::LitKind::Str | rustc_ast::token::LitKind::StrRaw(_)
+                    ) =>
+                {
+                    Some(lit.symbol.to_string())
+                }
+                _ => None,
+            });
+
+        self.compile_error_guards.push(CompileErrorGuard {
+            complex_feature,
+            enclosing,
+            message,
+            span: Some(SourceSpan::from_span(item.span, &self.source_map)),
+        });
+    }
+
     /// Extract the features of the Term node from the stack and update the Terms Tree
     fn post_walk(&mut self, node_id: NodeId) {
         let (node_index, features) = self
@@ -566,11 +1461,13 @@ impl CollectVisitor {
         // create artifact if some features are found
         if features != ComplexFeature::None {
             let ident = term_node.ident.clone();
+            let span = term_node.span.clone();
             // convert features to index of the features (the features node already exist)
 
             self.artifacts_tree.create_node(
                 SimpleArtifactKey(node_id),
                 ident,
+                span,
                 features.clone(),
                 TermWeight::ToBeCalculated,
             );
@@ -645,32 +1542,68 @@ impl CollectVisitor {
             }
         }
 
-        let weight = match &self
+        let term_node = self
             .terms_tree
             .graph
             .node_weight(start_index)
-            .expect("Error: cannot find Term node weighting Terms Tree")
-            .weight_kind
-        {
-            TermWeightKind::Intrinsic(..) => TermWeight::Weight(1.0 + child_weight),
-            TermWeightKind::Children(..) => TermWeight::Weight(child_weight),
-            TermWeightKind::Reference(.., Some(to)) => match self.idents_weights.get(to) {
-                Some(vec_fn_weight) => TermWeight::Weight(
-                    (vec_fn_weight.iter().sum::<f64>() / vec_fn_weight.len() as f64) + child_weight,
-                ),
-                None => {
-                    self.weights_to_resolve.insert(start_index);
-                    TermWeight::Wait(to.to_string())
-                }
-            },
-            TermWeightKind::Reference(.., None) => TermWeight::Weight(child_weight),
-            TermWeightKind::No(..) => TermWeight::Weight(0.0),
+            .expect("Error: cannot find Term node weighting Terms Tree");
+        let weight_kind = &term_node.weight_kind;
+        let reference = self.reference_key(term_node);
+        // items that cannot be resolved keep the recovery weight, otherwise the weights of
+        // the items (with the same name or with the same resolved path) are averaged
+        let reference_weight = reference.and_then(|(dep, resolved)| {
+            if let Some(recovered_weight) = self.recovered_deps.get(dep) {
+                return Some(*recovered_weight);
+            }
+            let vec_fn_weight = if resolved {
+                self.defs_weights.get(dep)
+            } else {
+                self.idents_weights.get(dep)
+            };
+            vec_fn_weight
+                .map(|vec_fn_weight| vec_fn_weight.iter().sum::<f64>() / vec_fn_weight.len() as f64)
+        });
+        let weight = match (reference, reference_weight) {
+            (Some(_), Some(reference_weight)) => TermWeight::Weight(self.weight_model.weight(
+                weight_kind,
+                child_weight,
+                Some(reference_weight),
+            )),
+            (Some((dep, _)), None) => {
+                self.weights_to_resolve.insert(start_index);
+                TermWeight::Wait(dep.to_string())
+            }
+            (None, _) => {
+                TermWeight::Weight(self.weight_model.weight(weight_kind, child_weight, None))
+            }
         };
+        let recovered = reference.is_some_and(|(dep, _)| self.recovered_deps.contains_key(dep));
 
         self.update_weight(start_index, weight.clone());
+        if let TermWeight::Weight(..) = weight {
+            self.terms_tree.graph[start_index].recovered = recovered;
+        }
         weight
     }
 
+    /// Item referenced by the Term node, if it is a reference: the resolved path if it is
+    /// defined in the Terms Tree (`true`, see `defs_weights`), otherwise the name of the item
+    /// (`false`, see `idents_weights`)
+    fn reference_key<'a>(
+        &self,
+        term_node: &'a TermNode<SimpleTermKey>,
+    ) -> Option<(&'a String, bool)> {
+        let target = term_node
+            .target
+            .as_ref()
+            .filter(|target| self.defined_paths.contains(*target));
+        match (&term_node.weight_kind, target) {
+            (TermWeightKind::Reference(..), Some(target)) => Some((target, true)),
+            (TermWeightKind::Reference(.., Some(to)), None) => Some((to, false)),
+            _ => None,
+        }
+    }
+
     /// Calculate the weights of the nodes in wait
     fn resolve_weights_in_wait(&mut self) {
         let mut seen = HashSet::new();
@@ -680,10 +1613,30 @@ impl CollectVisitor {
             // of unsolved weights, then it's a loop. Enter recovery mode: weight the
             // deepest node and continue
             if seen.contains(&(cur_index, self.weights_to_resolve.len())) {
-                // because of how the queue is formed, the node that first detects a loop
-                // is also the "deepest" one, the one that has all adjacents fully resolved
-                self.update_weight(cur_index, RECOVERY_WEIGHT);
+                // the node that first detects a loop is not necessarily the one that cannot
+                // be resolved: the deepest node in wait, the one that has all adjacents fully
+                // resolved, references something that cannot be resolved. Give it the recovery
+                // weight and try again (progress is made, so forget the seen nodes)
+                let deepest_index = self.deepest_in_wait(cur_index);
+                let term_node = &self.terms_tree.graph[deepest_index];
+                match self.reference_key(term_node).map(|(dep, _)| dep.clone()) {
+                    Some(dep) => {
+                        let weight = self.recover_weight(&dep);
+                        self.recovered_deps.insert(dep, weight);
+                    }
+                    None => {
+                        let dep = match &term_node.weight {
+                            TermWeight::Wait(dep) => dep.clone(),
+                            _ => String::new(),
+                        };
+                        let weight = self.recover_weight(&dep);
+                        self.update_weight(deepest_index, TermWeight::Weight(weight));
+                        self.terms_tree.graph[deepest_index].recovered = true;
+                    }
+                }
 
+                seen.clear();
+                self.weights_to_resolve.insert(cur_index);
                 continue;
             }
 
@@ -694,6 +1647,303 @@ impl CollectVisitor {
         }
     }
 
+    /// Deepest Term node in wait below the given one (itself if all its adjacents are resolved)
+    fn deepest_in_wait(&self, term_index: TermIndex) -> TermIndex {
+        let in_wait = self
+            .terms_tree
+            .graph
+            .neighbors(term_index)
+            .find(|index| matches!(self.terms_tree.graph[*index].weight, TermWeight::Wait(..)));
+        match in_wait {
+            Some(index) => self.deepest_in_wait(index),
+            None => term_index,
+        }
+    }
+
+    /// Weight of an item that cannot be resolved: the weight of the item of another crate,
+    /// if known, otherwise the recovery weight
+    fn recover_weight(&mut self, dep: &str) -> f64 {
+        if let Some(weight) = self.dependency_weights.weights.get(dep) {
+            return *weight;
+        }
+
+        // computed once, when everything that can be resolved is resolved
+        *self.recovered_weight.get_or_insert_with(|| {
+            let functions_weights = self
+                .terms_tree
+                .graph
+                .node_weights()
+                .filter(|term_node| term_node.weight_kind.kind_name() == "Fn")
+                .filter_map(|term_node| match term_node.weight {
+                    TermWeight::Weight(weight) => Some(weight),
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+            self.recovery_weight.compute(&functions_weights)
+        })
+    }
+
+    /// Calculate the part of the weight of each Term node that comes from recovered terms
+    /// (directly, from its children or from the items it references) and copy it to the artifacts.
+    ///
+    /// The Terms Tree is expected to be reversed, as while weighting it
+    fn estimate_weights(&mut self) {
+        // items by name and by resolved path, as while weighting references
+        let mut idents_terms: HashMap<String, Vec<TermIndex>> = HashMap::new();
+        let mut paths_terms: HashMap<String, Vec<TermIndex>> = HashMap::new();
+        for index in self.terms_tree.graph.node_indices() {
+            let term_node = &self.terms_tree.graph[index];
+            if let Some(ident) = &term_node.ident {
+                idents_terms.entry(ident.clone()).or_default().push(index);
+            }
+            if let Some(def_path) = &term_node.def_path {
+                paths_terms.entry(def_path.clone()).or_default().push(index);
+            }
+        }
+
+        let mut estimated = HashMap::new();
+        self.rec_estimate_weights(
+            TermIndex::new(GLOBAL_NODE_INDEX),
+            &idents_terms,
+            &paths_terms,
+            &mut estimated,
+            &mut HashSet::new(),
+        );
+
+        for (index, estimated_weight) in estimated {
+            let term_node = &mut self.terms_tree.graph[index];
+            term_node.estimated_weight = estimated_weight;
+            if term_node.features != ComplexFeature::None {
+                let artifact_index =
+                    self.artifacts_tree.nodes[&SimpleArtifactKey(term_node.node_id.0)];
+                self.artifacts_tree.graph[artifact_index].estimated_weight = estimated_weight;
+            }
+        }
+    }
+
+    /// Replace the weights of the artifacts with the MIR-based weights of the items (by resolved path).
+    /// An artifact weighs as the items defined in it or, if it is part of an item (e.g., a statement),
+    /// as its share of the AST weight of the item. Artifacts without MIR (e.g., types) weigh 0.0
+    fn apply_mir_weights(&mut self, mir_weights: &HashMap<String, f64>) {
+        let mut subtrees_weights = HashMap::new();
+        self.rec_subtree_mir_weight(
+            TermIndex::new(GLOBAL_NODE_INDEX),
+            mir_weights,
+            &mut subtrees_weights,
+        );
+
+        for artifact_index in self.artifacts_tree.graph.node_indices() {
+            let node_id = self.artifacts_tree.graph[artifact_index].artifact.0;
+            let term_index = self.terms_tree.nodes[&SimpleTermKey(node_id)];
+            let weight = match subtrees_weights.get(&term_index) {
+                Some(weight) if *weight > 0.0 => *weight,
+                _ => self.enclosing_mir_share(term_index, mir_weights),
+            };
+
+            let artifact_node = &mut self.artifacts_tree.graph[artifact_index];
+            artifact_node.weight = TermWeight::Weight(weight);
+            artifact_node.estimated_weight = 0.0;
+        }
+    }
+
+    /// Replace the weights of the artifacts with the ones measured on their source code (the
+    /// weight mode must be based on the source code). Artifacts without source code (the global
+    /// one) weigh as the artifacts they directly contain
+    fn apply_source_weights(&mut self, mode: &WeightMode) {
+        let mut missing = Vec::new();
+        for artifact_index in self.artifacts_tree.graph.node_indices() {
+            let weight = self.artifacts_tree.graph[artifact_index]
+                .span
+                .as_ref()
+                .and_then(|span| self.source_snippet(span))
+                .and_then(|snippet| mode.source_weight(&snippet));
+            match weight {
+                Some(weight) => self.set_artifact_weight(artifact_index, weight),
+                None => missing.push(artifact_index),
+            }
+        }
+
+        for artifact_index in missing {
+            let weight = self
+                .artifacts_tree
+                .graph
+                .neighbors_directed(artifact_index, Direction::Incoming)
+                .map(|index| match self.artifacts_tree.graph[index].weight {
+                    TermWeight::Weight(weight) => weight,
+                    _ => 0.0,
+                })
+                .sum::<f64>();
+            self.set_artifact_weight(artifact_index, weight);
+        }
+    }
+
+    /// Set the (measured) weight of an artifact
+    fn set_artifact_weight(&mut self, artifact_index: ArtifactIndex, weight: f64) {
+        let artifact_node = &mut self.artifacts_tree.graph[artifact_index];
+        artifact_node.weight = TermWeight::Weight(weight);
+        artifact_node.estimated_weight = 0.0;
+    }
+
+    /// Source code of a span, as written in the file (before the rewriting of the `cfg`s)
+    fn source_snippet(&self, span: &SourceSpan) -> Option<String> {
+        let files = self.source_map.files();
+        let file = files
+            .iter()
+            .find(|file| file.name.prefer_local().to_string() == span.file)?;
+        let src = file.src.as_ref()?;
+
+        let mut snippet = String::new();
+        for (line_number, line) in src
+            .lines()
+            .enumerate()
+            .map(|(index, line)| (index + 1, line))
+            .skip(span.lo_line - 1)
+            .take(span.hi_line + 1 - span.lo_line)
+        {
+            let start = if line_number == span.lo_line {
+                span.lo_col - 1
+            } else {
+                0
+            };
+            let end = if line_number == span.hi_line {
+                span.hi_col - 1
+            } else {
+                usize::MAX
+            };
+            snippet.extend(line.chars().take(end).skip(start));
+            if line_number != span.hi_line {
+                snippet.push('\n');
+            }
+        }
+        Some(utils::restore_cfg(&snippet))
+    }
+
+    /// MIR weight of the items defined in the subtree of the Term node
+    fn rec_subtree_mir_weight(
+        &self,
+        start_index: TermIndex,
+        mir_weights: &HashMap<String, f64>,
+        subtrees_weights: &mut HashMap<TermIndex, f64>,
+    ) -> f64 {
+        let children_weight = self
+            .terms_tree
+            .graph
+            .neighbors_directed(start_index, Direction::Incoming)
+            .map(|index| self.rec_subtree_mir_weight(index, mir_weights, subtrees_weights))
+            .sum::<f64>();
+        let weight = self.terms_tree.graph[start_index]
+            .def_path
+            .as_ref()
+            .and_then(|def_path| mir_weights.get(def_path))
+            .unwrap_or(&0.0)
+            + children_weight;
+
+        subtrees_weights.insert(start_index, weight);
+        weight
+    }
+
+    /// Share of the MIR weight of the item enclosing the Term node, proportional to the AST weights
+    fn enclosing_mir_share(
+        &self,
+        term_index: TermIndex,
+        mir_weights: &HashMap<String, f64>,
+    ) -> f64 {
+        let ast_weight = |index: TermIndex| match self.terms_tree.graph[index].weight {
+            TermWeight::Weight(weight) => weight,
+            _ => 0.0,
+        };
+
+        let mut cur_index = term_index;
+        // edges go from children to parents
+        while let Some(parent_index) = self.terms_tree.graph.neighbors(cur_index).next() {
+            let parent_node = &self.terms_tree.graph[parent_index];
+            let mir_weight = parent_node
+                .def_path
+                .as_ref()
+                .and_then(|def_path| mir_weights.get(def_path));
+            if let Some(mir_weight) = mir_weight {
+                let item_weight = ast_weight(parent_index);
+                return if item_weight == 0.0 {
+                    0.0
+                } else {
+                    mir_weight * ast_weight(term_index) / item_weight
+                };
+            }
+            cur_index = parent_index;
+        }
+
+        0.0
+    }
+
+    /// Estimated weight of a Term node, see `estimate_weights`.
+    /// Recursive references are not followed again
+    fn rec_estimate_weights(
+        &self,
+        start_index: TermIndex,
+        idents_terms: &HashMap<String, Vec<TermIndex>>,
+        paths_terms: &HashMap<String, Vec<TermIndex>>,
+        estimated: &mut HashMap<TermIndex, f64>,
+        visiting: &mut HashSet<TermIndex>,
+    ) -> f64 {
+        if let Some(estimated_weight) = estimated.get(&start_index) {
+            return *estimated_weight;
+        }
+        if !visiting.insert(start_index) {
+            return 0.0;
+        }
+
+        let term_node = &self.terms_tree.graph[start_index];
+        let weight = match term_node.weight {
+            TermWeight::Weight(weight) => weight,
+            _ => 0.0,
+        };
+
+        let mut rec_estimate = |index| {
+            self.rec_estimate_weights(index, idents_terms, paths_terms, estimated, visiting)
+        };
+        let children_estimated = self
+            .terms_tree
+            .graph
+            .neighbors(start_index)
+            .collect::<Vec<_>>()
+            .into_iter()
+            .map(&mut rec_estimate)
+            .sum::<f64>();
+        // the recovered weight of an item that cannot be resolved is estimated, otherwise the
+        // item is estimated as much as its definitions (averaged, as while weighting)
+        let reference = self.reference_key(term_node);
+        let reference_estimated = match reference {
+            Some((dep, resolved)) => match self.recovered_deps.get(dep) {
+                Some(recovered_weight) => *recovered_weight,
+                None => {
+                    let terms = if resolved {
+                        paths_terms.get(dep)
+                    } else {
+                        idents_terms.get(dep)
+                    };
+                    terms.map_or(0.0, |indexes| {
+                        indexes
+                            .iter()
+                            .map(|index| rec_estimate(*index))
+                            .sum::<f64>()
+                            / indexes.len() as f64
+                    })
+                }
+            },
+            None => 0.0,
+        };
+
+        let estimated_weight = match term_node.weight_kind {
+            _ if term_node.recovered && reference.is_none() => weight,
+            TermWeightKind::No(..) => 0.0,
+            _ => (children_estimated + reference_estimated).min(weight),
+        };
+
+        visiting.remove(&start_index);
+        estimated.insert(start_index, estimated_weight);
+        estimated_weight
+    }
+
     /// Update the weight of the Term node.
     /// Remove the updated node from nodes in wait (only if the weight is not a Wait).
     /// Add the weight to the `idents_weights` map if the node has an ident
@@ -719,6 +1969,7 @@ impl CollectVisitor {
         }
 
         term_node.weight = weight.clone();
+        term_node.recovered = false;
 
         // remove from nodes in wait
         if let TermWeight::Weight(..) = weight {
@@ -726,8 +1977,13 @@ impl CollectVisitor {
         }
 
         // add to idents map if it has an ident
-        if let (TermWeight::Weight(weight), Some(ident)) = (weight, term_node.ident.clone()) {
-            self.idents_weights.entry(ident).or_default().push(weight);
+        if let (TermWeight::Weight(weight), Some(ident)) = (&weight, term_node.ident.clone()) {
+            self.idents_weights.entry(ident).or_default().push(*weight);
+        }
+        // add to definitions map if it has a resolved path
+        if let (TermWeight::Weight(weight), Some(def_path)) = (&weight, term_node.def_path.clone())
+        {
+            self.defs_weights.entry(def_path).or_default().push(*weight);
         }
     }
 
@@ -857,70 +2113,142 @@ impl CollectVisitor {
             });
     }
 
-    fn get_fgraph_to_cnf<T>(
-        &mut self,
-        method: ConversionMethod,
-    ) -> (CnfFormula<T>, HashMap<String, T>)
+    /// CNF of the feature model: the nesting of the `cfg`s (see `ArtifactsTree::nesting_formula`)
+    /// with the constraints of the `compile_error!` guards on top of it. Every feature is a
+    /// variable, even if it is not constrained
+    fn feature_model_cnf<T>(&mut self) -> (CnfFormula<T>, HashMap<String, T>)
     where
         T: Ordinal + Clone,
     {
-        let mut prop_formula = self.features_graph.to_prop_formula(method);
-        prop_formula.to_cnf_repr::<T>(true)
+        let mut prop_formula = PropFormula::And(
+            std::iter::once(self.artifacts_tree.nesting_formula())
+                .chain(
+                    self.compile_error_guards
+                        .iter()
+                        .map(CompileErrorGuard::to_prop_formula),
+                )
+                .collect(),
+        );
+        let vars = prop_formula.vars();
+        // the constraints are nested in the formula, it must be flattened
+        prop_formula.to_flat_cnf();
+        let size = prop_formula.size();
+        // the configurations are enumerated from the CNF: only the passes keeping every model
+        prop_formula.simplify();
+        self.formula_sizes = (size, prop_formula.size());
+        let (cnf, mut mapping) = prop_formula.to_cnf_repr::<T>(true);
+
+        // the features only in the clauses removed by the simplification, or in no clause, are
+        // unconstrained
+        let mut next = mapping
+            .values()
+            .max()
+            .cloned()
+            .map_or(T::default(), |mut max| {
+                max.suc();
+                max
+            });
+        let features = self
+            .features_graph
+            .graph
+            .node_references()
+            .filter(|(index, _)| {
+                *index != FeatureIndex::new(GLOBAL_NODE_INDEX)
+                    && *index != FeatureIndex::new(GLOBAL_DUMMY_INDEX)
+            })
+            .map(|(_, feature_node)| feature_node.feature.0.name.clone());
+        for var in vars.into_iter().chain(features) {
+            if let Entry::Vacant(entry) = mapping.entry(var) {
+                entry.insert(next.clone());
+                next.suc();
+            }
+        }
+        (cnf, mapping)
     }
 
-    fn compute_centrality(&mut self, cnf_mapping: &HashMap<String, u32>) -> Centrality<u32> {
-        let refiner_hm = self
+    /// Compute the centrality measures of the Features Graph, refined with the artifacts weights.
+    /// With `unify_polarity` they are computed on the Polarity Graph, one for each real feature
+    /// (optionally split by polarity).
+    /// Errors are collected and the measures that cannot be computed are left empty
+    fn compute_centrality(
+        &mut self,
+        cnf_mapping: &HashMap<String, u32>,
+        params: &CentralityParams,
+        unify_polarity: bool,
+        polarity_split: bool,
+    ) -> Centrality<u32> {
+        let (refiner_hm, errors) = self
             .artifacts_tree
             .refiner_hash_map(&self.features_graph, true);
+        self.errors.extend(errors);
+
+        let (centrality, errors) = match unify_polarity {
+            true => PolarityGraph::new(&self.features_graph).centrality(
+                &self.features_graph,
+                &refiner_hm,
+                cnf_mapping,
+                params,
+                polarity_split,
+            ),
+            false => {
+                Centrality::<u32>::new(&self.features_graph, &refiner_hm, cnf_mapping, true, params)
+            }
+        };
+        self.errors.extend(errors);
 
-        Centrality::<u32>::new(&self.features_graph, &refiner_hm, cnf_mapping, true)
+        centrality
     }
 
     /// Serialize the centrality measures of the Features Graph
-    fn serialized_centrality(&self, centrality: Centrality<u32>, kind: &CentralityKind) {
+    fn serialized_centrality(&self, centrality: Centrality<u32>, kind: &CentralityKind) -> String {
         match kind {
             CentralityKind::All => {
-                println!(
-                    "{}",
-                    serde_json::to_string(&centrality).expect("Error: cannot serialize data")
-                );
+                serde_json::to_string(&centrality).expect("Error: cannot serialize data")
             }
             CentralityKind::Katz => {
                 let katz = centrality.katz();
-                println!(
-                    "{}",
-                    serde_json::to_string(&katz).expect("Error: cannot serialize data")
-                );
+                serde_json::to_string(&katz).expect("Error: cannot serialize data")
             }
             CentralityKind::Closeness => {
                 let closeness = centrality.closeness();
-                println!(
-                    "{}",
-                    serde_json::to_string(&closeness).expect("Error: cannot serialize data")
-                );
+                serde_json::to_string(&closeness).expect("Error: cannot serialize data")
             }
             CentralityKind::Eigenvector => {
                 let eigenvector = centrality.eigenvector();
-                println!(
-                    "{}",
-                    serde_json::to_string(&eigenvector).expect("Error: cannot serialize data")
-                );
+                serde_json::to_string(&eigenvector).expect("Error: cannot serialize data")
+            }
+            CentralityKind::PageRank => {
+                let pagerank = centrality.pagerank();
+                serde_json::to_string(&pagerank).expect("Error: cannot serialize data")
+            }
+            CentralityKind::Betweenness => {
+                let betweenness = centrality.betweenness();
+                serde_json::to_string(&betweenness).expect("Error: cannot serialize data")
+            }
+            CentralityKind::InDegree => {
+                let in_degree = centrality.in_degree();
+                serde_json::to_string(&in_degree).expect("Error: cannot serialize data")
+            }
+            CentralityKind::OutDegree => {
+                let out_degree = centrality.out_degree();
+                serde_json::to_string(&out_degree).expect("Error: cannot serialize data")
+            }
+            CentralityKind::Harmonic => {
+                let harmonic = centrality.harmonic();
+                serde_json::to_string(&harmonic).expect("Error: cannot serialize data")
             }
         }
     }
 
-    /// Print all extracted graphs serialized
-    fn print_serialized_graphs(&self) {
+    /// All extracted graphs serialized
+    fn serialized_graphs(&self) -> String {
         let graphs = SimpleSerialization {
             terms_tree: self.terms_tree.clone(),
             features_graph: self.features_graph.clone(),
             artifacts_tree: self.artifacts_tree.clone(),
         };
 
-        println!(
-            "{}",
-            serde_json::to_string(&graphs).expect("Error: cannot serialize data")
-        );
+        serde_json::to_string(&graphs).expect("Error: cannot serialize data")
     }
 
     /// Add a dummy node in features graph, connected from the root (global feature)
@@ -944,8 +2272,8 @@ impl CollectVisitor {
             });
     }
 
-    /// Print metadata about the graphs in json format
-    fn print_metadata(&self) {
+    /// Metadata about the graphs in json format
+    fn metadata(&self) -> String {
         #[derive(Serialize)]
         struct Metadata {
             term_nodes: u32,
@@ -958,6 +2286,12 @@ impl CollectVisitor {
 
             artifacts_nodes: u32,
             artifacts_edges: u32,
+
+            recovered_terms: u32,
+            recovered_weight: Option<f64>,
+
+            formula_size_before: u32,
+            formula_size_after: u32,
         }
 
         let metadata = Metadata {
@@ -974,12 +2308,60 @@ impl CollectVisitor {
 
             artifacts_nodes: self.artifacts_tree.graph.node_count() as u32,
             artifacts_edges: self.artifacts_tree.graph.edge_count() as u32,
+
+            recovered_terms: self
+                .terms_tree
+                .graph
+                .node_weights()
+                .filter(|term_node| term_node.recovered)
+                .count() as u32,
+            recovered_weight: self.recovered_weight,
+
+            formula_size_before: self.formula_sizes.0 as u32,
+            formula_size_after: self.formula_sizes.1 as u32,
         };
 
-        println!(
-            "{}",
-            serde_json::to_string(&metadata).expect("Error: cannot serialize metadata")
-        );
+        serde_json::to_string(&metadata).expect("Error: cannot serialize metadata")
+    }
+
+    /// Weight of each feature and the part of it that is estimated (it comes from recovered
+    /// terms) in json format
+    fn estimated_weights(&self) -> String {
+        #[derive(Serialize)]
+        struct EstimatedWeight {
+            name: String,
+            not: bool,
+            weight: f64,
+            estimated_weight: f64,
+            estimated_fraction: f64,
+        }
+
+        let mut weights = self
+            .artifacts_tree
+            .estimated_weights(&self.features_graph, true)
+            .into_iter()
+            .collect::<Vec<_>>();
+        weights.sort_by_key(|(index, _)| *index);
+
+        let estimated_weights = weights
+            .into_iter()
+            .map(|(index, (weight, estimated_weight))| {
+                let feature = &self.features_graph.graph[index].feature.0;
+                EstimatedWeight {
+                    name: feature.name.clone(),
+                    not: feature.not,
+                    weight,
+                    estimated_weight,
+                    estimated_fraction: if weight == 0.0 {
+                        0.0
+                    } else {
+                        estimated_weight / weight
+                    },
+                }
+            })
+            .collect::<Vec<_>>();
+
+        serde_json::to_string(&estimated_weights).expect("Error: cannot serialize data")
     }
 
     /// Squash the edges making the multigraph a graph. Edges weight is summed up
@@ -1045,34 +2427,64 @@ impl<'ast> Visitor<'ast> for CollectVisitor {
         if let Some(meta) = attr.meta() {
             if meta.name_or_empty() == Symbol::intern("rustex_cfg") {
                 if let MetaItemKind::List(ref list) = meta.kind {
-                    match self.stack.pop() {
-                        Some((term_index, ComplexFeature::None)) => {
-                            let parsed_features = self.rec_expand_features(list.to_vec(), false);
-                            log::info!("Parsed features: {:?}", parsed_features);
-
-                            match parsed_features.len() {
-                                // This should be `unreachable!()` because in `rec_expand_features`
-                                // we always return at least one feature
-                                0 => {
-                                    self.stack.push((term_index, ComplexFeature::None));
-                                }
-                                // well-formed with built-in feature (we need the ignore): #[cfg(windows)]
-                                // well-formed with feature (we need the feature): #[cfg(feature = "a"))]
-                                1 => {
-                                    self.stack.push((term_index, parsed_features[0].to_owned()));
-                                }
-                                // malformed (panic): #[cfg(feature = "a", feature = "b")]
-                                _ => {
-                                    panic!("Error: multiple (not nested) features in cfg attribute")
-                                }
+                    // the kinds are saved only if the attribute is well-formed
+                    let mut cfg_kinds = HashMap::new();
+                    let parsed_features =
+                        match self.rec_expand_features(list.to_vec(), false, &mut cfg_kinds) {
+                            Ok(parsed_features) => parsed_features,
+                            Err(error) => {
+                                self.errors.push(error);
+                                Vec::new()
                             }
+                        };
+                    log::info!("Parsed features: {:?}", parsed_features);
+
+                    let parsed_feature = match parsed_features.len() {
+                        // malformed attribute (already reported) or empty: #[cfg()]
+                        0 => None,
+                        // well-formed with built-in feature (we need the ignore): #[cfg(windows)]
+                        // well-formed with feature (we need the feature): #[cfg(feature = "a"))]
+                        1 => Some(parsed_features[0].to_owned()),
+                        // malformed (ignored): #[cfg(feature = "a", feature = "b")]
+                        _ => {
+                            let error =
+                                self.malformed_cfg("multiple (not nested) features", attr.span);
+                            self.errors.push(error);
+                            None
                         }
-                        Some((.., ComplexFeature::Simple(..)))
-                        | Some((.., ComplexFeature::All(..)))
-                        | Some((.., ComplexFeature::Any(..))) => {
-                            panic!("Error: node on stack already has a feature visiting attribute")
+                    };
+
+                    if let Some(parsed_feature) = parsed_feature {
+                        self.rec_create_feature_nodes(&parsed_feature);
+                        if !self.stack.is_empty() {
+                            self.record_cfg_kinds(cfg_kinds);
+                        }
+
+                        match self.stack.pop() {
+                            Some((term_index, ComplexFeature::None)) => {
+                                self.stack.push((term_index, parsed_feature));
+                            }
+                            // multiple attributes on the same node: all of them must be satisfied
+                            // #[cfg(feature = "a")] #[cfg(feature = "b")]
+                            Some((term_index, ComplexFeature::All(mut features))) => {
+                                features.push(parsed_feature);
+                                self.stack.push((term_index, ComplexFeature::All(features)));
+                            }
+                            Some((term_index, features)) => {
+                                self.stack.push((
+                                    term_index,
+                                    ComplexFeature::All(vec![features, parsed_feature]),
+                                ));
+                            }
+                            // attribute on the crate root: #![cfg(feature = "a")]
+                            None => {
+                                let error = self.malformed_cfg(
+                                    "features on the crate root are not supported",
+                                    attr.span,
+                                );
+                                self.errors.push(error);
+                            }
                         }
-                        None => panic!("Error: stack is empty while in attribute (cfg) visit"),
                     }
                 }
             }
@@ -1086,6 +2498,21 @@ impl<'ast> Visitor<'ast> for CollectVisitor {
         let ident = None;
         let node_id = self.get_node_id();
         let kind_string = TermWeightKind::parse_kind_variant_name(format!("{:?}", &cur_ex.kind));
+        let target = match &cur_ex.kind {
+            ExprKind::Call(call, ..) => match (&call.kind, &self.resolutions) {
+                (ExprKind::Path(None, path), Some(resolutions)) if self.resolve_calls => {
+                    resolutions.call_target(call.id, path)
+                }
+                _ => None,
+            },
+            ExprKind::MethodCall(method_call) => match &self.resolutions {
+                Some(resolutions) if self.resolve_calls => {
+                    resolutions.method_target(&self.source_map, method_call.seg.ident)
+                }
+                _ => None,
+            },
+            _ => None,
+        };
         let kind = match &cur_ex.kind {
             // children weight
             ExprKind::Array(..)
@@ -1169,13 +2596,19 @@ impl<'ast> Visitor<'ast> for CollectVisitor {
             | ExprKind::Dummy => TermWeightKind::No(kind_string),
         };
 
-        self.pre_walk(kind, ident, node_id);
+        self.pre_walk(kind, ident, node_id, cur_ex.span);
+        self.set_resolution(node_id, None, target);
         walk_expr(self, cur_ex);
         self.post_walk(node_id);
     }
 
     /// Visit item, like functions, structs, enums
     fn visit_item(&mut self, cur_item: &'ast Item) {
+        if CollectVisitor::compile_error_guard_args(cur_item).is_some() {
+            self.collect_compile_error_guard(cur_item);
+            return;
+        }
+
         let ident = Some(cur_item.ident.to_string());
         let node_id = self.get_node_id();
         let kind_string = TermWeightKind::parse_kind_variant_name(format!("{:?}", &cur_item.kind));
@@ -1219,7 +2652,12 @@ impl<'ast> Visitor<'ast> for CollectVisitor {
             | ItemKind::DelegationMac(..) => TermWeightKind::No(kind_string),
         };
 
-        self.pre_walk(kind, ident, node_id);
+        let def_path = self
+            .resolutions
+            .as_ref()
+            .and_then(|resolutions| resolutions.definition(cur_item.id));
+        self.pre_walk(kind, ident, node_id, cur_item.span);
+        self.set_resolution(node_id, def_path, None);
         walk_item(self, cur_item);
         self.post_walk(node_id);
     }
@@ -1256,13 +2694,25 @@ impl<'ast> Visitor<'ast> for CollectVisitor {
             AssocItemKind::DelegationMac(..) => TermWeightKind::No(kind_string),
         };
 
-        self.pre_walk(kind, ident, node_id);
+        let def_path = self
+            .resolutions
+            .as_ref()
+            .and_then(|resolutions| resolutions.definition(cur_aitem.id));
+        self.pre_walk(kind, ident, node_id, cur_aitem.span);
+        self.set_resolution(node_id, def_path, None);
         walk_assoc_item(self, cur_aitem, ctxt);
         self.post_walk(node_id);
     }
 
     /// Visit statement, like let, if, while
     fn visit_stmt(&mut self, cur_stmt: &'ast Stmt) -> Self::Result {
+        // a `compile_error!` in a block is not a statement (see `visit_item`)
+        if let StmtKind::Item(item) = &cur_stmt.kind {
+            if CollectVisitor::compile_error_guard_args(item).is_some() {
+                return self.visit_item(item);
+            }
+        }
+
         let ident = None;
         let node_id = self.get_node_id();
         let kind_string = TermWeightKind::parse_kind_variant_name(format!("{:?}", &cur_stmt.kind));
@@ -1292,7 +2742,7 @@ impl<'ast> Visitor<'ast> for CollectVisitor {
             StmtKind::Empty => TermWeightKind::No(kind_string),
         };
 
-        self.pre_walk(kind, ident, node_id);
+        self.pre_walk(kind, ident, node_id, cur_stmt.span);
         walk_stmt(self, cur_stmt);
         self.post_walk(node_id);
     }
@@ -1304,7 +2754,7 @@ impl<'ast> Visitor<'ast> for CollectVisitor {
         let kind_string = "FieldDef".to_string();
         let kind = TermWeightKind::Intrinsic(kind_string);
 
-        self.pre_walk(kind, ident, node_id);
+        self.pre_walk(kind, ident, node_id, cur_field.span);
         walk_field_def(self, cur_field);
         self.post_walk(node_id);
     }
@@ -1316,7 +2766,7 @@ impl<'ast> Visitor<'ast> for CollectVisitor {
         let kind_string = "Variant".to_string();
         let kind = TermWeightKind::Intrinsic(kind_string);
 
-        self.pre_walk(kind, ident, node_id);
+        self.pre_walk(kind, ident, node_id, cur_var.span);
         walk_variant(self, cur_var);
         self.post_walk(node_id);
     }
@@ -1328,7 +2778,7 @@ impl<'ast> Visitor<'ast> for CollectVisitor {
         let kind_string = "Arm".to_string();
         let kind = TermWeightKind::Children(kind_string);
 
-        self.pre_walk(kind, ident, node_id);
+        self.pre_walk(kind, ident, node_id, cur_arm.span);
         walk_arm(self, cur_arm);
         self.post_walk(node_id);
     }
@@ -1340,7 +2790,7 @@ impl<'ast> Visitor<'ast> for CollectVisitor {
         let kind_string = "Param".to_string();
         let kind = TermWeightKind::No(kind_string);
 
-        self.pre_walk(kind, ident, node_id);
+        self.pre_walk(kind, ident, node_id, cur_par.span);
         walk_param(self, cur_par);
         self.post_walk(node_id);
     }
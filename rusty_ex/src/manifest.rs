@@ -0,0 +1,101 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::env;
+use std::path::{Path, PathBuf};
+
+use crate::configs::prop_formula::PropFormula;
+use crate::errors::{Result, RustyExError};
+
+/// Features declared in the `[features]` table of the manifest of a package
+#[derive(Debug, Clone, Default)]
+pub struct ManifestFeatures {
+    /// Each feature with the features (or optional dependencies) it enables
+    pub features: BTreeMap<String, Vec<String>>,
+}
+
+impl ManifestFeatures {
+    /// Features of the package being compiled (the manifest in `CARGO_MANIFEST_DIR`)
+    pub fn from_env() -> Result<Self> {
+        let dir =
+            env::var("CARGO_MANIFEST_DIR").map_err(|error| RustyExError::InvalidManifest {
+                path: "CARGO_MANIFEST_DIR".to_string(),
+                message: error.to_string(),
+            })?;
+        ManifestFeatures::from_manifest(&PathBuf::from(dir).join("Cargo.toml"))
+    }
+
+    /// Features of the package of a `Cargo.toml`
+    pub fn from_manifest(manifest_path: &Path) -> Result<Self> {
+        let invalid_manifest = |message: String| RustyExError::InvalidManifest {
+            path: manifest_path.display().to_string(),
+            message,
+        };
+
+        let manifest_path = manifest_path
+            .canonicalize()
+            .map_err(|error| invalid_manifest(error.to_string()))?;
+        let metadata = cargo_metadata::MetadataCommand::new()
+            .manifest_path(&manifest_path)
+            .no_deps()
+            .other_options(["--offline".to_string()])
+            .exec()
+            .map_err(|error| invalid_manifest(error.to_string()))?;
+        // the metadata lists all the packages of the workspace
+        let package = metadata
+            .packages
+            .into_iter()
+            .find(|package| package.manifest_path == manifest_path)
+            .ok_or_else(|| invalid_manifest("not a package".to_string()))?;
+
+        Ok(ManifestFeatures {
+            features: package.features.into_iter().collect(),
+        })
+    }
+
+    /// Features enabled by cargo when the given ones are requested (and the `default` one, if
+    /// `default`): the requested features and the ones they enable, transitively. Optional
+    /// dependencies and features of dependencies are skipped
+    pub fn enabled_features(
+        &self,
+        requested: impl IntoIterator<Item = String>,
+        default: bool,
+    ) -> BTreeSet<String> {
+        let mut enabled = BTreeSet::new();
+        let mut to_visit = requested.into_iter().collect::<Vec<_>>();
+        if default && self.features.contains_key("default") {
+            to_visit.push("default".to_string());
+        }
+        while let Some(feature) = to_visit.pop() {
+            if feature.starts_with("dep:")
+                || feature.contains('/')
+                || !enabled.insert(feature.clone())
+            {
+                continue;
+            }
+            to_visit.extend(self.features.get(&feature).into_iter().flatten().cloned());
+        }
+        enabled
+    }
+
+    /// Dependencies between the features as implications: `a = ["b", "c"]` is `a -> (b & c)`.
+    /// Optional dependencies (`dep:x`) and features of dependencies (`x/y`) are skipped
+    pub fn to_prop_formula(&self) -> PropFormula<String> {
+        PropFormula::And(
+            self.features
+                .iter()
+                .filter_map(|(feature, enables)| {
+                    let enables = enables
+                        .iter()
+                        .filter(|enabled| !enabled.starts_with("dep:") && !enabled.contains('/'))
+                        .map(|enabled| PropFormula::Var(enabled.clone()))
+                        .collect::<Vec<_>>();
+                    (!enables.is_empty()).then(|| {
+                        PropFormula::Implies(
+                            Box::new(PropFormula::Var(feature.clone())),
+                            Box::new(PropFormula::And(enables)),
+                        )
+                    })
+                })
+                .collect(),
+        )
+    }
+}
@@ -0,0 +1,428 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+use crate::configs::config_solver::ConfigSolver;
+use crate::configs::prop_formula::PropFormula;
+use crate::configs::{CnfFormula, CnfLit};
+use crate::coverage::{Configuration, ConfigurationCoverage, Coverage, CoverageArtifact};
+use crate::errors::RustyExError;
+use crate::types::{ArtifactIndex, ArtifactKey, ArtifactsTree, CfgKind, ComplexFeature, Feature};
+use crate::GLOBAL_NODE_INDEX;
+
+/// Smallest set of valid configurations compiling every artifact that some valid configuration
+/// compiles (see `--min-configurations`). Valid configurations satisfy the constraints between
+/// the features (the `compile_error!` guards and, optionally, the manifest) and disable the other
+/// `cfg` options (e.g., `unix`), which cargo arguments cannot enable
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MinimumCover {
+    /// Configurations (named by their cargo arguments), the one compiling the most new artifacts
+    /// first (the most new weight, with the weighted variant)
+    pub configurations: Vec<ConfigurationCoverage>,
+    /// Whether no smaller set exists, false if the time limit is hit before proving it
+    pub optimal: bool,
+    pub weighted: bool,
+    /// Number of artifacts compiled by at least one valid configuration, the global one excluded
+    pub artifacts: usize,
+    /// Total weight of the artifacts compiled by at least one valid configuration, each one
+    /// without the artifacts nested in it
+    pub weight: f64,
+    /// Artifacts not compiled by any valid configuration
+    pub unsatisfiable: Vec<CoverageArtifact>,
+}
+
+/// Clauses whose literals are the names of the features
+type NamedCnf = Vec<Vec<(String, bool)>>;
+
+/// Artifact to cover, with the `cfg`s of the artifact and of its ancestors and its exclusive
+/// weight
+struct CoverArtifact {
+    index: ArtifactIndex,
+    cfgs: Vec<ComplexFeature<Feature>>,
+    cnf: NamedCnf,
+    weight: f64,
+}
+
+impl CoverArtifact {
+    fn compiled_by(&self, enabled: &BTreeSet<String>) -> bool {
+        self.cfgs.iter().all(|cfg| cfg.is_enabled(enabled))
+    }
+}
+
+/// Variable of the encoding, of one of the configurations
+#[derive(Clone, Eq, Hash, PartialEq)]
+enum CoverVar {
+    /// The feature is enabled
+    Feature(String, usize),
+    /// The artifact (by position in the artifacts to cover) is compiled
+    Compiled(usize, usize),
+}
+
+/// Encoding of one or more configurations (copies of the features) as SAT variables
+#[derive(Default)]
+struct Encoding {
+    vars: HashMap<CoverVar, u32>,
+}
+
+impl Encoding {
+    fn var(&mut self, var: CoverVar) -> u32 {
+        let next = self.vars.len() as u32;
+        *self.vars.entry(var).or_insert(next)
+    }
+
+    /// Clauses over the features of the `configuration`, implied by the `guard` variable if any
+    fn clauses(
+        &mut self,
+        cnf: &NamedCnf,
+        configuration: usize,
+        guard: Option<u32>,
+    ) -> CnfFormula<u32> {
+        let mut clauses = Vec::new();
+        for clause in cnf {
+            let mut lits = guard
+                .map(|guard| (guard, false))
+                .into_iter()
+                .collect::<Vec<_>>();
+            for (name, positive) in clause {
+                lits.push((
+                    self.var(CoverVar::Feature(name.clone(), configuration)),
+                    *positive,
+                ));
+            }
+            clauses.push(lits);
+        }
+        clauses
+    }
+
+    /// Literal forcing the artifact to be compiled by the `configuration`
+    fn compiled(&mut self, artifact: usize, configuration: usize) -> CnfLit<u32> {
+        (self.var(CoverVar::Compiled(artifact, configuration)), true)
+    }
+
+    /// Features enabled in the `configuration` by a solution
+    fn enabled(&self, true_vars: &HashSet<u32>, configuration: usize) -> BTreeSet<String> {
+        self.vars
+            .iter()
+            .filter_map(|(var, index)| match var {
+                CoverVar::Feature(name, c) if *c == configuration && true_vars.contains(index) => {
+                    Some(name.clone())
+                }
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+/// CNF of the conjunction of the formulas, over the names of the features
+fn named_cnf(formulas: Vec<PropFormula<String>>) -> NamedCnf {
+    let mut formula = PropFormula::And(formulas);
+    formula.to_flat_cnf();
+    let (cnf, mapping) = formula.to_cnf_repr::<u32>(true);
+    let names = mapping
+        .into_iter()
+        .map(|(name, var)| (var, name))
+        .collect::<HashMap<_, _>>();
+    cnf.into_iter()
+        .map(|clause| {
+            clause
+                .into_iter()
+                .map(|(var, positive)| (names[&var].clone(), positive))
+                .collect()
+        })
+        .collect()
+}
+
+/// Find a small set of valid configurations compiling every artifact that can be compiled.
+///
+/// A greedy cover is built first: each configuration compiles the first uncovered artifact (the
+/// heaviest with `weighted`, the one with the most `cfg`s otherwise) and as many of the others as
+/// the solver can fit, with the features not needed disabled. Then, while there is time, a set
+/// with one configuration less is searched, until none exists (the set is optimal). The solver
+/// is interrupted at the time limit (if it can be represented), keeping the best set found so
+/// far.
+///
+/// If a check of the greedy cover is interrupted, the checked artifacts are neither covered nor
+/// reported as unsatisfiable, and the interruption is pushed to `errors`
+pub fn minimum_cover<Key: ArtifactKey>(
+    artifacts_tree: &ArtifactsTree<Key>,
+    constraints: &[PropFormula<String>],
+    cfg_kinds: &HashMap<String, CfgKind>,
+    weighted: bool,
+    time_limit: Duration,
+    errors: &mut Vec<RustyExError>,
+) -> MinimumCover {
+    let deadline = Instant::now().checked_add(time_limit);
+    let mut constraints = named_cnf(constraints.to_vec());
+    // cargo arguments only set the features, the other options are disabled
+    constraints.extend(
+        cfg_kinds
+            .iter()
+            .filter(|(_, kind)| **kind != CfgKind::Feature)
+            .map(|(name, _)| vec![(name.clone(), false)]),
+    );
+    let artifacts = artifacts_tree
+        .graph
+        .node_indices()
+        .filter(|index| *index != ArtifactIndex::new(GLOBAL_NODE_INDEX))
+        .map(|index| {
+            let cfgs = artifacts_tree
+                .ancestors_chain(index)
+                .iter()
+                .map(|index| artifacts_tree.graph[*index].complex_feature.clone())
+                .filter(|complex_feature| !matches!(complex_feature, ComplexFeature::None))
+                .collect::<Vec<_>>();
+            CoverArtifact {
+                index,
+                cnf: named_cnf(cfgs.iter().map(|cfg| cfg.to_prop_formula()).collect()),
+                cfgs,
+                weight: artifacts_tree.exclusive_weight(index),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    // a single configuration, to check the artifacts and to build the configurations
+    let mut encoding = Encoding::default();
+    let mut solver = ConfigSolver::default();
+    solver.add_cnf(encoding.clauses(&constraints, 0, None));
+    for (i, artifact) in artifacts.iter().enumerate() {
+        let (compiled, _) = encoding.compiled(i, 0);
+        solver.add_cnf(encoding.clauses(&artifact.cnf, 0, Some(compiled)));
+    }
+
+    let mut interrupted = false;
+    let (mut coverable, mut unsatisfiable) = (Vec::new(), Vec::new());
+    for i in 0..artifacts.len() {
+        match solver.is_satisfiable_assuming(&[encoding.compiled(i, 0)]) {
+            Some(true) => coverable.push(i),
+            Some(false) => unsatisfiable.push(i),
+            None => interrupted = true,
+        }
+    }
+    match weighted {
+        true => coverable.sort_by(|a, b| artifacts[*b].weight.total_cmp(&artifacts[*a].weight)),
+        false => coverable.sort_by_key(|i| std::cmp::Reverse(artifacts[*i].cfgs.len())),
+    }
+
+    let mut configurations = Vec::new();
+    let mut uncovered = coverable.clone();
+    while let Some(first) = uncovered.first() {
+        let mut selected = vec![*first];
+        for i in &uncovered[1..] {
+            if expired(deadline) {
+                break;
+            }
+            let mut assumptions = selected
+                .iter()
+                .map(|j| encoding.compiled(*j, 0))
+                .collect::<Vec<_>>();
+            assumptions.push(encoding.compiled(*i, 0));
+            match solver.is_satisfiable_assuming(&assumptions) {
+                Some(true) => selected.push(*i),
+                Some(false) => {}
+                None => interrupted = true,
+            }
+        }
+        match configuration(&mut solver, &mut encoding, &selected, deadline) {
+            Some(enabled) => {
+                uncovered.retain(|i| !selected.contains(i) && !artifacts[*i].compiled_by(&enabled));
+                configurations.push(enabled);
+            }
+            // interrupted, the selected artifacts are left uncovered
+            None => {
+                interrupted = true;
+                uncovered.retain(|i| !selected.contains(i));
+            }
+        }
+    }
+
+    if interrupted {
+        errors.push(RustyExError::SolverInterrupted {
+            task: "building the minimum configurations".to_string(),
+        });
+    }
+
+    let mut optimal = configurations.len() <= 1;
+    while !optimal && !expired(deadline) {
+        match smaller_cover(
+            &artifacts,
+            &constraints,
+            &coverable,
+            configurations.len() - 1,
+            deadline,
+        ) {
+            SmallerCover::Found(smaller) => {
+                configurations = smaller
+                    .iter()
+                    .map(|enabled| {
+                        let selected = coverable
+                            .iter()
+                            .copied()
+                            .filter(|i| artifacts[*i].compiled_by(enabled))
+                            .collect::<Vec<_>>();
+                        // the configuration found is kept as is, if interrupted
+                        configuration(&mut solver, &mut encoding, &selected, deadline)
+                            .unwrap_or_else(|| enabled.clone())
+                    })
+                    .collect();
+                optimal = configurations.len() <= 1;
+            }
+            SmallerCover::NotFound => optimal = true,
+            SmallerCover::Interrupted => break,
+        }
+    }
+
+    // the configuration compiling the most new artifacts (or weight) first, the useless ones dropped
+    let mut uncovered = coverable.iter().copied().collect::<HashSet<_>>();
+    let mut ordered = Vec::new();
+    while !uncovered.is_empty() && !configurations.is_empty() {
+        let gain = |enabled: &BTreeSet<String>| {
+            uncovered
+                .iter()
+                .filter(|i| artifacts[**i].compiled_by(enabled))
+                .map(|i| match weighted {
+                    true => artifacts[*i].weight,
+                    false => 1.0,
+                })
+                .sum::<f64>()
+        };
+        let (best, _) = configurations
+            .iter()
+            .enumerate()
+            .map(|(i, enabled)| (i, gain(enabled)))
+            .max_by(|(i, a), (j, b)| a.total_cmp(b).then(j.cmp(i)))
+            .expect("Error: no configurations");
+        let enabled = configurations.remove(best);
+        uncovered.retain(|i| !artifacts[*i].compiled_by(&enabled));
+        ordered.push(Configuration {
+            name: cargo_args(&enabled),
+            enabled,
+        });
+    }
+
+    MinimumCover {
+        configurations: Coverage::new(artifacts_tree, &ordered).configurations,
+        optimal,
+        weighted,
+        artifacts: coverable.len(),
+        weight: coverable.iter().map(|i| artifacts[*i].weight).sum(),
+        unsatisfiable: unsatisfiable
+            .iter()
+            .map(|i| {
+                let artifact_node = &artifacts_tree.graph[artifacts[*i].index];
+                CoverageArtifact {
+                    ident: artifact_node.ident.clone(),
+                    span: artifact_node.span.clone(),
+                    complex_feature: artifact_node.complex_feature.to_string(),
+                    weight: artifacts[*i].weight,
+                    configurations: Vec::new(),
+                }
+            })
+            .collect(),
+    }
+}
+
+/// Whether the deadline, if any, has passed
+fn expired(deadline: Option<Instant>) -> bool {
+    deadline.is_some_and(|deadline| Instant::now() >= deadline)
+}
+
+/// Valid configuration compiling the `selected` artifacts, with the features that are not needed
+/// disabled (one at a time, until the deadline). `None` if the artifacts cannot be compiled
+/// together or the solver is interrupted before finding it
+fn configuration(
+    solver: &mut ConfigSolver<rustsat_minisat::core::Minisat>,
+    encoding: &mut Encoding,
+    selected: &[usize],
+    deadline: Option<Instant>,
+) -> Option<BTreeSet<String>> {
+    let mut assumptions = selected
+        .iter()
+        .map(|i| encoding.compiled(*i, 0))
+        .collect::<Vec<_>>();
+    if solver.is_satisfiable_assuming(&assumptions) != Some(true) {
+        return None;
+    }
+    let mut enabled = encoding.enabled(&solver.true_vars(), 0);
+    for name in enabled.clone() {
+        if expired(deadline) {
+            break;
+        }
+        assumptions.push((encoding.var(CoverVar::Feature(name, 0)), false));
+        match solver.is_satisfiable_assuming(&assumptions) {
+            Some(true) => enabled = encoding.enabled(&solver.true_vars(), 0),
+            // the feature is kept enabled, also if the check is interrupted
+            Some(false) | None => {
+                assumptions.pop();
+            }
+        }
+    }
+    Some(enabled)
+}
+
+/// Outcome of the search of a smaller cover
+enum SmallerCover {
+    /// Enabled features of each configuration
+    Found(Vec<BTreeSet<String>>),
+    NotFound,
+    /// The solver was interrupted at the deadline
+    Interrupted,
+}
+
+/// `size` valid configurations compiling all the `coverable` artifacts, if they exist and are
+/// found before the deadline
+fn smaller_cover(
+    artifacts: &[CoverArtifact],
+    constraints: &NamedCnf,
+    coverable: &[usize],
+    size: usize,
+    deadline: Option<Instant>,
+) -> SmallerCover {
+    let mut encoding = Encoding::default();
+    let mut solver = ConfigSolver::default();
+    for configuration in 0..size {
+        solver.add_cnf(encoding.clauses(constraints, configuration, None));
+        for i in coverable {
+            let (compiled, _) = encoding.compiled(*i, configuration);
+            solver.add_cnf(encoding.clauses(&artifacts[*i].cnf, configuration, Some(compiled)));
+        }
+    }
+    for i in coverable {
+        let clause = (0..size)
+            .map(|configuration| encoding.compiled(*i, configuration))
+            .collect();
+        solver.add_cnf(vec![clause]);
+    }
+    // the configurations are interchangeable: the first artifact is compiled by the first one
+    if let Some(first) = coverable.first() {
+        solver.add_cnf(vec![vec![encoding.compiled(*first, 0)]]);
+    }
+
+    let satisfiable = match deadline {
+        Some(deadline) => solver.is_satisfiable_before(deadline),
+        None => solver.is_satisfiable(),
+    };
+    match satisfiable {
+        Some(true) => {
+            let true_vars = solver.true_vars();
+            SmallerCover::Found(
+                (0..size)
+                    .map(|configuration| encoding.enabled(&true_vars, configuration))
+                    .collect(),
+            )
+        }
+        Some(false) => SmallerCover::NotFound,
+        None => SmallerCover::Interrupted,
+    }
+}
+
+/// Cargo arguments enabling exactly the enabled features (the other `cfg` options are disabled,
+/// the names not used in the `cfg`s come from the manifest)
+fn cargo_args(enabled: &BTreeSet<String>) -> String {
+    match enabled.is_empty() {
+        true => "--no-default-features".to_string(),
+        false => format!(
+            "--no-default-features --features {}",
+            enabled.iter().cloned().collect::<Vec<_>>().join(",")
+        ),
+    }
+}
@@ -0,0 +1,81 @@
+use rustc_hir::def::DefKind;
+use rustc_middle::mir::{Body, TerminatorKind};
+use rustc_middle::ty::TyCtxt;
+use std::collections::HashMap;
+
+use crate::errors::RustyExError;
+use crate::resolution::def_path_string;
+
+/// Callbacks of the compilation used to weight the functions by their MIR.
+///
+/// The compilation uses the actual `cfg`s (the ones of the `cargo` invocation), so only the
+/// code enabled in the current configuration has MIR: the `cfg`s cannot be captured in the
+/// same compilation, because the code of all configurations does not type check together
+#[derive(Default)]
+struct MirCallbacks {
+    /// MIR weight of each item by resolved path, `None` if the analysis is not reached
+    weights: Option<HashMap<String, f64>>,
+}
+
+impl rustc_driver::Callbacks for MirCallbacks {
+    /// Called before creating the compiler instance
+    fn config(&mut self, config: &mut rustc_interface::Config) {
+        // Errors and warnings are already reported by the compilation capturing the `cfg`s
+        config.psess_created = Some(Box::new(|sess| {
+            sess.dcx().make_silent(None, false);
+        }));
+    }
+
+    /// Called after analysis, when the MIR of the crate can be built
+    fn after_analysis(
+        &mut self,
+        _compiler: &rustc_interface::interface::Compiler,
+        tcx: TyCtxt<'_>,
+    ) -> rustc_driver::Compilation {
+        let mut weights = HashMap::new();
+        for def_id in tcx.mir_keys(()) {
+            let def_id = def_id.to_def_id();
+            if !matches!(
+                tcx.def_kind(def_id),
+                DefKind::Fn | DefKind::AssocFn | DefKind::Closure
+            ) || !tcx.is_mir_available(def_id)
+            {
+                continue;
+            }
+
+            // closures are weighted with the item that defines them
+            let item = def_path_string(tcx, tcx.typeck_root_def_id(def_id));
+            *weights.entry(item).or_insert(0.0) += mir_weight(tcx.optimized_mir(def_id));
+        }
+        self.weights = Some(weights);
+
+        rustc_driver::Compilation::Stop
+    }
+}
+
+/// Weight of a MIR body: number of basic blocks, statements and calls
+pub fn mir_weight(body: &Body<'_>) -> f64 {
+    let basic_blocks = body.basic_blocks.len();
+    let statements = body
+        .basic_blocks
+        .iter()
+        .map(|block| block.statements.len())
+        .sum::<usize>();
+    let calls = body
+        .basic_blocks
+        .iter()
+        .filter(|block| matches!(block.terminator().kind, TerminatorKind::Call { .. }))
+        .count();
+
+    (basic_blocks + statements + calls) as f64
+}
+
+/// Compile the crate with the actual `cfg`s up to the analysis and weight each function
+/// (and associated function) by its MIR, by resolved path
+pub fn mir_weights(compiler_args: &[String]) -> Result<HashMap<String, f64>, RustyExError> {
+    let mut callbacks = MirCallbacks::default();
+    rustc_driver::catch_fatal_errors(|| rustc_driver::run_compiler(compiler_args, &mut callbacks))
+        .ok()
+        .and(callbacks.weights)
+        .ok_or(RustyExError::MirUnavailable)
+}
@@ -0,0 +1,75 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Name of the index manifest written in the output directory of each crate
+pub const INDEX_FILE_NAME: &str = "index.json";
+
+/// Index manifest of the output directory of a crate: the files written, by output
+#[derive(Debug, Default, Serialize)]
+struct OutputIndex {
+    crate_name: String,
+    crate_type: String,
+    files: BTreeMap<String, String>,
+}
+
+/// Destination of the outputs of the analysis of a crate: `stdout`, or a file for each
+/// output in the directory of the crate (see `--output-dir`)
+#[derive(Debug, Default)]
+pub struct Output {
+    /// Directory of the crate, `None` to print on `stdout`
+    dir: Option<PathBuf>,
+    index: OutputIndex,
+}
+
+impl Output {
+    /// Print the outputs on `stdout`
+    pub fn stdout() -> Self {
+        Output::default()
+    }
+
+    /// Write the outputs in the directory `<output_dir>/<crate_name>-<crate_type>`
+    pub fn directory(output_dir: &Path, crate_name: &str, crate_type: &str) -> Self {
+        let dir = output_dir.join(format!("{}-{}", crate_name, crate_type));
+        fs::create_dir_all(&dir).expect("Error: cannot create the output directory");
+        Output {
+            dir: Some(dir),
+            index: OutputIndex {
+                crate_name: crate_name.to_string(),
+                crate_type: crate_type.to_string(),
+                files: BTreeMap::new(),
+            },
+        }
+    }
+
+    /// Whether the outputs are written in a directory
+    pub fn is_directory(&self) -> bool {
+        self.dir.is_some()
+    }
+
+    /// Print an output, or write it in its file (`name` is the key in the index)
+    pub fn emit(&mut self, name: &str, file_name: &str, content: &str) {
+        match &self.dir {
+            Some(dir) => {
+                fs::write(dir.join(file_name), format!("{}\n", content))
+                    .expect("Error: cannot write the output file");
+                self.index
+                    .files
+                    .insert(name.to_string(), file_name.to_string());
+            }
+            None => println!("{}", content),
+        }
+    }
+
+    /// Write the index manifest (nothing to do on `stdout`)
+    pub fn finish(self) {
+        if let Some(dir) = &self.dir {
+            fs::write(
+                dir.join(INDEX_FILE_NAME),
+                serde_json::to_string_pretty(&self.index).expect("Error: cannot serialize data"),
+            )
+            .expect("Error: cannot write the output file");
+        }
+    }
+}
@@ -0,0 +1,319 @@
+use rustworkx_core::petgraph::dot::{Config, Dot};
+use rustworkx_core::petgraph::graph::DiGraph;
+use rustworkx_core::petgraph::visit::EdgeRef;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Display, Formatter};
+
+use crate::configs::centrality::{
+    Centrality, CentralityParams, FeatureCentrality, PolarityWeights,
+};
+use crate::errors::RustyExError;
+use crate::export::{AttrType, AttrValue, ExportEdge, ExportGraph, ExportNode};
+use crate::types::{Edge, Feature, FeatureIndex, FeatureKey, FeaturesGraph};
+use crate::GLOBAL_DUMMY_INDEX;
+
+/// Polarity of a feature in a `cfg`: enabled (`a`) or disabled (`not(a)`)
+#[derive(Debug, Clone, Copy, Eq, Hash, PartialEq, Serialize, Deserialize)]
+pub enum Polarity {
+    Positive,
+    Negative,
+}
+
+impl Polarity {
+    fn of(feature: &Feature) -> Self {
+        match feature.not {
+            true => Polarity::Negative,
+            false => Polarity::Positive,
+        }
+    }
+}
+
+impl Display for Polarity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Polarity::Positive => write!(f, "+"),
+            Polarity::Negative => write!(f, "-"),
+        }
+    }
+}
+
+/// Edge of the Polarity Graph: the weight of the edge of the Features Graph and the polarity
+/// of the features at its ends
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PolarityEdge {
+    pub weight: f64,
+    pub source: Polarity,
+    pub target: Polarity,
+}
+
+/// Real feature (e.g., a cargo feature), merging the nodes of the feature and of its negation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PolarityNode {
+    pub name: String,
+    /// Index of the feature in the Features Graph, if it is used
+    pub positive: Option<FeatureIndex>,
+    /// Index of the negated feature in the Features Graph, if it is used
+    pub negative: Option<FeatureIndex>,
+}
+
+/// Features Graph with a single node for each real feature (`a` and `!a` are the same node),
+/// the polarity of the features is on the edges
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PolarityGraph {
+    pub graph: DiGraph<PolarityNode, PolarityEdge>,
+    #[serde(skip)]
+    pub nodes: HashMap<String, FeatureIndex>,
+}
+
+impl PolarityGraph {
+    /// Merge the nodes of the features with the same name. The nodes keep the order of the
+    /// Features Graph (the global and the dummy node come first). The edges of the dummy node are
+    /// not duplicated: they only make the graph strongly connected
+    pub fn new(features_graph: &FeaturesGraph) -> Self {
+        let mut polarity_graph = PolarityGraph {
+            graph: DiGraph::new(),
+            nodes: HashMap::new(),
+        };
+
+        for index in features_graph.graph.node_indices() {
+            let feature = &features_graph.graph[index].feature.0;
+            let polarity_index = match polarity_graph.nodes.get(&feature.name) {
+                Some(polarity_index) => *polarity_index,
+                None => {
+                    let polarity_index = polarity_graph.graph.add_node(PolarityNode {
+                        name: feature.name.clone(),
+                        positive: None,
+                        negative: None,
+                    });
+                    polarity_graph
+                        .nodes
+                        .insert(feature.name.clone(), polarity_index);
+                    polarity_index
+                }
+            };
+            let polarity_node = &mut polarity_graph.graph[polarity_index];
+            match Polarity::of(feature) {
+                Polarity::Positive => polarity_node.positive = Some(index),
+                Polarity::Negative => polarity_node.negative = Some(index),
+            }
+        }
+
+        let mut dummy_targets = HashSet::new();
+        for edge in features_graph.graph.edge_references() {
+            let (source, target) = (
+                &features_graph.graph[edge.source()].feature.0,
+                &features_graph.graph[edge.target()].feature.0,
+            );
+            let (source_index, target_index) = (
+                polarity_graph.nodes[&source.name],
+                polarity_graph.nodes[&target.name],
+            );
+            if edge.source() == FeatureIndex::new(GLOBAL_DUMMY_INDEX)
+                && !dummy_targets.insert(target_index)
+            {
+                continue;
+            }
+            polarity_graph.graph.add_edge(
+                source_index,
+                target_index,
+                PolarityEdge {
+                    weight: edge.weight().weight,
+                    source: Polarity::of(source),
+                    target: Polarity::of(target),
+                },
+            );
+        }
+
+        polarity_graph
+    }
+
+    /// The Polarity Graph as a Features Graph (of positive features), to compute the centrality
+    /// measures. The polarity is dropped, as are the self loops (a feature nested in its negation)
+    fn to_features_graph(&self, features_graph: &FeaturesGraph) -> FeaturesGraph {
+        let mut unified = FeaturesGraph::new();
+        for polarity_node in self.graph.node_weights() {
+            let complex_feature = [polarity_node.positive, polarity_node.negative]
+                .into_iter()
+                .flatten()
+                .flat_map(|index| features_graph.graph[index].complex_feature.clone())
+                .collect();
+            unified.create_node(
+                FeatureKey(Feature {
+                    name: polarity_node.name.clone(),
+                    not: false,
+                }),
+                None,
+                complex_feature,
+            );
+        }
+        for edge in self
+            .graph
+            .edge_references()
+            .filter(|edge| edge.source() != edge.target())
+        {
+            unified.graph.add_edge(
+                edge.source(),
+                edge.target(),
+                Edge {
+                    weight: edge.weight().weight,
+                },
+            );
+        }
+        unified
+    }
+
+    /// Centrality measures of each real feature, refined with the weights of the artifacts
+    /// annotated by the feature and by its negation (`refiner_hm` is by index of the Features
+    /// Graph). With `split`, the centrality of the Features Graph is kept, to split the measures
+    /// into the values of the feature and of its negation
+    pub fn centrality(
+        &self,
+        features_graph: &FeaturesGraph,
+        refiner_hm: &HashMap<FeatureIndex, f64>,
+        cnf_mapping: &HashMap<String, u32>,
+        params: &CentralityParams,
+        split: bool,
+    ) -> (Centrality<u32>, Vec<RustyExError>) {
+        let weight = |index: Option<FeatureIndex>| {
+            index
+                .and_then(|index| refiner_hm.get(&index))
+                .copied()
+                .unwrap_or(0.0)
+        };
+        let polarity_weights = self
+            .graph
+            .node_indices()
+            .map(|index| {
+                let polarity_node = &self.graph[index];
+                let weights = PolarityWeights {
+                    positive: weight(polarity_node.positive),
+                    negative: weight(polarity_node.negative),
+                };
+                (index, weights)
+            })
+            .collect::<HashMap<_, _>>();
+
+        // normalized as the refiner of the Features Graph, by the maximum weight
+        let max = polarity_weights
+            .values()
+            .map(|weights| weights.positive + weights.negative)
+            .fold(0.0, f64::max);
+        let unified_refiner_hm = polarity_weights
+            .iter()
+            .filter(|(_, weights)| weights.positive + weights.negative > 0.0)
+            .map(|(index, weights)| (*index, (weights.positive + weights.negative) / max))
+            .collect();
+
+        let (mut centrality, errors) = Centrality::new(
+            &self.to_features_graph(features_graph),
+            &unified_refiner_hm,
+            cnf_mapping,
+            true,
+            params,
+        );
+        if split {
+            // the measures that cannot be computed are already reported
+            let (polarity_centrality, _) =
+                Centrality::new(features_graph, refiner_hm, cnf_mapping, true, params);
+            centrality.polarity_centrality = Some(Box::new(polarity_centrality));
+        }
+        (centrality, errors)
+    }
+
+    /// Polarity Graph in DOT format, edges are labelled with the polarity of their ends
+    pub fn to_dot(&self) -> String {
+        format!(
+            "{:?}",
+            Dot::with_attr_getters(
+                &self.graph,
+                &[Config::NodeNoLabel, Config::EdgeNoLabel],
+                &|_g, e| {
+                    let edge = e.weight();
+                    format!(
+                        "label=\"{}{} {:.2}\"",
+                        edge.source, edge.target, edge.weight
+                    )
+                },
+                &|_g, (index, polarity_node)| {
+                    let polarities = [
+                        polarity_node.positive.map(|_| Polarity::Positive),
+                        polarity_node.negative.map(|_| Polarity::Negative),
+                    ]
+                    .into_iter()
+                    .flatten()
+                    .map(|polarity| polarity.to_string())
+                    .collect::<String>();
+                    format!(
+                        "label=\"i{}: {} [{}]\"",
+                        index.index(),
+                        polarity_node.name,
+                        polarities
+                    )
+                },
+            )
+        )
+    }
+
+    /// Polarity Graph with typed attributes (and the centrality of the features), to be exported
+    pub fn export_graph(&self, centrality: &[FeatureCentrality]) -> ExportGraph {
+        let node_attrs = vec![
+            ("name", AttrType::String),
+            ("positive", AttrType::Bool),
+            ("negative", AttrType::Bool),
+            ("refined_weight", AttrType::Double),
+            ("rank", AttrType::Int),
+        ];
+        let centrality = centrality
+            .iter()
+            .filter(|f| !f.not)
+            .map(|f| (f.name.as_str(), f))
+            .collect::<HashMap<_, _>>();
+
+        let nodes = self
+            .graph
+            .node_indices()
+            .map(|index| {
+                let polarity_node = &self.graph[index];
+                let f = centrality.get(polarity_node.name.as_str());
+                ExportNode {
+                    id: index.index(),
+                    label: polarity_node.name.clone(),
+                    attrs: vec![
+                        AttrValue::String(polarity_node.name.clone()),
+                        AttrValue::Bool(polarity_node.positive.is_some()),
+                        AttrValue::Bool(polarity_node.negative.is_some()),
+                        f.map(|f| f.refined_weight).into(),
+                        f.map_or(AttrValue::None, |f| AttrValue::Int(f.rank as i64)),
+                    ],
+                }
+            })
+            .collect();
+
+        let edges = self
+            .graph
+            .edge_references()
+            .map(|edge| ExportEdge {
+                source: edge.source().index(),
+                target: edge.target().index(),
+                attrs: vec![
+                    AttrValue::Double(edge.weight().weight),
+                    AttrValue::String(edge.weight().source.to_string()),
+                    AttrValue::String(edge.weight().target.to_string()),
+                ],
+            })
+            .collect();
+
+        ExportGraph {
+            multigraph: true,
+            node_attrs,
+            edge_attrs: vec![
+                ("weight", AttrType::Double),
+                ("source_polarity", AttrType::String),
+                ("target_polarity", AttrType::String),
+            ],
+            nodes,
+            edges,
+        }
+    }
+}
@@ -0,0 +1,144 @@
+use rustworkx_core::petgraph::visit::EdgeRef;
+use serde::Serialize;
+
+use crate::configs::centrality::FeatureCentrality;
+use crate::types::{ArtifactKey, ArtifactsTree, FeatureIndex, FeaturesGraph, SourceSpan};
+use crate::utils::xml_escape;
+use crate::GLOBAL_DUMMY_INDEX;
+
+/// Template of the report, the data is injected in place of `DATA_PLACEHOLDER`
+const TEMPLATE: &str = include_str!("report.html");
+const DATA_PLACEHOLDER: &str = "/*__RUSTY_EX_DATA__*/null";
+
+/// Node of a graph of the report
+#[derive(Debug, Serialize)]
+struct ReportNode {
+    id: usize,
+    label: String,
+    /// Complex features (`cfg` expressions) of the node
+    cfgs: Vec<String>,
+    /// Artifacts of the node (indices in the artifacts tree)
+    artifacts: Vec<usize>,
+    weight: String,
+    span: Option<SourceSpan>,
+}
+
+/// Edge of a graph of the report, from the enclosed node to the enclosing one
+#[derive(Debug, Serialize)]
+struct ReportEdge {
+    source: usize,
+    target: usize,
+    weight: Option<f64>,
+}
+
+#[derive(Debug, Serialize)]
+struct ReportGraph {
+    nodes: Vec<ReportNode>,
+    edges: Vec<ReportEdge>,
+}
+
+#[derive(Debug, Serialize)]
+struct ReportData<'a> {
+    crate_name: &'a str,
+    features_graph: ReportGraph,
+    artifacts_tree: ReportGraph,
+    centrality: &'a [FeatureCentrality],
+}
+
+/// Self-contained (offline) HTML report of the squashed features graph, the artifacts tree
+/// and the centrality of the features
+pub fn html_report<Key: ArtifactKey>(
+    crate_name: &str,
+    features_graph: &FeaturesGraph,
+    artifacts_tree: &ArtifactsTree<Key>,
+    centrality: &[FeatureCentrality],
+) -> String {
+    let dummy = FeatureIndex::new(GLOBAL_DUMMY_INDEX);
+
+    let features_nodes = features_graph
+        .graph
+        .node_indices()
+        .filter(|index| *index != dummy)
+        .map(|index| {
+            let feature_node = &features_graph.graph[index];
+            let feature = &feature_node.feature.0;
+            let mut cfgs = feature_node
+                .complex_feature
+                .iter()
+                .map(|complex_feature| complex_feature.to_string())
+                .collect::<Vec<_>>();
+            cfgs.sort();
+            ReportNode {
+                id: index.index(),
+                label: feature.to_string(),
+                cfgs,
+                artifacts: artifacts_tree
+                    .graph
+                    .node_indices()
+                    .filter(|i| artifacts_tree.graph[*i].complex_feature.contains(feature))
+                    .map(|i| i.index())
+                    .collect(),
+                weight: feature_node
+                    .weight
+                    .map_or("-".to_string(), |weight| format!("{:.2}", weight)),
+                span: None,
+            }
+        })
+        .collect();
+    let features_edges = features_graph
+        .graph
+        .edge_references()
+        .filter(|edge| edge.source() != dummy && edge.target() != dummy)
+        .map(|edge| ReportEdge {
+            source: edge.source().index(),
+            target: edge.target().index(),
+            weight: Some(edge.weight().weight),
+        })
+        .collect();
+
+    let artifacts_nodes = artifacts_tree
+        .graph
+        .node_indices()
+        .map(|index| {
+            let artifact_node = &artifacts_tree.graph[index];
+            ReportNode {
+                id: index.index(),
+                label: artifact_node.ident.clone().unwrap_or("-".to_string()),
+                cfgs: vec![artifact_node.complex_feature.to_string()],
+                artifacts: vec![index.index()],
+                weight: artifact_node.weight.to_string(),
+                span: artifact_node.span.clone(),
+            }
+        })
+        .collect();
+    let artifacts_edges = artifacts_tree
+        .graph
+        .edge_references()
+        .map(|edge| ReportEdge {
+            source: edge.source().index(),
+            target: edge.target().index(),
+            weight: None,
+        })
+        .collect();
+
+    let data = ReportData {
+        crate_name,
+        features_graph: ReportGraph {
+            nodes: features_nodes,
+            edges: features_edges,
+        },
+        artifacts_tree: ReportGraph {
+            nodes: artifacts_nodes,
+            edges: artifacts_edges,
+        },
+        centrality,
+    };
+    // `</` would close the script tag embedding the data
+    let data = serde_json::to_string(&data)
+        .expect("Error: cannot serialize data")
+        .replace("</", "<\\/");
+
+    TEMPLATE
+        .replace("__RUSTY_EX_TITLE__", &xml_escape(crate_name))
+        .replace(DATA_PLACEHOLDER, &data)
+}
@@ -0,0 +1,252 @@
+use rustc_ast::visit::{walk_item, Visitor};
+use rustc_ast::{ast::*, NodeId};
+use rustc_data_structures::unord::UnordMap;
+use rustc_hir::def::{DefKind, PartialRes, Res};
+use rustc_hir::def_id::{DefId, LocalDefId, LOCAL_CRATE};
+use rustc_hir::intravisit;
+use rustc_middle::ty::{Instance, ResolverAstLowering, TyCtxt, TypeckResults, TypingEnv};
+use rustc_span::source_map::SourceMap;
+use rustc_span::symbol::Ident;
+use std::collections::HashMap;
+
+/// Path of a local definition, e.g., `my_crate::foo::{impl#0}::new`.
+/// It is the same across compilations of the crate, so it can be used to link definitions
+/// found in different compilations (e.g., with and without the actual `cfg`s)
+pub fn def_path_string(tcx: TyCtxt<'_>, def_id: DefId) -> String {
+    let path = tcx.def_path(def_id).to_string_no_crate_verbose();
+    format!("{}{}", tcx.crate_name(LOCAL_CRATE), path)
+}
+
+/// Target of each method call, by position of the call (see `method_call_key`)
+pub type MethodTargets = HashMap<(String, usize, String), String>;
+
+/// Position of a method call: file, line and name of the method. The file loader of the
+/// analysis preserves the lines, so the position links the calls found in different compilations
+fn method_call_key(source_map: &SourceMap, method: Ident) -> (String, usize, String) {
+    let loc = source_map.lookup_char_pos(method.span.lo());
+    (
+        loc.file.name.prefer_local().to_string(),
+        loc.line,
+        method.to_string(),
+    )
+}
+
+/// Callbacks of the compilation used to resolve the method calls.
+///
+/// Methods are resolved during type checking, so the compilation uses the actual `cfg`s: only
+/// the method calls of the current configuration are resolved
+#[derive(Default)]
+struct MethodCallbacks {
+    /// Target of each method call, `None` if the analysis is not reached
+    targets: Option<MethodTargets>,
+}
+
+impl rustc_driver::Callbacks for MethodCallbacks {
+    /// Called before creating the compiler instance
+    fn config(&mut self, config: &mut rustc_interface::Config) {
+        // Errors and warnings are already reported by the compilation capturing the `cfg`s
+        config.psess_created = Some(Box::new(|sess| {
+            sess.dcx().make_silent(None, false);
+        }));
+    }
+
+    /// Called after analysis, when the type checking results of the crate are available
+    fn after_analysis(
+        &mut self,
+        _compiler: &rustc_interface::interface::Compiler,
+        tcx: TyCtxt<'_>,
+    ) -> rustc_driver::Compilation {
+        let mut targets = HashMap::new();
+        for owner in tcx.hir_body_owners() {
+            let root = tcx.typeck_root_def_id(owner.to_def_id()).expect_local();
+            let mut collector = MethodCallsCollector {
+                tcx,
+                owner,
+                typeck: tcx.typeck(root),
+                targets: &mut targets,
+            };
+            intravisit::Visitor::visit_body(&mut collector, tcx.hir_body_owned_by(owner));
+        }
+        self.targets = Some(
+            targets
+                .into_iter()
+                .filter_map(|(key, target)| Some((key, target?)))
+                .collect(),
+        );
+
+        rustc_driver::Compilation::Stop
+    }
+}
+
+/// Compile the crate with the actual `cfg`s up to the analysis and resolve the method calls to
+/// local functions. Calls that cannot be resolved (e.g., the crate does not compile) are missing
+pub fn method_targets(compiler_args: &[String]) -> MethodTargets {
+    let mut callbacks = MethodCallbacks::default();
+    rustc_driver::catch_fatal_errors(|| rustc_driver::run_compiler(compiler_args, &mut callbacks))
+        .ok()
+        .and(callbacks.targets)
+        .unwrap_or_default()
+}
+
+/// Visitor collecting the targets of the method calls of a body
+struct MethodCallsCollector<'a, 'tcx> {
+    tcx: TyCtxt<'tcx>,
+    owner: LocalDefId,
+    typeck: &'tcx TypeckResults<'tcx>,
+    /// Target of each method call, `None` if it is not a local function or if calls on the same
+    /// line with the same name target different functions
+    targets: &'a mut HashMap<(String, usize, String), Option<String>>,
+}
+
+impl<'tcx> intravisit::Visitor<'tcx> for MethodCallsCollector<'_, 'tcx> {
+    fn visit_expr(&mut self, expr: &'tcx rustc_hir::Expr<'tcx>) {
+        if let rustc_hir::ExprKind::MethodCall(segment, ..) = expr.kind {
+            // trait methods are resolved to the implementation, if it is known
+            let target = self
+                .typeck
+                .type_dependent_def_id(expr.hir_id)
+                .map(|def_id| {
+                    let typing_env = TypingEnv::post_analysis(self.tcx, self.owner);
+                    let args = self.typeck.node_args(expr.hir_id);
+                    match Instance::try_resolve(self.tcx, typing_env, def_id, args) {
+                        Ok(Some(instance)) => instance.def_id(),
+                        _ => def_id,
+                    }
+                })
+                .filter(|def_id| def_id.is_local())
+                .map(|def_id| def_path_string(self.tcx, def_id));
+            let key = method_call_key(self.tcx.sess.source_map(), segment.ident);
+            self.targets
+                .entry(key)
+                .and_modify(|found| {
+                    if *found != target {
+                        *found = None;
+                    }
+                })
+                .or_insert(target);
+        }
+        intravisit::walk_expr(self, expr);
+    }
+}
+
+/// Results of the name resolution of the crate, used to link calls to the exact item they target.
+///
+/// The resolver only resolves paths, so method calls are resolved by a type checked compilation
+/// of the current configuration (see `method_targets`). Calls to items outside the crate and
+/// method calls of other configurations are not linked: they fall back to the weight of the
+/// items with the same name
+pub struct Resolutions {
+    /// Path of each local definition, e.g., `my_crate::foo::{impl#0}::new`
+    paths: UnordMap<DefId, String>,
+    /// Definition of each AST node that defines something
+    definitions: UnordMap<NodeId, DefId>,
+    /// Resolution of each AST path
+    partial_res: UnordMap<NodeId, PartialRes>,
+    /// Methods of the inherent impls of local types, by type and method name
+    inherent_methods: HashMap<(DefId, String), DefId>,
+    /// Path of the local function called by each method call (see `method_targets`)
+    method_targets: MethodTargets,
+}
+
+impl Resolutions {
+    /// Collect the resolutions of the crate, after the expansion
+    pub fn new(
+        tcx: TyCtxt<'_>,
+        resolver: &ResolverAstLowering,
+        krate: &Crate,
+        method_targets: MethodTargets,
+    ) -> Self {
+        let definitions: UnordMap<NodeId, DefId> = resolver
+            .node_id_to_def_id
+            .items()
+            .map(|(node_id, def_id)| (*node_id, def_id.to_def_id()))
+            .collect();
+        let paths = definitions
+            .items()
+            .map(|(_, def_id)| (*def_id, def_path_string(tcx, *def_id)))
+            .collect();
+        let partial_res = resolver.partial_res_map.clone();
+
+        let mut collector = InherentMethodsCollector {
+            definitions: &definitions,
+            partial_res: &partial_res,
+            methods: HashMap::new(),
+        };
+        collector.visit_crate(krate);
+        let inherent_methods = collector.methods;
+
+        Resolutions {
+            paths,
+            definitions,
+            partial_res,
+            inherent_methods,
+            method_targets,
+        }
+    }
+
+    /// Path of the item defined by the AST node, if any
+    pub fn definition(&self, node_id: NodeId) -> Option<String> {
+        self.definitions
+            .get(&node_id)
+            .and_then(|def_id| self.paths.get(def_id))
+            .cloned()
+    }
+
+    /// Path of the local function (or associated function) called through the AST path expression.
+    /// Type-relative paths (e.g., `Foo::new`) are resolved looking at the inherent impls of local types
+    pub fn call_target(&self, node_id: NodeId, path: &Path) -> Option<String> {
+        let partial_res = self.partial_res.get(&node_id)?;
+        let def_id = match (partial_res.base_res(), partial_res.unresolved_segments()) {
+            (Res::Def(DefKind::Fn | DefKind::AssocFn, def_id), 0) => def_id,
+            (Res::Def(DefKind::Struct | DefKind::Enum | DefKind::Union, ty), 1)
+            | (Res::SelfTyAlias { alias_to: ty, .. }, 1) => {
+                let method = path.segments.last()?.ident.to_string();
+                *self.inherent_methods.get(&(ty, method))?
+            }
+            _ => return None,
+        };
+        self.paths.get(&def_id).cloned()
+    }
+
+    /// Path of the local function called through the method, if the call is resolved
+    pub fn method_target(&self, source_map: &SourceMap, method: Ident) -> Option<String> {
+        self.method_targets
+            .get(&method_call_key(source_map, method))
+            .cloned()
+    }
+}
+
+/// Visitor collecting the functions of the inherent impls (`impl Foo { .. }`) of local types
+struct InherentMethodsCollector<'a> {
+    definitions: &'a UnordMap<NodeId, DefId>,
+    partial_res: &'a UnordMap<NodeId, PartialRes>,
+    methods: HashMap<(DefId, String), DefId>,
+}
+
+impl<'ast> Visitor<'ast> for InherentMethodsCollector<'_> {
+    fn visit_item(&mut self, item: &'ast Item) {
+        if let ItemKind::Impl(impl_) = &item.kind {
+            let self_ty = match self
+                .partial_res
+                .get(&impl_.self_ty.id)
+                .and_then(|res| res.full_res())
+            {
+                Some(Res::Def(_, def_id)) if impl_.of_trait.is_none() && def_id.is_local() => {
+                    Some(def_id)
+                }
+                _ => None,
+            };
+            if let Some(self_ty) = self_ty {
+                for impl_item in &impl_.items {
+                    if let (AssocItemKind::Fn(..), Some(def_id)) =
+                        (&impl_item.kind, self.definitions.get(&impl_item.id))
+                    {
+                        self.methods
+                            .insert((self_ty, impl_item.ident.to_string()), *def_id);
+                    }
+                }
+            }
+        }
+        walk_item(self, item);
+    }
+}
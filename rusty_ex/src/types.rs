@@ -1,16 +1,21 @@
 use rustc_ast::NodeId;
+use rustc_span::source_map::SourceMap;
+use rustc_span::Span;
 use rustworkx_core::petgraph::dot::{Config, Dot};
 use rustworkx_core::petgraph::graph::{DiGraph, NodeIndex};
-use rustworkx_core::petgraph::visit::IntoNodeReferences;
+use rustworkx_core::petgraph::visit::{EdgeRef, IntoNodeReferences};
+use rustworkx_core::petgraph::Direction;
 use serde::{Deserialize, Serialize};
 use std::clone::Clone;
 use std::cmp::Eq;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::fmt::{Debug, Display, Formatter};
 use std::hash::Hash;
 use std::panic;
+use std::str::FromStr;
 
 use crate::configs::prop_formula::{ConversionMethod, PropFormula, ToPropFormula};
+use crate::errors::RustyExError;
 use crate::GLOBAL_NODE_INDEX;
 
 // Terminology:
@@ -21,7 +26,7 @@ use crate::GLOBAL_NODE_INDEX;
 // -------------------- Features --------------------
 
 /// Simple feature
-#[derive(Debug, Clone, Eq, Hash, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Eq, Hash, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct Feature {
     pub name: String,
     pub not: bool,
@@ -33,6 +38,64 @@ impl Feature {
     }
 }
 
+impl Display for Feature {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.not {
+            true => write!(f, "!{}", self.name),
+            false => write!(f, "{}", self.name),
+        }
+    }
+}
+
+impl FromStr for Feature {
+    type Err = String;
+
+    /// Parse a feature name, negated if it starts with `!`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.strip_prefix('!').unwrap_or(s);
+        if name.is_empty() {
+            return Err("empty feature name".to_string());
+        }
+        Ok(Feature {
+            name: name.to_string(),
+            not: name.len() != s.len(),
+        })
+    }
+}
+
+/// Kind of `cfg` option a feature comes from
+#[derive(Debug, Clone, Eq, Hash, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum CfgKind {
+    /// Cargo feature, e.g., `#[cfg(feature = "a")]`
+    Feature,
+    /// Name option, e.g., `#[cfg(unix)]`
+    Name,
+    /// Name-value option other than features, e.g., `#[cfg(target_os = "linux")]` (only the name is kept)
+    NameValue,
+}
+
+impl CfgKind {
+    /// Save the kind of the option `name`. A name used by options of different kinds (e.g.,
+    /// `feature = "unix"` and `unix`) keeps the first kind in declaration order, whatever the
+    /// order of the options in the code
+    pub fn record(cfg_kinds: &mut HashMap<String, CfgKind>, name: &str, kind: CfgKind) {
+        cfg_kinds
+            .entry(name.to_string())
+            .and_modify(|recorded| *recorded = recorded.clone().min(kind.clone()))
+            .or_insert(kind);
+    }
+}
+
+impl Display for CfgKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CfgKind::Feature => write!(f, "feature"),
+            CfgKind::Name => write!(f, "name"),
+            CfgKind::NameValue => write!(f, "name-value"),
+        }
+    }
+}
+
 /// Complex feature: none, a single feature (with not included), all features, or any feature
 #[derive(Debug, Clone, Eq, Hash, PartialEq, Serialize, Deserialize)]
 pub enum ComplexFeature<T> {
@@ -42,7 +105,51 @@ pub enum ComplexFeature<T> {
     Any(Vec<ComplexFeature<T>>),
 }
 
+impl<T: PartialEq> ComplexFeature<T> {
+    /// Whether the feature appears in the complex feature
+    pub fn contains(&self, feature: &T) -> bool {
+        match self {
+            ComplexFeature::None => false,
+            ComplexFeature::Simple(simple) => simple == feature,
+            ComplexFeature::All(features) | ComplexFeature::Any(features) => {
+                features.iter().any(|f| f.contains(feature))
+            }
+        }
+    }
+}
+
 impl ComplexFeature<Feature> {
+    /// Whether the complex feature is satisfied when only the given features (or other `cfg`
+    /// options) are enabled
+    pub fn is_enabled(&self, enabled: &BTreeSet<String>) -> bool {
+        match self {
+            ComplexFeature::None => true,
+            ComplexFeature::Simple(feature) => enabled.contains(&feature.name) != feature.not,
+            ComplexFeature::All(features) => features.iter().all(|f| f.is_enabled(enabled)),
+            ComplexFeature::Any(features) => features.iter().any(|f| f.is_enabled(enabled)),
+        }
+    }
+
+    /// Propositional formula of the complex feature, variables are the names of the features
+    pub fn to_prop_formula(&self) -> PropFormula<String> {
+        match self {
+            ComplexFeature::None => PropFormula::None,
+            ComplexFeature::Simple(feature) => {
+                if feature.is_negated() {
+                    PropFormula::Not(Box::new(PropFormula::Var(feature.name.to_string())))
+                } else {
+                    PropFormula::Var(feature.name.to_string())
+                }
+            }
+            ComplexFeature::All(features) => {
+                PropFormula::And(features.iter().map(|f| f.to_prop_formula()).collect())
+            }
+            ComplexFeature::Any(features) => {
+                PropFormula::Or(features.iter().map(|f| f.to_prop_formula()).collect())
+            }
+        }
+    }
+
     fn to_feature_index(&self, fgraph: &FeaturesGraph) -> ComplexFeature<FeatureIndex> {
         match self {
             ComplexFeature::None => ComplexFeature::None,
@@ -93,6 +200,21 @@ pub enum TermWeight {
     Wait(String),
 }
 
+// -------------------- Source --------------------
+
+/// Location of a term in the source code of the analysed crate.
+///
+/// The compiler `Span` is only meaningful inside the compiler session, so it is resolved
+/// to file, lines and columns (1-based) as soon as the term is visited
+#[derive(Debug, Clone, Eq, Hash, PartialEq, Serialize, Deserialize)]
+pub struct SourceSpan {
+    pub file: String,
+    pub lo_line: usize,
+    pub lo_col: usize,
+    pub hi_line: usize,
+    pub hi_col: usize,
+}
+
 // -------------------- Graphs common --------------------
 
 /// Edge between nodes, has a weight
@@ -127,9 +249,24 @@ impl TermKey for SuperTermKey {}
 pub struct TermNode<Key: TermKey> {
     pub node_id: Key,
     pub ident: Option<String>,
+    #[serde(default)]
+    pub span: Option<SourceSpan>,
     pub features: ComplexFeature<Feature>,
     pub weight_kind: TermWeightKind,
     pub weight: TermWeight,
+    /// Resolved path of the item defined by the term (only with name resolution)
+    #[serde(default)]
+    pub def_path: Option<String>,
+    /// Resolved path of the item referenced by the term (only with name resolution)
+    #[serde(default)]
+    pub target: Option<String>,
+    /// The term references an item that cannot be resolved (e.g., a function of another
+    /// crate or a recursive call), so the item weighs as the recovery weight
+    #[serde(default)]
+    pub recovered: bool,
+    /// Part of the weight that comes from recovered terms, so it is estimated and not measured
+    #[serde(default)]
+    pub estimated_weight: f64,
 }
 
 /// Index of a term node in the graph representing the Terms Tree (UIR).
@@ -213,9 +350,14 @@ impl ArtifactKey for SuperArtifactKey {}
 pub struct ArtifactNode<Key: ArtifactKey> {
     pub artifact: Key,
     pub ident: Option<String>,
+    #[serde(default)]
+    pub span: Option<SourceSpan>,
     /// Feature that annotate the Term, making it an Artifact
     pub complex_feature: ComplexFeature<Feature>,
     pub weight: TermWeight,
+    /// Part of the weight that comes from recovered terms, so it is estimated and not measured
+    #[serde(default)]
+    pub estimated_weight: f64,
 }
 
 /// Index of a artifact node in the graph representing the Artifacts Dependency Tree.
@@ -269,6 +411,27 @@ impl Display for SuperArtifactKey {
     }
 }
 
+impl SourceSpan {
+    /// Resolve a compiler `Span` using the source map of the current compiler session
+    pub fn from_span(span: Span, source_map: &SourceMap) -> Self {
+        let lo = source_map.lookup_char_pos(span.lo());
+        let hi = source_map.lookup_char_pos(span.hi());
+        SourceSpan {
+            file: lo.file.name.prefer_local().to_string(),
+            lo_line: lo.line,
+            lo_col: lo.col.0 + 1,
+            hi_line: hi.line,
+            hi_col: hi.col.0 + 1,
+        }
+    }
+}
+
+impl Display for SourceSpan {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}:{}", self.file, self.lo_line, self.lo_col)
+    }
+}
+
 impl Feature {
     pub fn is_negated(&self) -> bool {
         self.not
@@ -290,6 +453,7 @@ impl<Key: TermKey> TermsTree<Key> {
         &mut self,
         node_id: Key,
         ident: Option<String>,
+        span: Option<SourceSpan>,
         features: ComplexFeature<Feature>,
         weight_kind: TermWeightKind,
         weight: TermWeight,
@@ -301,9 +465,14 @@ impl<Key: TermKey> TermsTree<Key> {
         let index = self.graph.add_node(TermNode {
             node_id: node_id.clone(),
             ident,
+            span,
             features,
             weight_kind,
             weight,
+            def_path: None,
+            target: None,
+            recovered: false,
+            estimated_weight: 0.0,
         });
         self.nodes.insert(node_id, index);
 
@@ -312,22 +481,32 @@ impl<Key: TermKey> TermsTree<Key> {
 
     /// Print Terms Tree (UIR) in DOT format
     pub fn print_dot(&self) {
+        println!("{}", self.to_dot());
+    }
+
+    /// Terms Tree (UIR) in DOT format
+    pub fn to_dot(&self) -> String {
         let get_node_attr = |_g: &DiGraph<TermNode<Key>, Edge>,
                              node: (NodeIndex, &TermNode<Key>)| {
             let index = node.0.index();
             let term_node = node.1;
             format!(
-                "label=\"i{}: node{} ({}) '{}' #[{}] {}\"",
+                "label=\"i{}: node{} ({}) '{}' #[{}] {}{}\"",
                 index,
                 term_node.node_id,
                 term_node.weight_kind,
                 term_node.ident.clone().unwrap_or(" ".to_string()),
                 term_node.features,
                 term_node.weight,
+                if term_node.recovered {
+                    " recovered"
+                } else {
+                    ""
+                },
             )
         };
 
-        println!(
+        format!(
             "{:?}",
             Dot::with_attr_getters(
                 &self.graph,
@@ -382,39 +561,10 @@ impl FeaturesGraph {
     /// the complex features of each node.
     /// The formula is a conjunction of all the complex features of all nodes.
     fn to_prop_formula_naive(&self) -> PropFormula<String> {
-        fn resolve_complex_feature_rec(
-            complex_feature: &ComplexFeature<Feature>,
-        ) -> PropFormula<String> {
-            match complex_feature {
-                ComplexFeature::None => PropFormula::None,
-                ComplexFeature::Simple(feature) => {
-                    if feature.is_negated() {
-                        PropFormula::Not(Box::new(PropFormula::Var(feature.name.to_string())))
-                    } else {
-                        PropFormula::Var(feature.name.to_string())
-                    }
-                }
-                ComplexFeature::All(features) => {
-                    let mut formula = Vec::new();
-                    for feature in features {
-                        formula.push(resolve_complex_feature_rec(feature));
-                    }
-                    PropFormula::And(formula)
-                }
-                ComplexFeature::Any(features) => {
-                    let mut formula = Vec::new();
-                    for feature in features {
-                        formula.push(resolve_complex_feature_rec(feature));
-                    }
-                    PropFormula::Or(formula)
-                }
-            }
-        }
-
         let mut formula = Vec::new();
         for (_, feature_node) in self.graph.node_references() {
             for complex_feature in &feature_node.complex_feature {
-                formula.push(resolve_complex_feature_rec(complex_feature));
+                formula.push(complex_feature.to_prop_formula());
             }
         }
 
@@ -423,6 +573,11 @@ impl FeaturesGraph {
 
     /// Print features graph in DOT format
     pub fn print_dot(&self) {
+        println!("{}", self.to_dot());
+    }
+
+    /// Features graph in DOT format
+    pub fn to_dot(&self) -> String {
         let get_node_attr = |_g: &DiGraph<FeatureNode, Edge>, node: (NodeIndex, &FeatureNode)| {
             let index = node.0.index();
             let feature = node.1;
@@ -454,7 +609,7 @@ impl FeaturesGraph {
             }
         };
 
-        println!(
+        format!(
             "{:?}",
             Dot::with_attr_getters(
                 &self.graph,
@@ -489,12 +644,68 @@ impl<Key: ArtifactKey> ArtifactsTree<Key> {
         }
     }
 
+    /// Feature model inferred from the nesting of the code: the `cfg` of each artifact implies the
+    /// `cfg` of the artifact enclosing it, as the nested code is compiled only with it (e.g.,
+    /// `b -> a` for `#[cfg(feature = "b")]` code in `#[cfg(feature = "a")]` code). Top-level
+    /// artifacts do not constrain the features
+    pub fn nesting_formula(&self) -> PropFormula<String> {
+        let global = ArtifactIndex::new(GLOBAL_NODE_INDEX);
+        // edges go from the enclosed artifact to the enclosing one
+        PropFormula::And(
+            self.graph
+                .edge_references()
+                .filter(|edge| edge.target() != global)
+                .map(|edge| {
+                    PropFormula::Implies(
+                        Box::new(self.graph[edge.source()].complex_feature.to_prop_formula()),
+                        Box::new(self.graph[edge.target()].complex_feature.to_prop_formula()),
+                    )
+                })
+                .collect(),
+        )
+    }
+
+    /// The artifact and its ancestors, from the outermost one (the global artifact excluded).
+    /// The artifact is compiled only if all of them are
+    pub fn ancestors_chain(&self, index: ArtifactIndex) -> Vec<ArtifactIndex> {
+        let global = ArtifactIndex::new(GLOBAL_NODE_INDEX);
+        // edges go from the enclosed artifact to the enclosing one
+        let mut chain = vec![index];
+        while let Some(parent) = self
+            .graph
+            .neighbors_directed(*chain.last().unwrap(), Direction::Outgoing)
+            .next()
+            .filter(|parent| *parent != global)
+        {
+            chain.push(parent);
+        }
+        chain.reverse();
+        chain
+    }
+
+    /// Weight of the artifact without the artifacts it directly contains (their weights are part
+    /// of its weight), so each piece of code is counted once. Unresolved weights are 0
+    pub fn exclusive_weight(&self, index: ArtifactIndex) -> f64 {
+        let weight = |index: ArtifactIndex| match self.graph[index].weight {
+            TermWeight::Weight(weight) => weight,
+            _ => 0.0,
+        };
+        // edges go from the enclosed artifact to the enclosing one
+        let nested = self
+            .graph
+            .neighbors_directed(index, Direction::Incoming)
+            .map(weight)
+            .sum::<f64>();
+        (weight(index) - nested).max(0.0)
+    }
+
     /// Create an artifact node in the artifacts tree and add it to the artifacts nodes hashmap.
     /// Return the index of the created node
     pub fn create_node(
         &mut self,
         artifact: Key,
         ident: Option<String>,
+        span: Option<SourceSpan>,
         complex_feature: ComplexFeature<Feature>,
         weight: TermWeight,
     ) -> ArtifactIndex {
@@ -505,8 +716,10 @@ impl<Key: ArtifactKey> ArtifactsTree<Key> {
         let index = self.graph.add_node(ArtifactNode {
             artifact: artifact.clone(),
             ident,
+            span,
             complex_feature,
             weight,
+            estimated_weight: 0.0,
         });
         self.nodes.insert(artifact, index);
 
@@ -515,38 +728,13 @@ impl<Key: ArtifactKey> ArtifactsTree<Key> {
 
     /// NOTE: The `FeatureGraph` is expected to be the same as the one used to create the `ArtifactTree`
     /// NOTE: The `ArtifactTree` does not have the `dummy` node.
+    ///
+    /// Artifacts with an unresolved weight are ignored, an error is returned for each of them.
     pub fn refiner_hash_map(
         &self,
         fgraph: &FeaturesGraph,
         remove_global: bool,
-    ) -> HashMap<FeatureIndex, f64> {
-        fn refined_hash_map_rec(
-            complex_index: ComplexFeature<FeatureIndex>,
-            weight: f64,
-            hm: &mut HashMap<FeatureIndex, f64>,
-        ) {
-            match complex_index {
-                ComplexFeature::None => panic!("ComplexFeature::None not expected"),
-                ComplexFeature::Simple(index) => {
-                    // Add the weight to the feature index
-                    *hm.entry(index).or_insert(0.0) += weight;
-                }
-                ComplexFeature::All(complex_indexes) => {
-                    // In `All` case, the weight is divided by the number of features
-                    // because all features must be satisfied.
-                    let new_weight = weight / complex_indexes.len() as f64;
-                    for complex_index in complex_indexes {
-                        refined_hash_map_rec(complex_index, new_weight, hm);
-                    }
-                }
-                ComplexFeature::Any(complex_indexes) => {
-                    for complex_index in complex_indexes {
-                        refined_hash_map_rec(complex_index, weight, hm);
-                    }
-                }
-            }
-        }
-
+    ) -> (HashMap<FeatureIndex, f64>, Vec<RustyExError>) {
         fn normalize_max(hm: &mut HashMap<FeatureIndex, f64>) {
             let max = hm.values().cloned().fold(0.0, f64::max);
             if max == 0.0 {
@@ -558,6 +746,7 @@ impl<Key: ArtifactKey> ArtifactsTree<Key> {
         }
 
         let mut refiner_hm = HashMap::new();
+        let mut errors = Vec::new();
         for (artifact_index, artifact_node) in self.graph.node_references() {
             if remove_global && artifact_index == ArtifactIndex::new(GLOBAL_NODE_INDEX) {
                 continue;
@@ -567,16 +756,63 @@ impl<Key: ArtifactKey> ArtifactsTree<Key> {
             let weight = if let TermWeight::Weight(w) = artifact_node.weight {
                 w
             } else {
-                panic!("Weight not expected to be a Wait")
+                errors.push(RustyExError::UnresolvedWeight {
+                    ident: artifact_node.ident.clone(),
+                    span: artifact_node.span.clone(),
+                });
+                continue;
             };
-            refined_hash_map_rec(complex_index, weight, &mut refiner_hm);
+            distribute_weight(complex_index, weight, &mut refiner_hm);
         }
         normalize_max(&mut refiner_hm);
-        refiner_hm
+        (refiner_hm, errors)
+    }
+
+    /// NOTE: The `FeatureGraph` is expected to be the same as the one used to create the `ArtifactTree`
+    ///
+    /// Weight of each feature (as in `refiner_hash_map`, but not normalized) and the part of it
+    /// that is estimated, because it comes from recovered terms.
+    /// Artifacts with an unresolved weight are ignored
+    pub fn estimated_weights(
+        &self,
+        fgraph: &FeaturesGraph,
+        remove_global: bool,
+    ) -> HashMap<FeatureIndex, (f64, f64)> {
+        let mut weights = HashMap::new();
+        let mut estimated = HashMap::new();
+        for (artifact_index, artifact_node) in self.graph.node_references() {
+            if remove_global && artifact_index == ArtifactIndex::new(GLOBAL_NODE_INDEX) {
+                continue;
+            }
+            if let TermWeight::Weight(weight) = artifact_node.weight {
+                let complex_index = artifact_node.complex_feature.to_feature_index(fgraph);
+                distribute_weight(complex_index.clone(), weight, &mut weights);
+                distribute_weight(
+                    complex_index,
+                    artifact_node.estimated_weight,
+                    &mut estimated,
+                );
+            }
+        }
+
+        weights
+            .into_iter()
+            .map(|(index, weight)| {
+                (
+                    index,
+                    (weight, estimated.get(&index).cloned().unwrap_or(0.0)),
+                )
+            })
+            .collect()
     }
 
     /// Print artifacts tree in DOT format
     pub fn print_dot(&self) {
+        println!("{}", self.to_dot());
+    }
+
+    /// Artifacts tree in DOT format
+    pub fn to_dot(&self) -> String {
         let get_node_attr = |_g: &DiGraph<ArtifactNode<Key>, Edge>,
                              node: (NodeIndex, &ArtifactNode<Key>)| {
             let index = node.0.index();
@@ -591,7 +827,7 @@ impl<Key: ArtifactKey> ArtifactsTree<Key> {
             )
         };
 
-        println!(
+        format!(
             "{:?}",
             Dot::with_attr_getters(
                 &self.graph,
@@ -603,6 +839,35 @@ impl<Key: ArtifactKey> ArtifactsTree<Key> {
     }
 }
 
+/// Distribute the weight of an artifact to the features annotating it
+fn distribute_weight(
+    complex_index: ComplexFeature<FeatureIndex>,
+    weight: f64,
+    hm: &mut HashMap<FeatureIndex, f64>,
+) {
+    match complex_index {
+        // Artifacts always have features, nothing to refine
+        ComplexFeature::None => {}
+        ComplexFeature::Simple(index) => {
+            // Add the weight to the feature index
+            *hm.entry(index).or_insert(0.0) += weight;
+        }
+        ComplexFeature::All(complex_indexes) => {
+            // In `All` case, the weight is divided by the number of features
+            // because all features must be satisfied.
+            let new_weight = weight / complex_indexes.len() as f64;
+            for complex_index in complex_indexes {
+                distribute_weight(complex_index, new_weight, hm);
+            }
+        }
+        ComplexFeature::Any(complex_indexes) => {
+            for complex_index in complex_indexes {
+                distribute_weight(complex_index, weight, hm);
+            }
+        }
+    }
+}
+
 impl<Key: ArtifactKey> Default for ArtifactsTree<Key> {
     fn default() -> Self {
         ArtifactsTree::new()
@@ -660,6 +925,16 @@ impl TermWeightKind {
     pub fn parse_kind_variant_name(s: String) -> String {
         s.split(['(', '{']).next().unwrap_or("").trim().to_string()
     }
+
+    /// Name of the kind of term, e.g., `Closure` or `MethodCall`
+    pub fn kind_name(&self) -> &str {
+        match self {
+            TermWeightKind::Intrinsic(name)
+            | TermWeightKind::Children(name)
+            | TermWeightKind::Reference(name, _)
+            | TermWeightKind::No(name) => name,
+        }
+    }
 }
 
 impl Display for TermWeightKind {
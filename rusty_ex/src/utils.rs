@@ -5,3 +5,172 @@ macro_rules! bx {
 }
 
 pub(crate) use bx;
+
+/// Undo the rewriting of the file loader (`cfg` attributes and macros replaced with `rustex_cfg`)
+pub(crate) fn restore_cfg(source: &str) -> String {
+    source
+        .replace("#[rustex_cfg(", "#[cfg(")
+        .replace("rustex_cfg", "cfg!")
+}
+
+/// Function replacing the `compile_error!` macros guarded by a `cfg` (see `rewrite_compile_error`)
+pub(crate) const COMPILE_ERROR_GUARD: &str = "rustex_compile_error";
+
+/// Replace the `compile_error!` macros guarded by a `cfg` attribute (already rewritten to
+/// `rustex_cfg`) with a constant calling `rustex_compile_error`, e.g.,
+/// `#[rustex_cfg(a)] compile_error!("a");` becomes
+/// `#[rustex_cfg(a)] const _: () = rustex_compile_error("a");`. The macro aborts the expansion
+/// of the item, so it would be lost before the `after_expansion` hook. The `compile_error!`
+/// macros without a `cfg` and the ones in the body of a `macro_rules!` are left as they are.
+/// The number of lines is preserved
+pub(crate) fn rewrite_compile_error(source: &str) -> String {
+    const ATTRIBUTE: &str = "#[rustex_cfg(";
+    const MACRO: &str = "compile_error!";
+
+    // the bodies are expanded where the macros are used, with other `cfg`s
+    let macro_bodies = macro_rules_bodies(source);
+
+    let mut rewritten = String::with_capacity(source.len());
+    let mut copied = 0;
+    for (start, _) in source.match_indices(ATTRIBUTE) {
+        if start < copied || macro_bodies.iter().any(|body| body.contains(&start)) {
+            continue;
+        }
+        let Some(attribute_end) = group_end(source, start + 1) else {
+            continue;
+        };
+        let Some(call) = source[attribute_end..].trim_start().strip_prefix(MACRO) else {
+            continue;
+        };
+        let Some((arguments, len)) = macro_arguments(call) else {
+            continue;
+        };
+        let call_start = source.len() - call.len() - MACRO.len();
+        rewritten.push_str(&source[copied..call_start]);
+        rewritten.push_str(&format!(
+            "const _: () = {}({});",
+            COMPILE_ERROR_GUARD, arguments
+        ));
+        let newlines = call[..len].matches('\n').count() - arguments.matches('\n').count();
+        rewritten.push_str(&"\n".repeat(newlines));
+        copied = call_start + MACRO.len() + len;
+    }
+    rewritten.push_str(&source[copied..]);
+    rewritten
+}
+
+/// Byte ranges of the bodies of the `macro_rules!` definitions
+fn macro_rules_bodies(source: &str) -> Vec<std::ops::Range<usize>> {
+    const MACRO_RULES: &str = "macro_rules!";
+
+    source
+        .match_indices(MACRO_RULES)
+        .filter_map(|(start, _)| {
+            let open = start + MACRO_RULES.len();
+            let open = open + source[open..].find(['(', '[', '{'])?;
+            Some(open..group_end(source, open)?)
+        })
+        .collect()
+}
+
+/// Arguments of a macro call (the source after the `!`) and the length of the call, including
+/// the trailing `;`. `None` if the delimiters are not balanced
+fn macro_arguments(source: &str) -> Option<(&str, usize)> {
+    let open = source.len() - source.trim_start().len();
+    if !matches!(source[open..].chars().next(), Some('(' | '[' | '{')) {
+        return None;
+    }
+
+    let end = group_end(source, open)?;
+    let arguments = &source[open + 1..end - 1];
+    let len = match source[end..].trim_start().strip_prefix(';') {
+        Some(rest) => source.len() - rest.len(),
+        None => end,
+    };
+    Some((arguments, len))
+}
+
+/// End (past the closing delimiter) of the delimited group opening at `open`, skipping the
+/// delimiters in strings, raw strings, character literals and comments. `None` if the delimiters
+/// are not balanced
+fn group_end(source: &str, open: usize) -> Option<usize> {
+    let bytes = source.as_bytes();
+    let mut depth = 0;
+    let mut i = open;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'(' | b'[' | b'{' => depth += 1,
+            b')' | b']' | b'}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i + 1);
+                }
+            }
+            b'"' => i = string_end(source, i)? - 1,
+            b'\'' => i = char_end(source, i) - 1,
+            b'/' if bytes.get(i + 1) == Some(&b'/') => {
+                i += source[i..].find('\n').unwrap_or(source.len() - i);
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => i += source[i..].find("*/")? + 1,
+            c if c == b'_' || c.is_ascii_alphabetic() => {
+                let ident = source[i..]
+                    .find(|c: char| c != '_' && !c.is_ascii_alphanumeric())
+                    .unwrap_or(source.len() - i);
+                let after = &source[i + ident..];
+                let hashes = after.len() - after.trim_start_matches('#').len();
+                i = match &source[i..i + ident] {
+                    "r" | "br" | "cr" if after[hashes..].starts_with('"') => {
+                        let closing = format!("\"{}", "#".repeat(hashes));
+                        let content = i + ident + hashes + 1;
+                        content + source.get(content..)?.find(&closing)? + closing.len()
+                    }
+                    _ => i + ident,
+                } - 1;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+/// End (past the closing quote) of the string literal starting at `start`
+fn string_end(source: &str, start: usize) -> Option<usize> {
+    let mut escaped = false;
+    for (i, c) in source[start + 1..].char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '"' => return Some(start + 1 + i + 1),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// End (past the closing quote) of the character literal starting at `start`, or past the quote
+/// if it is a lifetime
+fn char_end(source: &str, start: usize) -> usize {
+    let mut chars = source[start + 1..].char_indices();
+    match chars.next() {
+        // the escaped character may be a quote
+        Some((_, '\\')) => source
+            .get(start + 3..)
+            .and_then(|rest| rest.find('\''))
+            .map_or(start + 1, |end| start + 3 + end + 1),
+        Some((_, _)) => match chars.next() {
+            Some((end, '\'')) => start + 1 + end + 1,
+            _ => start + 1,
+        },
+        None => start + 1,
+    }
+}
+
+/// Escape the characters with a meaning in XML (and HTML)
+pub(crate) fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
@@ -0,0 +1,313 @@
+use rustc_lexer::TokenKind;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::str::FromStr;
+
+use crate::errors::RustyExError;
+use crate::types::TermWeightKind;
+
+/// Source of the weights of the artifacts
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub enum WeightMode {
+    /// Weight of the terms of the AST, according to the weight model
+    #[default]
+    Ast,
+    /// Weight of the MIR of the functions (basic blocks, statements and calls), available only
+    /// for the code enabled in the current configuration
+    Mir,
+    /// Physical lines of the source code of the artifacts
+    Lines,
+    /// Lines of the source code of the artifacts that are neither blank nor comments
+    SourceLines,
+    /// Tokens of the source code of the artifacts (comments excluded)
+    Tokens,
+}
+
+impl WeightMode {
+    /// Whether the weights are measured on the source code of the artifacts
+    pub fn is_source_based(&self) -> bool {
+        matches!(
+            self,
+            WeightMode::Lines | WeightMode::SourceLines | WeightMode::Tokens
+        )
+    }
+
+    /// Weight of a piece of source code, `None` if the mode is not based on the source code
+    pub fn source_weight(&self, source: &str) -> Option<f64> {
+        match self {
+            WeightMode::Ast | WeightMode::Mir => None,
+            WeightMode::Lines => Some(source.lines().count() as f64),
+            WeightMode::SourceLines => Some(source_lines(source) as f64),
+            WeightMode::Tokens => Some(source_tokens(source) as f64),
+        }
+    }
+}
+
+/// Whitespaces and comments (doc comments included) do not count as source code
+fn is_trivia(kind: &TokenKind) -> bool {
+    matches!(
+        kind,
+        TokenKind::Whitespace | TokenKind::LineComment { .. } | TokenKind::BlockComment { .. }
+    )
+}
+
+/// Number of lines containing at least a token that is not a whitespace or a comment
+pub fn source_lines(source: &str) -> usize {
+    let mut lines = HashSet::new();
+    let (mut offset, mut line) = (0, 0);
+    for token in rustc_lexer::tokenize(source) {
+        let text = &source[offset..offset + token.len as usize];
+        let newlines = text.matches('\n').count();
+        if !is_trivia(&token.kind) {
+            lines.extend(line..=line + newlines);
+        }
+        offset += token.len as usize;
+        line += newlines;
+    }
+    lines.len()
+}
+
+/// Operators made of more than one character, longest first. The lexer returns a token per
+/// character, the parser glues the adjacent ones (e.g., `+=` is one token, `+ =` are two)
+const GLUED_OPERATORS: [&str; 24] = [
+    "<<=", ">>=", "...", "..=", "::", "->", "=>", "==", "!=", "<=", ">=", "&&", "||", "+=", "-=",
+    "*=", "/=", "%=", "^=", "&=", "|=", "<<", ">>", "..",
+];
+
+/// Number of tokens that are not whitespaces or comments, multi-character operators count
+/// as one token (see `GLUED_OPERATORS`)
+pub fn source_tokens(source: &str) -> usize {
+    // text of each token, `None` for whitespaces and comments
+    let mut offset = 0;
+    let tokens = rustc_lexer::tokenize(source)
+        .map(|token| {
+            let text = &source[offset..offset + token.len as usize];
+            offset += token.len as usize;
+            (!is_trivia(&token.kind)).then_some(text)
+        })
+        .collect::<Vec<_>>();
+
+    let mut count = 0;
+    let mut index = 0;
+    while index < tokens.len() {
+        if tokens[index].is_some() {
+            count += 1;
+            index += glued_len(&tokens[index..]);
+        } else {
+            index += 1;
+        }
+    }
+    count
+}
+
+/// Number of lexer tokens glued in the first token (more than one for an operator)
+fn glued_len(tokens: &[Option<&str>]) -> usize {
+    GLUED_OPERATORS
+        .iter()
+        .find(|operator| {
+            tokens.len() >= operator.len()
+                && tokens[..operator.len()]
+                    .iter()
+                    .map(|token| token.unwrap_or(" "))
+                    .collect::<String>()
+                    == **operator
+        })
+        .map_or(1, |operator| operator.len())
+}
+
+/// Heuristic used to weight the terms of the Terms Tree.
+///
+/// The kind of each term (and so its category: intrinsic, children, reference or no weight)
+/// is decided while visiting the AST, the model decides how much each term weighs
+pub trait WeightModel {
+    /// Weight of a term, given the sum of the weights of its children and the weight of the
+    /// referenced item (only for resolved references)
+    fn weight(
+        &self,
+        kind: &TermWeightKind,
+        children_weight: f64,
+        reference_weight: Option<f64>,
+    ) -> f64;
+}
+
+/// Default weight model:
+/// - `Intrinsic` terms weigh 1.0 plus their children
+/// - `Children` terms weigh as their children
+/// - `Reference` terms weigh as the referenced item plus their children
+/// - `No` terms weigh 0.0
+#[derive(Debug, Clone, Default)]
+pub struct DefaultWeightModel;
+
+impl WeightModel for DefaultWeightModel {
+    fn weight(
+        &self,
+        kind: &TermWeightKind,
+        children_weight: f64,
+        reference_weight: Option<f64>,
+    ) -> f64 {
+        match kind {
+            TermWeightKind::Intrinsic(..) => 1.0 + children_weight,
+            TermWeightKind::Children(..) => children_weight,
+            TermWeightKind::Reference(..) => reference_weight.unwrap_or(0.0) + children_weight,
+            TermWeightKind::No(..) => 0.0,
+        }
+    }
+}
+
+/// Weight model overriding the intrinsic weight of some kinds of terms, read from a TOML file:
+///
+/// ```toml
+/// [weights]
+/// Closure = 2.0
+/// MethodCall = 0.5
+/// Match = 3.0
+/// ```
+///
+/// The keys are the names of the kinds of terms (as shown in the Terms Tree).
+/// The overridden weight replaces the intrinsic weight of the kind (1.0 for `Intrinsic`, 0.0
+/// otherwise), the weight of the children and of the referenced item is still added
+/// (except for `No` terms, that ignore their children).
+/// Kinds not listed fall back to the `DefaultWeightModel`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TomlWeightModel {
+    #[serde(default)]
+    pub weights: HashMap<String, f64>,
+}
+
+impl TomlWeightModel {
+    /// Read the weight model from a TOML file
+    pub fn from_file(path: &str) -> Result<Self, RustyExError> {
+        read_toml(path).map_err(|message| RustyExError::InvalidWeightModel {
+            path: path.to_string(),
+            message,
+        })
+    }
+}
+
+impl WeightModel for TomlWeightModel {
+    fn weight(
+        &self,
+        kind: &TermWeightKind,
+        children_weight: f64,
+        reference_weight: Option<f64>,
+    ) -> f64 {
+        match (self.weights.get(kind.kind_name()), kind) {
+            (Some(weight), TermWeightKind::No(..)) => *weight,
+            (Some(weight), _) => weight + reference_weight.unwrap_or(0.0) + children_weight,
+            (None, _) => DefaultWeightModel.weight(kind, children_weight, reference_weight),
+        }
+    }
+}
+
+/// Weight of the terms that cannot be resolved when no data is available
+pub const DEFAULT_RECOVERY_WEIGHT: f64 = 7.0;
+
+/// Strategy to choose the weight of the terms that cannot be resolved (e.g., calls to
+/// functions of other crates), computed from the weights of the resolved functions
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum RecoveryWeight {
+    /// Fixed weight
+    Constant(f64),
+    /// Mean of the weights of the resolved functions
+    Mean,
+    /// Median of the weights of the resolved functions
+    Median,
+    /// Percentile (between 0 and 100) of the weights of the resolved functions
+    Percentile(f64),
+}
+
+impl Default for RecoveryWeight {
+    fn default() -> Self {
+        RecoveryWeight::Constant(DEFAULT_RECOVERY_WEIGHT)
+    }
+}
+
+impl RecoveryWeight {
+    /// Compute the recovery weight from the weights of the resolved functions.
+    /// Without resolved functions `DEFAULT_RECOVERY_WEIGHT` is used
+    pub fn compute(&self, weights: &[f64]) -> f64 {
+        if let RecoveryWeight::Constant(weight) = self {
+            return *weight;
+        }
+        if weights.is_empty() {
+            return DEFAULT_RECOVERY_WEIGHT;
+        }
+
+        let mut sorted = weights.to_vec();
+        sorted.sort_by(f64::total_cmp);
+        match self {
+            RecoveryWeight::Constant(weight) => *weight,
+            RecoveryWeight::Mean => sorted.iter().sum::<f64>() / sorted.len() as f64,
+            RecoveryWeight::Median => percentile(&sorted, 50.0),
+            RecoveryWeight::Percentile(p) => percentile(&sorted, *p),
+        }
+    }
+}
+
+/// Percentile of sorted (not empty) values, linearly interpolated between the closest ranks
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    let rank = p.clamp(0.0, 100.0) / 100.0 * (sorted.len() - 1) as f64;
+    let (lower, upper) = (rank.floor() as usize, rank.ceil() as usize);
+    sorted[lower] + (sorted[upper] - sorted[lower]) * (rank - lower as f64)
+}
+
+impl FromStr for RecoveryWeight {
+    type Err = String;
+
+    /// Parse `mean`, `median`, `p<percentile>` (e.g., `p90`) or a fixed weight (e.g., `7.0`)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "mean" => Ok(RecoveryWeight::Mean),
+            "median" => Ok(RecoveryWeight::Median),
+            _ => match s.strip_prefix('p') {
+                Some(p) => match p.parse::<f64>() {
+                    Ok(p) if (0.0..=100.0).contains(&p) => Ok(RecoveryWeight::Percentile(p)),
+                    _ => Err(format!("invalid percentile '{}', expected p0-p100", s)),
+                },
+                None => match s.parse::<f64>() {
+                    Ok(weight) if weight.is_finite() && weight >= 0.0 => {
+                        Ok(RecoveryWeight::Constant(weight))
+                    }
+                    _ => Err(format!(
+                        "invalid recovery weight '{}', expected mean, median, p<percentile> or a non-negative number",
+                        s
+                    )),
+                },
+            },
+        }
+    }
+}
+
+/// Weights of the items of other crates, read from a TOML file:
+///
+/// ```toml
+/// [weights]
+/// "serde_json::to_string" = 12.0
+/// push = 2.0
+/// ```
+///
+/// The keys are the names of the referenced items (as shown in the Terms Tree).
+/// Unresolved terms referencing a listed item get its weight instead of the recovery weight
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DependencyWeights {
+    #[serde(default)]
+    pub weights: HashMap<String, f64>,
+}
+
+impl DependencyWeights {
+    /// Read the dependency weights from a TOML file
+    pub fn from_file(path: &str) -> Result<Self, RustyExError> {
+        read_toml(path).map_err(|message| RustyExError::InvalidDependencyWeights {
+            path: path.to_string(),
+            message,
+        })
+    }
+}
+
+/// Read and deserialize a TOML file
+fn read_toml<T: DeserializeOwned>(path: &str) -> Result<T, String> {
+    let content = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    toml::from_str(&content).map_err(|e| e.to_string())
+}
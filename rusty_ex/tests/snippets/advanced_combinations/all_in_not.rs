@@ -0,0 +1,7 @@
+#[cfg(not(all(feature = "a", feature = "b")))]
+fn not_all() {
+
+  #[cfg(feature = "c")]
+  fn one() {}
+
+}
@@ -0,0 +1,7 @@
+#[cfg(not(any(feature = "a", feature = "b")))]
+fn not_any() {
+
+  #[cfg(feature = "c")]
+  fn one() {}
+
+}
@@ -0,0 +1,11 @@
+fn baz(_: &str) {}
+
+#[cfg(feature = "f1")]
+fn foo() {
+    #[cfg(all(feature = "f2", feature = "f3"))]
+    baz("hello");
+}
+
+fn main() {
+    foo()
+}
@@ -0,0 +1,10 @@
+#[cfg(feature = "f1")]
+fn foo() {
+    #[cfg(unix)]
+    let _ = 1;
+
+    #[cfg(target_os = "linux")]
+    let _ = 1;
+}
+
+fn main() {}
@@ -0,0 +1,16 @@
+// malformed: the kinds of its features are not saved
+#[cfg(feature = "windows", unix)]
+fn malformed() {}
+
+#[cfg(windows)]
+fn foo() {
+    #[cfg(unix)]
+    let _ = 1;
+}
+
+#[cfg(feature = "unix")]
+fn bar() {
+    let _ = 1;
+}
+
+fn main() {}
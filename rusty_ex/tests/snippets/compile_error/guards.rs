@@ -0,0 +1,32 @@
+#[cfg(all(feature = "a", feature = "b"))]
+compile_error!("features `a` and `b` are mutually exclusive");
+
+#[cfg(not(any(feature = "c", feature = "d")))]
+compile_error!(
+    "at least one of the features `c` and `d` must be enabled"
+);
+
+#[cfg(feature = "a")]
+fn a() {}
+
+#[cfg(feature = "b")]
+fn b() {}
+
+#[cfg(feature = "c")]
+fn c() {}
+
+#[cfg(feature = "d")]
+mod d {
+    #[cfg(feature = "e")]
+    compile_error! {"feature `e` is not supported with `d`"}
+
+    // compile_error!("not a guard")
+}
+
+#[cfg(feature = "e")]
+fn e() {
+    #[cfg(feature = "a")]
+    compile_error!("features `a` and `e` are mutually exclusive");
+}
+
+fn main() {}
@@ -0,0 +1,28 @@
+macro_rules! unsupported {
+    ($feature:literal) => {
+        #[cfg(feature = "c")]
+        compile_error!(concat!("feature `", $feature, "` is not supported with `c`"));
+    };
+}
+
+macro_rules! quoted {
+    () => {
+        compile_error!(concat!("not a guard: ", '"', '\'', ")"))
+    };
+}
+
+#[cfg(feature = "a")]
+compile_error!(r#"feature "a" is not supported, use `b` (or `c`)"#);
+
+#[cfg(all(feature = "b", feature = "c"))]
+compile_error!(r"features `b` and `c` are mutually exclusive \");
+
+unsupported!("d");
+
+#[cfg(feature = "b")]
+fn b() {}
+
+#[cfg(feature = "c")]
+fn c() {}
+
+fn main() {}
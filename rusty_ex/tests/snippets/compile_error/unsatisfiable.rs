@@ -0,0 +1,10 @@
+#[cfg(feature = "a")]
+compile_error!("feature `a` is not supported");
+
+#[cfg(not(feature = "a"))]
+compile_error!("feature `a` is required");
+
+#[cfg(feature = "a")]
+fn a() {}
+
+fn main() {}
@@ -0,0 +1,15 @@
+#[cfg(all(feature = "a", feature = "b"))]
+compile_error!("features `a` and `b` are mutually exclusive");
+
+#[cfg(not(any(feature = "a", feature = "b")))]
+compile_error!("one of the features `a` and `b` is required");
+
+#[cfg(any(feature = "a", feature = "b"))]
+fn either() {}
+
+#[cfg(any(not(feature = "a"), not(feature = "b")))]
+fn not_both() {
+    let _ = 1;
+}
+
+fn main() {}
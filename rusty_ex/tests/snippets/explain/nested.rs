@@ -0,0 +1,16 @@
+#[cfg(feature = "a")]
+fn outer() {
+    #[cfg(feature = "b")]
+    fn inner() {
+        #[cfg(not(feature = "c"))]
+        let _ = 1;
+    }
+
+    #[cfg(all(feature = "a", feature = "d"))]
+    let _ = 2;
+}
+
+#[cfg(feature = "a")]
+fn other() {}
+
+fn main() {}
@@ -0,0 +1,16 @@
+#[cfg(feature = "a")]
+fn outer() {
+    #[cfg(all(feature = "b", not(feature = "c")))]
+    let _ = 1;
+
+    #[cfg(not(feature = "a"))]
+    let _ = 2;
+}
+
+#[cfg(any(feature = "b", feature = "c"))]
+fn either() {}
+
+#[cfg(all(feature = "b", not(feature = "c")))]
+fn both() {}
+
+fn main() {}
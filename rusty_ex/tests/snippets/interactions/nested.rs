@@ -0,0 +1,4 @@
+#[cfg(all(feature = "a", any(feature = "b", feature = "c")))]
+fn nested() {}
+
+fn main() {}
@@ -0,0 +1,5 @@
+#[cfg(any(feature, feature = "a"))]
+fn malformed() {}
+
+#[cfg(feature = "b")]
+fn well_formed() {}
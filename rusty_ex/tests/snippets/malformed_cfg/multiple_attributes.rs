@@ -0,0 +1,3 @@
+#[cfg(feature = "a")]
+#[cfg(feature = "b")]
+fn multiple() {}
@@ -0,0 +1,7 @@
+#[cfg(feature = "a", feature = "b")]
+fn malformed() {}
+
+#[cfg(feature = "c")]
+fn well_formed() {
+    malformed()
+}
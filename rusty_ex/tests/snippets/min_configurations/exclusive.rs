@@ -0,0 +1,22 @@
+#[cfg(all(feature = "a", feature = "b"))]
+compile_error!("features `a` and `b` are mutually exclusive");
+
+#[cfg(feature = "a")]
+fn a() {}
+
+#[cfg(all(feature = "a", feature = "c"))]
+fn a_and_c() {}
+
+#[cfg(feature = "b")]
+fn b() {
+    let v = vec![1, 2, 3];
+    let _ = v.iter().map(|x| x * 2).filter(|x| *x > 2).sum::<i32>();
+}
+
+#[cfg(not(feature = "c"))]
+fn no_c() {}
+
+#[cfg(all(feature = "a", feature = "b"))]
+fn a_and_b() {}
+
+fn main() {}
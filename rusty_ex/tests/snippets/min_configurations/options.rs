@@ -0,0 +1,16 @@
+#[cfg(feature = "a")]
+mod a {
+    #[cfg(feature = "b")]
+    fn a_and_b() {
+        let v = vec![1, 2, 3];
+        let _ = v.iter().map(|x| x * 2).filter(|x| *x > 2).sum::<i32>();
+    }
+}
+
+#[cfg(unix)]
+fn unix() {}
+
+#[cfg(all(feature = "b", not(unix)))]
+fn b_not_unix() {}
+
+fn main() {}
@@ -0,0 +1,27 @@
+#[cfg(not(feature = "a"))]
+fn straight() -> i32 {
+    1 + 2
+}
+
+#[cfg(not(feature = "b"))]
+fn branching(x: i32) -> i32 {
+    let mut sum = 0;
+    for i in 0..x {
+        if i % 2 == 0 {
+            sum += straight();
+        } else {
+            sum -= i;
+        }
+    }
+    sum
+}
+
+fn main() {
+    #[cfg(not(feature = "c"))]
+    let _ = branching(3);
+}
+
+#[cfg(feature = "d")]
+fn disabled() -> i32 {
+    straight() * 2
+}
@@ -0,0 +1,16 @@
+#[cfg(feature = "a")]
+fn enabled() {
+    #[cfg(not(feature = "b"))]
+    let _ = 1;
+}
+
+#[cfg(not(feature = "a"))]
+fn disabled() {
+    #[cfg(feature = "b")]
+    fn nested() {}
+}
+
+#[cfg(feature = "c")]
+fn other() {}
+
+fn main() {}
@@ -0,0 +1,33 @@
+fn one() {
+    1;
+}
+
+fn three() {
+    1;
+    2;
+    3;
+}
+
+fn eight() {
+    1;
+    2;
+    3;
+    4;
+    5;
+    6;
+    7;
+    8;
+}
+
+#[cfg(feature = "a")]
+fn external() {
+    std::process::exit(0);
+}
+
+#[cfg(feature = "b")]
+fn mixed() {
+    one();
+    std::process::exit(0);
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+#[cfg(feature = "a")]
+fn first() {
+    #[cfg(not(feature = "b"))]
+    let _ = 1;
+}
+
+#[cfg(any(feature = "a", feature = "c"))]
+fn second() {}
+
+fn main() {}
@@ -0,0 +1,39 @@
+struct A;
+struct B;
+
+impl A {
+    fn new() -> A {
+        A
+    }
+}
+
+impl B {
+    fn new() -> B {
+        1;
+        2;
+        B
+    }
+}
+
+mod small {
+    pub fn helper() {}
+
+    pub fn run() {
+        helper();
+    }
+}
+
+mod big {
+    pub fn helper() {
+        1;
+        2;
+        3;
+    }
+}
+
+fn main() {
+    A::new();
+    B::new();
+    small::run();
+    big::helper();
+}
@@ -0,0 +1,39 @@
+struct A;
+struct B;
+
+trait Shape {
+    fn area(&self) -> u32;
+}
+
+impl A {
+    fn size(&self) {}
+}
+
+impl B {
+    fn size(&self) {
+        1;
+        2;
+        3;
+    }
+}
+
+impl Shape for A {
+    fn area(&self) -> u32 {
+        1
+    }
+}
+
+impl Shape for B {
+    fn area(&self) -> u32 {
+        1;
+        2;
+        3
+    }
+}
+
+fn main() {
+    A.size();
+    B.size();
+    A.area();
+    B.area();
+}
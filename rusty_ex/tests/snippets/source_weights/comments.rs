@@ -0,0 +1,17 @@
+#[cfg(feature = "a")]
+fn commented() -> i32 {
+    // a comment
+
+    /* a block
+    comment */
+    let x = 1; // trailing comment
+    x + 1
+}
+
+#[cfg(feature = "b")]
+/// documented
+fn compact() -> bool {
+    cfg!(feature = "c") && true
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+fn main() {
+    a();
+    let _ = |x: i32| x;
+}
+
+fn a() {
+    1;
+}
@@ -0,0 +1,265 @@
+#![feature(rustc_private)]
+
+use clap::Parser;
+use rusty_ex::analysis::analyze;
+use rusty_ex::errors::RustyExError;
+use rusty_ex::types::TermWeight;
+use rusty_ex::PrintAstArgs;
+use std::path::PathBuf;
+
+/// Absolute path of a file or directory of the tests
+fn tests_path(path: &str) -> PathBuf {
+    std::env::current_dir().unwrap().join("tests").join(path)
+}
+
+/// Names of the artifacts of the Artifacts Tree
+fn artifact_idents(result: &rusty_ex::analysis::AnalysisResult) -> Vec<String> {
+    let mut idents = result
+        .artifacts_tree
+        .graph
+        .node_weights()
+        .filter_map(|node| node.ident.clone())
+        .collect::<Vec<_>>();
+    idents.sort();
+    idents
+}
+
+// =============================================
+
+#[test]
+fn test_analyze_file() {
+    let result = analyze(
+        &tests_path("snippets/mir/functions.rs"),
+        PrintAstArgs::default(),
+    )
+    .unwrap();
+
+    assert_eq!(
+        artifact_idents(&result),
+        vec!["__GLOBAL__", "branching", "disabled", "straight"]
+    );
+    for feature in ["a", "b", "c", "d"] {
+        assert!(result.cnf_mapping.contains_key(feature));
+    }
+    // no `cfg` is nested in another one: the features are not constrained
+    assert!(result.cnf.is_empty());
+    assert!(!result.configs.is_empty());
+    assert!(result.errors.is_empty());
+}
+
+#[test]
+fn test_analyze_nesting_model() {
+    let result = analyze(
+        &tests_path("snippets/basic_combinations/one_in_one.rs"),
+        PrintAstArgs::default(),
+    )
+    .unwrap();
+
+    // the code of `b` is nested in the code of `a`, so `b -> a`
+    let mut clause = vec![
+        (result.cnf_mapping["b"], false),
+        (result.cnf_mapping["a"], true),
+    ];
+    clause.sort();
+    assert_eq!(result.cnf, vec![clause]);
+}
+
+#[test]
+fn test_analyze_package() {
+    let result = analyze(
+        &tests_path("workspaces/simple_feature_no_weights"),
+        PrintAstArgs::default(),
+    )
+    .unwrap();
+
+    // the modules of the crate are analysed too
+    assert_eq!(
+        artifact_idents(&result),
+        vec!["__GLOBAL__", "five", "four", "one", "six", "three", "two"]
+    );
+    // one index for each feature, by CNF variable
+    assert_eq!(result.centrality.indices.len(), 6);
+    for index in &result.centrality.indices {
+        assert!(result
+            .cnf_mapping
+            .values()
+            .any(|variable| variable == index));
+    }
+}
+
+#[test]
+fn test_analyze_options() {
+    let options = PrintAstArgs::parse_from(["rusty-ex", "--weight-mode", "lines"]);
+    let result = analyze(&tests_path("snippets/source_weights/comments.rs"), options).unwrap();
+
+    let weights = result
+        .artifacts_tree
+        .graph
+        .node_weights()
+        .filter(|node| node.ident.as_deref() == Some("commented"))
+        .map(|node| node.weight.clone())
+        .collect::<Vec<_>>();
+    assert_eq!(weights, vec![TermWeight::Weight(8.0)]);
+}
+
+#[test]
+fn test_analyze_missing_crate() {
+    let path = tests_path("snippets/missing.rs");
+    let error = analyze(&path, PrintAstArgs::default()).unwrap_err();
+
+    assert_eq!(
+        error,
+        RustyExError::InvalidCrate {
+            path: path.display().to_string(),
+            message: "no such file or directory".to_string()
+        }
+    );
+}
+
+#[test]
+fn test_analyze_compile_error_guards() {
+    let result = analyze(
+        &tests_path("snippets/compile_error/guards.rs"),
+        PrintAstArgs::default(),
+    )
+    .unwrap();
+
+    // the guards are constraints, not artifacts
+    assert_eq!(
+        artifact_idents(&result),
+        vec!["__GLOBAL__", "a", "b", "c", "d", "e"]
+    );
+    let guards = result
+        .compile_error_guards
+        .iter()
+        .map(|guard| {
+            (
+                guard.complex_feature.to_string(),
+                guard
+                    .enclosing
+                    .iter()
+                    .map(|complex_feature| complex_feature.to_string())
+                    .collect::<Vec<_>>(),
+                guard.message.clone().unwrap(),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(guards.len(), 4);
+    assert_eq!(guards[0].0, "all(a, b)");
+    // De Morgan: none of them is enabled
+    assert_eq!(guards[1].0, "all(!c, !d)");
+    assert_eq!(
+        guards[1].2,
+        "at least one of the features `c` and `d` must be enabled"
+    );
+    assert_eq!(guards[2].0, "e");
+    assert_eq!(guards[2].1, vec!["d"]);
+    assert_eq!(guards[3].0, "a");
+    assert_eq!(guards[3].1, vec!["e"]);
+
+    // each guard excludes the configurations enabling it
+    let literal = |name: &str, value: bool| (result.cnf_mapping[name], value);
+    for mut clause in [
+        vec![literal("a", false), literal("b", false)],
+        vec![literal("c", true), literal("d", true)],
+        vec![literal("d", false), literal("e", false)],
+        vec![literal("a", false), literal("e", false)],
+    ] {
+        clause.sort();
+        assert!(result.cnf.contains(&clause), "missing clause {:?}", clause);
+    }
+    for feature in ["a", "b", "c", "d", "e"] {
+        assert!(result.cnf_mapping.contains_key(feature));
+    }
+
+    // the guards are applied on top of the feature model: no configuration enables a guard
+    let enabled =
+        |config: &Vec<(u32, bool)>, name: &str| config.contains(&(result.cnf_mapping[name], true));
+    assert!(!result.configs.is_empty());
+    for config in &result.configs {
+        assert!(!(enabled(config, "a") && enabled(config, "b")));
+        assert!(enabled(config, "c") || enabled(config, "d"));
+        assert!(!(enabled(config, "d") && enabled(config, "e")));
+        assert!(!(enabled(config, "a") && enabled(config, "e")));
+    }
+    // no valid configuration is lost by the simplification
+    for feature in ["a", "b", "c", "d", "e"] {
+        assert!(result.configs.iter().any(|config| enabled(config, feature)));
+    }
+    assert!(result.errors.is_empty());
+}
+
+#[test]
+fn test_analyze_compile_error_macros() {
+    let result = analyze(
+        &tests_path("snippets/compile_error/macros.rs"),
+        PrintAstArgs::default(),
+    )
+    .unwrap();
+
+    // the `compile_error!` in the body of `unsupported` is not a guard, even where it is used
+    let guards = result
+        .compile_error_guards
+        .iter()
+        .map(|guard| (guard.complex_feature.to_string(), guard.message.clone()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        guards,
+        vec![
+            (
+                "a".to_string(),
+                Some(r#"feature "a" is not supported, use `b` (or `c`)"#.to_string())
+            ),
+            (
+                "all(b, c)".to_string(),
+                Some(r"features `b` and `c` are mutually exclusive \".to_string())
+            ),
+        ]
+    );
+    assert_eq!(artifact_idents(&result), vec!["__GLOBAL__", "b", "c"]);
+}
+
+#[test]
+fn test_analyze_unsatisfiable_guards() {
+    let result = analyze(
+        &tests_path("snippets/compile_error/unsatisfiable.rs"),
+        PrintAstArgs::default(),
+    )
+    .unwrap();
+
+    // `a` is both required and rejected
+    assert!(result.configs.is_empty());
+    assert_eq!(
+        result.errors,
+        vec![RustyExError::UnsatisfiableGuards { guards: 2 }]
+    );
+}
+
+#[test]
+fn test_analyze_config_budget() {
+    let path = tests_path("snippets/config_generator/exclusive.rs");
+    let result = analyze(&path, PrintAstArgs::default()).unwrap();
+
+    // the guards require exactly one of `a` and `b`: two configurations cover `a`, `!a`, `b` and
+    // `!b`
+    assert_eq!(result.configs.len(), 2);
+    let (a, b) = (result.cnf_mapping["a"], result.cnf_mapping["b"]);
+    for config in &result.configs {
+        assert_ne!(config.contains(&(a, true)), config.contains(&(b, true)));
+    }
+    assert_ne!(result.configs[0], result.configs[1]);
+
+    let options =
+        PrintAstArgs::parse_from(["rusty-ex", "--config-budget", "1", "--rank-by", "in-degree"]);
+    let result = analyze(&path, options).unwrap();
+    assert_eq!(result.configs.len(), 1);
+
+    // the most central feature is covered first
+    let in_degree = result.centrality.in_degree();
+    let most_central = (0..in_degree.len())
+        .max_by(|i, j| in_degree[*i].total_cmp(&in_degree[*j]).then(j.cmp(i)))
+        .unwrap();
+    let feature = &result.centrality.features[most_central];
+    let lit = (result.cnf_mapping[&feature.name], !feature.not);
+    assert!(result.configs[0].contains(&lit));
+}
@@ -3,8 +3,10 @@
 mod utils;
 
 use pretty_assertions::assert_eq;
-use rusty_ex::configs::centrality::Centrality;
-use utils::run_with_cargo_bin_and_snippet;
+use rusty_ex::configs::centrality::{Centrality, CentralityParams, FeatureCentrality};
+use rusty_ex::types::{CfgKind, Edge, Feature, FeatureKey, FeatureNode, FeaturesGraph};
+use std::collections::{HashMap, HashSet};
+use utils::{run_with_cargo_bin_and_snippet, same_line};
 
 const CENTRALITY_FOLDER: &str = "tests/snippets/centrality";
 
@@ -46,12 +48,51 @@ macro_rules! assert_greatest_index {
 }
 
 fn get_centrality_measures(file: &str) -> Result<Centrality<u32>, String> {
+    get_centrality_measures_with_args(file, &[])
+}
+
+fn get_centrality_measures_with_args(file: &str, args: &[&str]) -> Result<Centrality<u32>, String> {
     let snippet = &std::fs::read_to_string(format!("{CENTRALITY_FOLDER}/{file}")).unwrap();
-    let (output, _) = run_with_cargo_bin_and_snippet(snippet, &["--serialized-centrality", "all"])?;
+    let args = [&["--serialized-centrality", "all"], args].concat();
+    let (output, _) = run_with_cargo_bin_and_snippet(snippet, &args)?;
     let deserialized_centrality: Centrality<u32> = serde_json::from_str(&output).unwrap();
     Ok(deserialized_centrality)
 }
 
+/// Centrality of a features graph with the given weighted edges between the nodes `0..nodes`,
+/// not refined (every feature weighs 1)
+fn graph_centrality(nodes: usize, edges: &[(usize, usize, f64)]) -> Centrality<u32> {
+    let mut features_graph = FeaturesGraph::new();
+    let indices = (0..nodes)
+        .map(|i| {
+            features_graph.graph.add_node(FeatureNode {
+                feature: FeatureKey(Feature {
+                    name: format!("f{i}"),
+                    not: false,
+                }),
+                weight: None,
+                complex_feature: HashSet::new(),
+            })
+        })
+        .collect::<Vec<_>>();
+    for (source, target, weight) in edges {
+        features_graph
+            .graph
+            .add_edge(indices[*source], indices[*target], Edge { weight: *weight });
+    }
+
+    let refiner = indices.iter().map(|index| (*index, 1.0)).collect();
+    let mapping: HashMap<String, u32> = (0..nodes).map(|i| (format!("f{i}"), i as u32)).collect();
+    let (centrality, _) = Centrality::new(
+        &features_graph,
+        &refiner,
+        &mapping,
+        false,
+        &CentralityParams::default(),
+    );
+    centrality
+}
+
 #[test]
 fn test_no_centrality() -> Result<(), String> {
     let centrality = get_centrality_measures("no_centrality.rs")?;
@@ -74,9 +115,9 @@ fn test_nested_features() -> Result<(), String> {
     let closeness = centrality.closeness();
     let eigenvector = centrality.eigenvector().unwrap();
 
-    let katz_out = vec![0.14722, 0.34022, 0.3236, 0.1078];
-    let closeness_out = vec![Some(0.2083), Some(0.4545), Some(0.5555), Some(0.1388)];
-    let eigenvector_out = vec![0.2067, 0.2606, 0.3415, 0.1138];
+    let katz_out = vec![0.1618, 0.4854, 0.2201, 0.0734];
+    let closeness_out = vec![Some(0.2778), Some(0.625), Some(0.2564), Some(0.0855)];
+    let eigenvector_out = vec![0.1708, 0.5123, 0.0704, 0.0235];
 
     assert_almost_equal_iter!(*katz_out, katz, 1e-4);
     assert_almost_equal_option_iter!(*closeness_out, closeness, 1e-4);
@@ -93,16 +134,16 @@ fn test_one_important_feature() -> Result<(), String> {
     let closeness = centrality.closeness();
     let eigenvector = centrality.eigenvector().unwrap();
 
-    let katz_out = vec![0.4620, 0.3708, 0.5064, 0.1205];
-    let closeness_out = vec![Some(0.625), Some(0.4545), Some(0.8333), Some(0.1190)];
-    let eigenvector_out = vec![0.6845, 0.3466, 0.5617, 0.0595];
+    let katz_out = vec![0.5064, 0.3617, 0.3617, 0.1206];
+    let closeness_out = vec![Some(0.8333), Some(0.3571), Some(0.3846), Some(0.1282)];
+    let eigenvector_out = vec![0.5618, 0.1785, 0.1785, 0.0595];
 
     assert_almost_equal_iter!(*katz_out, katz, 1e-4);
     assert_almost_equal_option_iter!(*closeness_out, closeness, 1e-4);
     assert_almost_equal_iter!(*eigenvector_out, eigenvector, 1e-4);
 
-    assert_greatest_index!(katz, 2);
-    assert_greatest_index!(closeness, 2);
+    assert_greatest_index!(katz, 0);
+    assert_greatest_index!(closeness, 0);
     assert_greatest_index!(eigenvector, 0);
 
     Ok(())
@@ -116,9 +157,9 @@ fn test_one_important_nested_feature() -> Result<(), String> {
     let closeness = centrality.closeness();
     let eigenvector = centrality.eigenvector().unwrap();
 
-    let katz_out = vec![0.4909, 0.4865];
-    let closeness_out = vec![Some(0.6), Some(0.5)];
-    let eigenvector_out = vec![0.5357, 0.4358];
+    let katz_out = vec![0.5347, 0.4861];
+    let closeness_out = [Some(0.75), Some(0.5)];
+    let eigenvector_out = vec![0.6356, 0.3450];
 
     assert_almost_equal_iter!(*katz_out, katz, 1e-4);
     assert_almost_equal_option_iter!(*closeness, closeness_out, 1e-4);
@@ -130,3 +171,231 @@ fn test_one_important_nested_feature() -> Result<(), String> {
 
     Ok(())
 }
+
+#[test]
+fn test_katz_not_converging() -> Result<(), String> {
+    let centrality =
+        get_centrality_measures_with_args("nested_features.rs", &["--katz-max-iter", "1"])?;
+
+    // the measure that does not converge is left empty, the others are still computed
+    assert!(centrality.katz().is_none());
+    assert_eq!(centrality.closeness().len(), 4);
+    assert_eq!(centrality.eigenvector().unwrap().len(), 4);
+
+    Ok(())
+}
+
+#[test]
+fn test_unweighted_closeness() -> Result<(), String> {
+    // `f2` and `f3` reach `f1` only through edges of weight 0.50, which are twice as long as
+    // the others in the weighted closeness
+    let weighted = get_centrality_measures("all_in_feature.rs")?;
+    let unweighted =
+        get_centrality_measures_with_args("all_in_feature.rs", &["--closeness", "unweighted"])?;
+
+    let weighted_out = [Some(0.5714), Some(0.1818), Some(0.1818)];
+    let unweighted_out = [Some(0.8), Some(0.2), Some(0.2)];
+
+    assert_almost_equal_option_iter!(weighted_out, weighted.closeness(), 1e-4);
+    assert_almost_equal_option_iter!(unweighted_out, unweighted.closeness(), 1e-4);
+    assert_eq!(weighted.katz(), unweighted.katz());
+
+    Ok(())
+}
+
+#[test]
+fn test_more_measures() -> Result<(), String> {
+    let no_centrality = get_centrality_measures("no_centrality.rs")?;
+    assert_eq!(*no_centrality.pagerank().unwrap(), vec![0.0, 0.0]);
+    assert_eq!(*no_centrality.betweenness(), vec![0.0, 0.0]);
+    assert_eq!(*no_centrality.in_degree(), vec![0.0, 0.0]);
+    assert_eq!(*no_centrality.out_degree(), vec![0.0, 0.0]);
+    assert_eq!(*no_centrality.harmonic(), vec![0.0, 0.0]);
+
+    let centrality = get_centrality_measures("nested_features.rs")?;
+    assert_eq!(centrality.pagerank().unwrap().len(), 4);
+    assert_eq!(centrality.betweenness().len(), 4);
+    assert_eq!(centrality.in_degree().len(), 4);
+    assert_eq!(centrality.out_degree().len(), 4);
+    assert_eq!(centrality.harmonic().len(), 4);
+    assert!(centrality.betweenness().iter().all(|b| *b >= 0.0));
+
+    Ok(())
+}
+
+#[test]
+fn test_pagerank_not_converging() -> Result<(), String> {
+    let centrality =
+        get_centrality_measures_with_args("nested_features.rs", &["--pagerank-max-iter", "1"])?;
+
+    assert!(centrality.pagerank().is_none());
+    assert_eq!(centrality.harmonic().len(), 4);
+
+    Ok(())
+}
+
+#[test]
+fn test_serialized_single_measure() -> Result<(), String> {
+    let snippet =
+        &std::fs::read_to_string(format!("{CENTRALITY_FOLDER}/nested_features.rs")).unwrap();
+    let (output, _) =
+        run_with_cargo_bin_and_snippet(snippet, &["--serialized-centrality", "betweenness"])?;
+    let betweenness: Vec<f64> = serde_json::from_str(&output).unwrap();
+    assert_eq!(betweenness.len(), 4);
+
+    Ok(())
+}
+
+#[test]
+fn test_features_centrality() -> Result<(), String> {
+    let snippet =
+        &std::fs::read_to_string(format!("{CENTRALITY_FOLDER}/one_important_feature.rs")).unwrap();
+    let (output, _) = run_with_cargo_bin_and_snippet(
+        snippet,
+        &["--print-features-centrality", "--rank-by", "eigenvector"],
+    )?;
+    let features: Vec<FeatureCentrality> = serde_json::from_str(&output).unwrap();
+
+    assert_eq!(features.len(), 4);
+    assert_eq!(features[0].name, "f1");
+    assert!(!features[0].not);
+    assert_eq!(features[0].cfg_kind, Some(CfgKind::Feature));
+    assert_eq!(features[0].rank, 1);
+    assert_almost_equal!(features[0].eigenvector.unwrap(), 0.5618, 1e-4);
+    assert_almost_equal!(features[0].refined_weight, 1.0, 1e-4);
+
+    let negated = features.iter().find(|f| f.not).unwrap();
+    assert_eq!(negated.name, "f3");
+    assert_eq!(negated.rank, 4);
+
+    Ok(())
+}
+
+#[test]
+fn test_centrality_table() -> Result<(), String> {
+    let snippet =
+        &std::fs::read_to_string(format!("{CENTRALITY_FOLDER}/one_important_feature.rs")).unwrap();
+    let (output, _) = run_with_cargo_bin_and_snippet(snippet, &["--print-centrality"])?;
+
+    assert!(same_line(&output, vec!["rank", "feature", "kind", "katz"]));
+    assert!(same_line(&output, vec!["1", "f1", "feature", "0.5064"]));
+    assert!(same_line(&output, vec!["4", "!f3", "feature"]));
+
+    Ok(())
+}
+
+#[test]
+fn test_features_cfg_kind() -> Result<(), String> {
+    let snippet = &std::fs::read_to_string(format!("{CENTRALITY_FOLDER}/cfg_kinds.rs")).unwrap();
+    let (output, _) = run_with_cargo_bin_and_snippet(snippet, &["--print-features-centrality"])?;
+    let features: Vec<FeatureCentrality> = serde_json::from_str(&output).unwrap();
+
+    let kind = |name: &str| {
+        features
+            .iter()
+            .find(|f| f.name == name)
+            .unwrap()
+            .cfg_kind
+            .clone()
+    };
+    assert_eq!(kind("f1"), Some(CfgKind::Feature));
+    assert_eq!(kind("unix"), Some(CfgKind::Name));
+    assert_eq!(kind("target_os"), Some(CfgKind::NameValue));
+
+    Ok(())
+}
+
+#[test]
+fn test_features_cfg_kind_precedence() -> Result<(), String> {
+    let snippet =
+        &std::fs::read_to_string(format!("{CENTRALITY_FOLDER}/cfg_kinds_precedence.rs")).unwrap();
+    let (output, _) = run_with_cargo_bin_and_snippet(snippet, &["--print-features-centrality"])?;
+    let features: Vec<FeatureCentrality> = serde_json::from_str(&output).unwrap();
+
+    let kind = |name: &str| {
+        features
+            .iter()
+            .find(|f| f.name == name)
+            .unwrap()
+            .cfg_kind
+            .clone()
+    };
+    // `feature = "windows"` is in a malformed attribute
+    assert_eq!(kind("windows"), Some(CfgKind::Name));
+    // `feature = "unix"` wins over `unix`, even if it comes later
+    assert_eq!(kind("unix"), Some(CfgKind::Feature));
+
+    Ok(())
+}
+
+#[test]
+fn test_measures_path() -> Result<(), String> {
+    // 0 -> 1 -> 2
+    let centrality = graph_centrality(3, &[(0, 1, 1.0), (1, 2, 1.0)]);
+
+    assert_almost_equal_iter!(
+        [0.1844, 0.3412, 0.4744],
+        centrality.pagerank().unwrap(),
+        1e-4
+    );
+    assert_almost_equal_iter!([0.0, 0.5, 0.0], centrality.betweenness(), 1e-4);
+    assert_almost_equal_iter!([0.0, 1.0, 1.0], centrality.in_degree(), 1e-4);
+    assert_almost_equal_iter!([1.0, 1.0, 0.0], centrality.out_degree(), 1e-4);
+    assert_almost_equal_iter!([0.0, 1.0, 1.5], centrality.harmonic(), 1e-4);
+
+    // the weights are the distances of the betweenness and harmonic centrality
+    let centrality = graph_centrality(3, &[(0, 1, 0.5), (1, 2, 2.0)]);
+
+    assert_almost_equal_iter!([0.0, 0.5, 0.0], centrality.betweenness(), 1e-4);
+    assert_almost_equal_iter!([0.0, 0.5, 2.0], centrality.in_degree(), 1e-4);
+    assert_almost_equal_iter!([0.5, 2.0, 0.0], centrality.out_degree(), 1e-4);
+    assert_almost_equal_iter!([0.0, 2.0, 0.9], centrality.harmonic(), 1e-4);
+
+    Ok(())
+}
+
+#[test]
+fn test_measures_star() -> Result<(), String> {
+    // 1 -> 0, 2 -> 0, 3 -> 0
+    let centrality = graph_centrality(4, &[(1, 0, 1.0), (2, 0, 1.0), (3, 0, 1.0)]);
+
+    assert_almost_equal_iter!(
+        [0.5420, 0.1527, 0.1527, 0.1527],
+        centrality.pagerank().unwrap(),
+        1e-4
+    );
+    assert_almost_equal_iter!([0.0, 0.0, 0.0, 0.0], centrality.betweenness(), 1e-4);
+    assert_almost_equal_iter!([3.0, 0.0, 0.0, 0.0], centrality.in_degree(), 1e-4);
+    assert_almost_equal_iter!([0.0, 1.0, 1.0, 1.0], centrality.out_degree(), 1e-4);
+    assert_almost_equal_iter!([3.0, 0.0, 0.0, 0.0], centrality.harmonic(), 1e-4);
+
+    Ok(())
+}
+
+#[test]
+fn test_measures_cycle() -> Result<(), String> {
+    // 0 -> 1 -> 2 -> 0
+    let centrality = graph_centrality(3, &[(0, 1, 1.0), (1, 2, 1.0), (2, 0, 1.0)]);
+
+    assert_almost_equal_iter!(
+        [0.3333, 0.3333, 0.3333],
+        centrality.pagerank().unwrap(),
+        1e-4
+    );
+    assert_almost_equal_iter!([0.5, 0.5, 0.5], centrality.betweenness(), 1e-4);
+    assert_almost_equal_iter!([1.0, 1.0, 1.0], centrality.in_degree(), 1e-4);
+    assert_almost_equal_iter!([1.0, 1.0, 1.0], centrality.out_degree(), 1e-4);
+    assert_almost_equal_iter!([1.5, 1.5, 1.5], centrality.harmonic(), 1e-4);
+
+    Ok(())
+}
+
+#[test]
+fn test_pagerank_zero_weights() -> Result<(), String> {
+    // the edges of 0 weigh nothing: its rank is spread to all the nodes, as if it had no edges
+    let centrality = graph_centrality(2, &[(0, 1, 0.0)]);
+
+    assert_almost_equal_iter!([0.5, 0.5], centrality.pagerank().unwrap(), 1e-4);
+
+    Ok(())
+}
@@ -3,7 +3,10 @@
 mod utils;
 
 use pretty_assertions::assert_eq;
+use rusty_ex::configs::config_generator::ConfigGenerator;
 use rusty_ex::configs::config_solver::{ConfigSolver, ConfigSolverUtils};
+use rusty_ex::configs::CnfFormula;
+use std::time::{Duration, Instant};
 
 #[test]
 fn test_zero_with_zero_true() -> Result<(), String> {
@@ -138,3 +141,93 @@ fn test_particular_case() -> Result<(), String> {
 
     Ok(())
 }
+
+fn complex_cnf() -> CnfFormula<u32> {
+    vec![
+        vec![(0, true), (1, false), (2, true)],
+        vec![(0, false), (1, true)],
+        vec![(2, true), (3, false)],
+    ]
+}
+
+/// All the configurations satisfying the literals, on a new solver, sorted
+fn fresh_configs(vars: Vec<(u32, bool)>) -> CnfFormula<u32> {
+    let mut generator = ConfigSolver::default();
+    generator.add_cnf(complex_cnf());
+    let mut configs = generator.all_configs_given_a_var(vars);
+    configs.sort();
+    configs
+}
+
+#[test]
+fn test_all_configs_assuming_repeated() -> Result<(), String> {
+    let mut generator = ConfigSolver::default();
+    generator.add_cnf(complex_cnf());
+
+    // the queries do not affect each other
+    for vars in [
+        vec![(1, true)],
+        vec![(1, false)],
+        vec![(1, true)],
+        vec![(0, false), (3, true)],
+    ] {
+        let mut configs = generator.all_configs_assuming(&vars);
+        configs.sort();
+        assert_eq!(configs, fresh_configs(vars));
+    }
+    assert_eq!(generator.is_satisfiable(), Some(true));
+
+    Ok(())
+}
+
+#[test]
+fn test_generator_repeated() -> Result<(), String> {
+    let targets = [((1, true), Some(0.5)), ((1, false), Some(1.0))];
+    let mut generator = ConfigGenerator::new(complex_cnf(), &targets, 2);
+
+    let check = |configs: CnfFormula<u32>| {
+        // `!x1` is the most central: one configuration disables it, the next one enables it
+        assert_eq!(configs.len(), 2);
+        assert!(configs[0].contains(&(1, false)));
+        assert!(configs[1].contains(&(1, true)));
+        for config in &configs {
+            assert_eq!(config.len(), 4);
+            assert!(complex_cnf()
+                .iter()
+                .all(|clause| clause.iter().any(|lit| config.contains(lit))));
+        }
+    };
+    check(generator.generate());
+    // the formula loaded once is not corrupted by the previous queries
+    check(generator.generate());
+
+    // one configuration at most, covering the most central feature
+    let mut generator = ConfigGenerator::new(complex_cnf(), &targets, 1);
+    let configs = generator.generate();
+    assert_eq!(configs.len(), 1);
+    assert!(configs[0].contains(&(1, false)));
+
+    Ok(())
+}
+
+#[test]
+fn test_satisfiable_before_deadline() -> Result<(), String> {
+    let mut generator = ConfigSolver::default();
+    generator.add_cnf(complex_cnf());
+    let later = Instant::now() + Duration::from_secs(60);
+    assert_eq!(generator.is_satisfiable_before(later), Some(true));
+
+    // past the deadline the solver may be interrupted, but never answers wrongly
+    let earlier = Instant::now() - Duration::from_secs(1);
+    assert!(matches!(
+        generator.is_satisfiable_before(earlier),
+        None | Some(true)
+    ));
+    // the terminator is detached after each call
+    assert_eq!(generator.is_satisfiable(), Some(true));
+
+    generator.add_cnf(vec![vec![(0, true)], vec![(0, false)]]);
+    assert_eq!(generator.is_satisfiable_before(later), Some(false));
+
+    Ok(())
+}
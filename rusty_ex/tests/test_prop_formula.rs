@@ -338,3 +338,266 @@ fn test_to_cnf_repr_2() -> Result<(), String> {
 
     Ok(())
 }
+
+#[test]
+fn test_flatten() -> Result<(), String> {
+    use PropFormula::*;
+
+    // P & (Q & (R | (S | T)))
+    let mut prop_formula = And(vec![
+        Var(0),
+        And(vec![Var(1), Or(vec![Var(2), Or(vec![Var(3), Var(4)])])]),
+    ]);
+
+    // P & Q & (R | S | T)
+    prop_formula.flatten();
+
+    assert_eq!(
+        prop_formula,
+        And(vec![Var(0), Var(1), Or(vec![Var(2), Var(3), Var(4)])])
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_to_flat_cnf() -> Result<(), String> {
+    use PropFormula::*;
+
+    // (P | (Q & (R | S))) & (P & !Q)
+    let mut prop_formula = And(vec![
+        Or(vec![Var(0), And(vec![Var(1), Or(vec![Var(2), Var(3)])])]),
+        And(vec![Var(0), Not(bx!(Var(1)))]),
+    ]);
+
+    // (P | Q) & (P | R | S) & P & !Q
+    prop_formula.to_flat_cnf();
+    let (cnf, _): (CnfFormula<u32>, _) = prop_formula.to_cnf_repr(true);
+
+    assert_eq!(
+        cnf,
+        [
+            vec![(0, true)],
+            vec![(0, true), (1, true)],
+            vec![(0, true), (2, true), (3, true)],
+            vec![(1, false)]
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_fold_constants() -> Result<(), String> {
+    use PropFormula::*;
+
+    // P & (Q & true) & P & !false
+    let mut prop_formula = And(vec![
+        Var(0),
+        And(vec![Var(1), And(vec![])]),
+        Var(0),
+        Not(bx!(Or(vec![]))),
+    ]);
+    prop_formula.fold_constants();
+    assert_eq!(prop_formula, And(vec![Var(0), Var(1)]));
+
+    // P | (Q & false)
+    let mut prop_formula = Or(vec![Var(0), And(vec![Var(1), Or(vec![])])]);
+    prop_formula.fold_constants();
+    assert_eq!(prop_formula, Var(0));
+
+    // (P | true) & (false -> Q)
+    let mut prop_formula = And(vec![
+        Or(vec![Var(0), And(vec![])]),
+        Implies(bx!(Or(vec![])), bx!(Var(1))),
+    ]);
+    prop_formula.fold_constants();
+    assert_eq!(prop_formula, And(vec![]));
+
+    Ok(())
+}
+
+#[test]
+fn test_simplify() -> Result<(), String> {
+    use PropFormula::*;
+
+    // P & (P | Q) & (!P | R) & (R | S | !S)
+    let mut prop_formula = And(vec![
+        Var(0),
+        Or(vec![Var(0), Var(1)]),
+        Or(vec![Not(bx!(Var(0))), Var(2)]),
+        Or(vec![Var(2), Var(3), Not(bx!(Var(3)))]),
+    ]);
+    // P & R
+    prop_formula.simplify();
+    assert_eq!(prop_formula, And(vec![Var(0), Var(2)]));
+
+    // (P | Q) & (P | Q | R) & (P | Q) & (!P | Q)
+    let mut prop_formula = And(vec![
+        Or(vec![Var(0), Var(1)]),
+        Or(vec![Var(0), Var(1), Var(2)]),
+        Or(vec![Var(0), Var(1)]),
+        Or(vec![Not(bx!(Var(0))), Var(1)]),
+    ]);
+    // (P | Q) & (!P | Q): no model is lost
+    prop_formula.simplify();
+    assert_eq!(
+        prop_formula,
+        And(vec![
+            Or(vec![Var(0), Var(1)]),
+            Or(vec![Not(bx!(Var(0))), Var(1)]),
+        ])
+    );
+    // Q is pure: it is fixed and satisfies both clauses
+    prop_formula.eliminate_pure_literals();
+    assert_eq!(prop_formula, And(vec![Var(1)]));
+
+    Ok(())
+}
+
+#[test]
+fn test_simplify_unsatisfiable() -> Result<(), String> {
+    use PropFormula::*;
+
+    // P & (!P | Q) & !Q
+    let mut prop_formula = And(vec![
+        Var(0),
+        Or(vec![Not(bx!(Var(0))), Var(1)]),
+        Not(bx!(Var(1))),
+    ]);
+    prop_formula.simplify();
+    assert_eq!(prop_formula, And(vec![Or(vec![])]));
+
+    // a formula not in CNF is only folded
+    let mut prop_formula = Implies(bx!(And(vec![Var(0), And(vec![])])), bx!(Var(1)));
+    prop_formula.simplify();
+    assert_eq!(prop_formula, Implies(bx!(Var(0)), bx!(Var(1))));
+
+    Ok(())
+}
+
+fn var(name: &str) -> PropFormula<String> {
+    PropFormula::Var(name.to_string())
+}
+
+#[test]
+fn test_parse_cfg() -> Result<(), String> {
+    use PropFormula::*;
+
+    let prop_formula = "all(a, not(b), any(c, d))".parse::<PropFormula<String>>()?;
+    assert_eq!(
+        prop_formula,
+        And(vec![
+            var("a"),
+            Not(bx!(var("b"))),
+            Or(vec![var("c"), var("d")])
+        ])
+    );
+
+    // only the name of the options other than `feature` is kept
+    let prop_formula = r#"any(feature = "serde-json", target_os = "linux", all(), any())"#
+        .parse::<PropFormula<String>>()?;
+    assert_eq!(
+        prop_formula,
+        Or(vec![
+            var("serde-json"),
+            var("target_os"),
+            And(vec![]),
+            Or(vec![])
+        ])
+    );
+
+    assert!("not(a, b)".parse::<PropFormula<String>>().is_err());
+    assert!("all(a, b".parse::<PropFormula<String>>().is_err());
+    assert!("one(a)".parse::<PropFormula<String>>().is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_parse_infix() -> Result<(), String> {
+    use PropFormula::*;
+
+    let prop_formula = "a & !b -> c".parse::<PropFormula<String>>()?;
+    assert_eq!(
+        prop_formula,
+        Implies(bx!(And(vec![var("a"), Not(bx!(var("b")))])), bx!(var("c")))
+    );
+
+    // `->` is right associative and binds more than `<->`
+    let prop_formula = "a->b->c <-> d | e & f".parse::<PropFormula<String>>()?;
+    assert_eq!(
+        prop_formula,
+        Iff(
+            bx!(Implies(
+                bx!(var("a")),
+                bx!(Implies(bx!(var("b")), bx!(var("c"))))
+            )),
+            bx!(Or(vec![var("d"), And(vec![var("e"), var("f")])]))
+        )
+    );
+
+    // the syntaxes can be mixed, names that are not identifiers are quoted
+    let prop_formula = r#"all(a, b) | !"a.b" & true"#.parse::<PropFormula<String>>()?;
+    assert_eq!(
+        prop_formula,
+        Or(vec![
+            And(vec![var("a"), var("b")]),
+            And(vec![Not(bx!(var("a.b"))), And(vec![])])
+        ])
+    );
+
+    assert!("a &".parse::<PropFormula<String>>().is_err());
+    assert!("a b".parse::<PropFormula<String>>().is_err());
+    assert!("(a | b))".parse::<PropFormula<String>>().is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_display_round_trip() -> Result<(), String> {
+    use PropFormula::*;
+
+    let prop_formula = Iff(
+        bx!(Implies(
+            bx!(And(vec![var("a"), Or(vec![var("b"), Not(bx!(var("c")))])])),
+            bx!(Implies(bx!(var("d")), bx!(Or(vec![])))),
+        )),
+        bx!(And(vec![
+            And(vec![var("serde-json"), var("a.b")]),
+            Not(bx!(Iff(bx!(var("e")), bx!(And(vec![])))))
+        ])),
+    );
+    assert_eq!(
+        prop_formula.to_string(),
+        r#"a & (b | !c) -> d -> false <-> (serde-json & "a.b") & !(e <-> true)"#
+    );
+    assert_eq!(
+        prop_formula.to_string().parse::<PropFormula<String>>()?,
+        prop_formula
+    );
+
+    // `->` and `<->` are written with `any`, `all` and `not`
+    let prop_formula = And(vec![
+        var("a"),
+        Not(bx!(Or(vec![var("b"), var("a.b")]))),
+        Implies(bx!(var("c")), bx!(var("d"))),
+    ]);
+    assert_eq!(
+        prop_formula.cfg().to_string(),
+        r#"all(a, not(any(b, feature = "a.b")), any(not(c), d))"#
+    );
+    assert_eq!(
+        prop_formula
+            .cfg()
+            .to_string()
+            .parse::<PropFormula<String>>()?,
+        And(vec![
+            var("a"),
+            Not(bx!(Or(vec![var("b"), var("a.b")]))),
+            Or(vec![Not(bx!(var("c"))), var("d")]),
+        ])
+    );
+
+    Ok(())
+}
@@ -198,3 +198,50 @@ fn test_all_in_any() -> Result<(), String> {
 
     Ok(())
 }
+
+// =============================================
+// ==================== NOT ====================
+// =============================================
+
+// De Morgan: `not(all(a, b))` is `any(not(a), not(b))` and `not(any(a, b))` is
+// `all(not(a), not(b))`. The negation used to keep the predicate, giving the edges of
+// `all(!a, !b)` (0.50) to `not(all(a, b))` and the ones of `any(!a, !b)` (1.00) to
+// `not(any(a, b))`
+
+#[test]
+fn test_all_in_not() -> Result<(), String> {
+    let snippet = &std::fs::read_to_string(format!("{FOLDER}/all_in_not.rs")).unwrap();
+    let (output, _) = run_with_cargo_bin_and_snippet(snippet, &["--print-features-graph"])?;
+
+    assert!(output.contains("0 [ label=\"i0: __GLOBAL__"));
+    assert!(output.contains("2 [ label=\"i2: !a"));
+    assert!(output.contains("3 [ label=\"i3: !b"));
+    assert!(output.contains("4 [ label=\"i4: c"));
+    assert!(output.contains("2 -> 0 [ label=\"1.00"));
+    assert!(output.contains("3 -> 0 [ label=\"1.00"));
+    assert!(!output.contains("2 -> 0 [ label=\"0.50"));
+    assert!(!output.contains("3 -> 0 [ label=\"0.50"));
+    assert!(output.contains("4 -> 2 [ label=\"1.00"));
+    assert!(output.contains("4 -> 3 [ label=\"1.00"));
+
+    Ok(())
+}
+
+#[test]
+fn test_any_in_not() -> Result<(), String> {
+    let snippet = &std::fs::read_to_string(format!("{FOLDER}/any_in_not.rs")).unwrap();
+    let (output, _) = run_with_cargo_bin_and_snippet(snippet, &["--print-features-graph"])?;
+
+    assert!(output.contains("0 [ label=\"i0: __GLOBAL__"));
+    assert!(output.contains("2 [ label=\"i2: !a"));
+    assert!(output.contains("3 [ label=\"i3: !b"));
+    assert!(output.contains("4 [ label=\"i4: c"));
+    assert!(output.contains("2 -> 0 [ label=\"0.50"));
+    assert!(output.contains("3 -> 0 [ label=\"0.50"));
+    assert!(!output.contains("2 -> 0 [ label=\"1.00"));
+    assert!(!output.contains("3 -> 0 [ label=\"1.00"));
+    assert!(output.contains("4 -> 2 [ label=\"1.00"));
+    assert!(output.contains("4 -> 3 [ label=\"1.00"));
+
+    Ok(())
+}
@@ -139,10 +139,14 @@ fn test_mutual_recursive() -> Result<(), String> {
     let (output, _) = run_with_cargo_bin_and_snippet(snippet, &["--print-terms-tree"])?;
 
     assert!(same_line(&output, vec!["Fn", "'main'", "w9.00"]));
-    assert!(same_line(&output, vec!["Fn", "'a'", "9.00"]));
+    assert!(same_line(&output, vec!["Fn", "'a'", "w8.00"]));
 
     assert!(same_line(&output, vec!["Reference(Call)->a", "w9.00"]));
-    assert!(same_line(&output, vec!["Reference(Call)->main", "w9.00"]));
+    // resolved with recovery mode: default recovery weight + children (see `test_recovery_in_loop`)
+    assert!(same_line(
+        &output,
+        vec!["Reference(Call)->main", "w8.00", "recovered"]
+    ));
 
     Ok(())
 }
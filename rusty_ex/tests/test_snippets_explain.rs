@@ -0,0 +1,52 @@
+mod utils;
+
+use utils::run_with_cargo_bin_and_snippet;
+
+const FOLDER: &str = "tests/snippets/explain";
+
+// =============================================
+
+#[test]
+fn test_explain_feature() -> Result<(), String> {
+    let snippet = &std::fs::read_to_string(format!("{FOLDER}/nested.rs")).unwrap();
+    let (output, _) = run_with_cargo_bin_and_snippet(snippet, &["--explain-feature", "a"])?;
+
+    assert!(output.contains("feature: a (feature)"));
+    assert!(output.contains("artifacts: 3"));
+    assert!(output.contains("  'outer' [a] w4.00 at src/lib.rs:2:1"));
+    assert!(output.contains("  'other' [a] w0.00 at src/lib.rs:14:1"));
+    assert!(output.contains("  '-' [all(a, d)] w2.00 at src/lib.rs:10:5"));
+    assert!(output.contains("complex features: a, all(a, d)"));
+    assert!(output.contains("parents: __GLOBAL__ (2.00)\n"));
+    assert!(output.contains("children: b (1.00), d (0.50)\n"));
+    // `!c` is gated through `b`
+    assert!(output.contains("gated features: !c, b, d\n"));
+    assert!(output.contains("refined weight: 1.00"));
+    assert!(output.contains("katz 1"));
+
+    Ok(())
+}
+
+#[test]
+fn test_explain_negated_feature() -> Result<(), String> {
+    let snippet = &std::fs::read_to_string(format!("{FOLDER}/nested.rs")).unwrap();
+    let (output, _) = run_with_cargo_bin_and_snippet(snippet, &["--explain-feature", "!c"])?;
+
+    assert!(output.contains("feature: !c (feature)"));
+    assert!(output.contains("  '-' [!c] w2.00 at src/lib.rs:6:9"));
+    assert!(output.contains("parents: b (1.00)\n"));
+    assert!(output.contains("children: \n"));
+    assert!(output.contains("gated features: \n"));
+
+    Ok(())
+}
+
+#[test]
+fn test_explain_unknown_feature() -> Result<(), String> {
+    let snippet = &std::fs::read_to_string(format!("{FOLDER}/nested.rs")).unwrap();
+    let (output, _) = run_with_cargo_bin_and_snippet(snippet, &["--explain-feature", "zz"])?;
+
+    assert!(!output.contains("feature: zz"));
+
+    Ok(())
+}
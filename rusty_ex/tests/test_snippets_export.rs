@@ -0,0 +1,96 @@
+mod utils;
+
+use utils::run_with_cargo_bin_and_snippet;
+
+const FOLDER: &str = "tests/snippets/export";
+
+// =============================================
+
+#[test]
+fn test_export_graphml() -> Result<(), String> {
+    let snippet = &std::fs::read_to_string(format!("{FOLDER}/nested.rs")).unwrap();
+    let (output, _) = run_with_cargo_bin_and_snippet(
+        snippet,
+        &["--graph-format", "graphml", "--print-features-graph"],
+    )?;
+
+    assert!(output.contains(r#"<graphml xmlns="http://graphml.graphdrawing.org/xmlns">"#));
+    assert!(output.contains(r#"<key id="d3" for="node" attr.name="weight" attr.type="double"/>"#));
+    assert!(output.contains(r#"<key id="d6" for="node" attr.name="rank" attr.type="long"/>"#));
+    assert!(output.contains(r#"<key id="e0" for="edge" attr.name="weight" attr.type="double"/>"#));
+    assert!(output.contains(r#"<graph id="G" edgedefault="directed">"#));
+    assert!(output.contains(
+        r#"<node id="n4"><data key="d0">c</data><data key="d1">true</data><data key="d2">!c</data>"#
+    ));
+    assert!(output.contains(r#"<data key="d2">a; all(a, d)</data>"#));
+
+    Ok(())
+}
+
+#[test]
+fn test_export_gexf() -> Result<(), String> {
+    let snippet = &std::fs::read_to_string(format!("{FOLDER}/nested.rs")).unwrap();
+    let (output, _) = run_with_cargo_bin_and_snippet(
+        snippet,
+        &["--graph-format", "gexf", "--print-artifacts-tree"],
+    )?;
+
+    assert!(output.contains(r#"<gexf xmlns="http://gexf.net/1.3" version="1.3">"#));
+    assert!(output.contains(r#"<attribute id="3" title="weight" type="double"/>"#));
+    assert!(output.contains(r#"<attribute id="6" title="span_lo_line" type="long"/>"#));
+    assert!(output.contains(r#"<node id="2" label="inner">"#));
+    assert!(output.contains(r#"<attvalue for="2" value="b"/>"#));
+
+    Ok(())
+}
+
+#[test]
+fn test_export_node_link() -> Result<(), String> {
+    let snippet = &std::fs::read_to_string(format!("{FOLDER}/nested.rs")).unwrap();
+    let (output, _) = run_with_cargo_bin_and_snippet(
+        snippet,
+        &["--graph-format", "json", "--print-artifacts-tree"],
+    )?;
+
+    let graph: serde_json::Value = serde_json::from_str(output.trim()).unwrap();
+    assert_eq!(graph["directed"], true);
+    assert_eq!(graph["multigraph"], false);
+    assert_eq!(graph["links"].as_array().unwrap().len(), 5);
+
+    let outer = graph["nodes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|node| node["ident"] == "outer")
+        .unwrap();
+    assert_eq!(outer["complex_feature"], "a");
+    assert_eq!(outer["weight"].as_f64(), Some(4.0));
+    assert_eq!(outer["span_file"], "src/lib.rs");
+    assert_eq!(outer["span_lo_line"].as_u64(), Some(2));
+    assert_eq!(outer["span_hi_line"].as_u64(), Some(11));
+
+    Ok(())
+}
+
+#[test]
+fn test_export_multigraph() -> Result<(), String> {
+    let snippet = &std::fs::read_to_string(format!("{FOLDER}/nested.rs")).unwrap();
+    let (output, _) = run_with_cargo_bin_and_snippet(
+        snippet,
+        &["--graph-format", "json", "--print-features-multigraph"],
+    )?;
+
+    let graph: serde_json::Value = serde_json::from_str(output.trim()).unwrap();
+    assert_eq!(graph["multigraph"], true);
+    let a = graph["nodes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|node| node["name"] == "a")
+        .unwrap();
+    assert_eq!(a["not"], false);
+    assert_eq!(a["rank"].as_u64(), Some(1));
+    assert!(a["katz"].is_f64());
+
+    Ok(())
+}
@@ -0,0 +1,100 @@
+mod utils;
+
+use utils::run_with_cargo_bin_and_snippet;
+
+const FOLDER: &str = "tests/snippets/interactions";
+
+// =============================================
+
+#[test]
+fn test_interactions() -> Result<(), String> {
+    let snippet = &std::fs::read_to_string(format!("{FOLDER}/combinations.rs")).unwrap();
+    let (output, _) = run_with_cargo_bin_and_snippet(snippet, &["--print-interactions"])?;
+
+    let interactions: serde_json::Value = serde_json::from_str(output.trim()).unwrap();
+    let interactions = interactions["interactions"].as_array().unwrap();
+    let find = |kind: &str, features: &[(&str, bool)]| {
+        interactions.iter().find(|interaction| {
+            interaction["kind"] == kind
+                && interaction["features"]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|f| (f["name"].as_str().unwrap(), f["not"].as_bool().unwrap()))
+                    .eq(features.iter().copied())
+        })
+    };
+
+    let conjunction = find("Conjunction", &[("b", false), ("c", true)]).unwrap();
+    assert_eq!(conjunction["weight"].as_f64(), Some(2.0));
+    assert_eq!(conjunction["occurrences"].as_array().unwrap().len(), 2);
+    assert_eq!(conjunction["occurrences"][1]["ident"], "both");
+    assert_eq!(conjunction["occurrences"][1]["span"]["lo_line"], 14);
+
+    let disjunction = find("Disjunction", &[("b", false), ("c", false)]).unwrap();
+    assert_eq!(
+        disjunction["occurrences"][0]["complex_feature"],
+        "any(b, c)"
+    );
+
+    // the enclosing feature comes first
+    assert!(find("Nesting", &[("a", false), ("b", false)]).is_some());
+    assert!(find("Nesting", &[("a", false), ("c", true)]).is_some());
+    assert!(find("Nesting", &[("b", false), ("a", false)]).is_none());
+
+    let negation = find("Negation", &[("a", false), ("a", true)]).unwrap();
+    assert_eq!(negation["occurrences"][0]["span"]["lo_line"], 7);
+    assert_eq!(interactions.len(), 6);
+
+    Ok(())
+}
+
+#[test]
+fn test_interactions_matrix() -> Result<(), String> {
+    let snippet = &std::fs::read_to_string(format!("{FOLDER}/combinations.rs")).unwrap();
+    let (output, _) = run_with_cargo_bin_and_snippet(snippet, &["--print-interactions-matrix"])?;
+
+    assert!(output.contains(
+        ",!a,!c,a,b,c\n\
+         !a,1,0,1,0,0\n\
+         !c,0,2,1,2,0\n\
+         a,1,1,1,1,0\n\
+         b,0,2,1,3,1\n\
+         c,0,0,0,1,1\n"
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_nested_interactions() -> Result<(), String> {
+    let snippet = &std::fs::read_to_string(format!("{FOLDER}/nested.rs")).unwrap();
+    let (output, _) = run_with_cargo_bin_and_snippet(snippet, &["--print-interactions"])?;
+
+    let interactions: serde_json::Value = serde_json::from_str(output.trim()).unwrap();
+    let interactions = interactions["interactions"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|interaction| {
+            let features = interaction["features"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|f| f["name"].as_str().unwrap().to_string())
+                .collect::<Vec<_>>();
+            (interaction["kind"].as_str().unwrap().to_string(), features)
+        })
+        .collect::<Vec<_>>();
+
+    // `a` is in a conjunction with the disjunction of `b` and `c`, not with `b` and `c`
+    assert_eq!(
+        interactions,
+        vec![(
+            "Disjunction".to_string(),
+            vec!["b".to_string(), "c".to_string()]
+        )]
+    );
+
+    Ok(())
+}
@@ -0,0 +1,66 @@
+mod utils;
+
+use utils::run_with_cargo_bin_and_snippet;
+use utils::run_with_cargo_bin_and_snippet_with_stderr;
+use utils::same_line;
+
+const FOLDER: &str = "tests/snippets/malformed_cfg";
+
+// =============================================
+
+#[test]
+fn test_multiple_features() -> Result<(), String> {
+    let snippet = &std::fs::read_to_string(format!("{FOLDER}/multiple_features.rs")).unwrap();
+    let (output, _) = run_with_cargo_bin_and_snippet(snippet, &["--print-features-graph"])?;
+
+    // the malformed attribute is ignored, the rest of the crate is analysed
+    assert!(output.contains("0 [ label=\"i0: __GLOBAL__"));
+    assert!(output.contains("2 [ label=\"i2: c"));
+    assert!(!output.contains("i3:"));
+
+    assert!(output.contains("2 -> 0 [ label=\"1.00"));
+
+    Ok(())
+}
+
+#[test]
+fn test_feature_without_value() -> Result<(), String> {
+    let snippet = &std::fs::read_to_string(format!("{FOLDER}/feature_without_value.rs")).unwrap();
+    let (output, _) = run_with_cargo_bin_and_snippet(snippet, &["--print-features-graph"])?;
+
+    assert!(output.contains("0 [ label=\"i0: __GLOBAL__"));
+    assert!(output.contains("2 [ label=\"i2: b"));
+    assert!(!output.contains("i3:"));
+
+    Ok(())
+}
+
+#[test]
+fn test_multiple_attributes() -> Result<(), String> {
+    let snippet = &std::fs::read_to_string(format!("{FOLDER}/multiple_attributes.rs")).unwrap();
+    let (output, _) = run_with_cargo_bin_and_snippet(snippet, &["--print-features-graph"])?;
+
+    // multiple attributes on the same node must all be satisfied
+    assert!(same_line(&output, vec!["i2: a", "all(a, b)"]));
+    assert!(same_line(&output, vec!["i3: b", "all(a, b)"]));
+
+    assert!(output.contains("2 -> 0 [ label=\"0.50"));
+    assert!(output.contains("3 -> 0 [ label=\"0.50"));
+
+    Ok(())
+}
+
+#[test]
+fn test_diagnostic_on_stderr() -> Result<(), String> {
+    let snippet = &std::fs::read_to_string(format!("{FOLDER}/multiple_features.rs")).unwrap();
+    let (output, stderr) =
+        run_with_cargo_bin_and_snippet_with_stderr(snippet, &["--print-features-graph"])?;
+
+    // the diagnostic points at the attribute and shows the original source, not the output
+    assert!(stderr.contains("warning: malformed `cfg` attribute ignored"));
+    assert!(stderr.contains("  --> src/lib.rs:1:1"));
+    assert!(stderr.contains("   | #[cfg(feature = \"a\", feature = \"b\")]"));
+    assert!(!output.contains("malformed"));
+
+    Ok(())
+}
@@ -0,0 +1,137 @@
+mod utils;
+
+use utils::run_with_cargo_bin_and_snippet;
+
+const FOLDER: &str = "tests/snippets/min_configurations";
+
+fn enabled(cover: &serde_json::Value) -> Vec<Vec<&str>> {
+    cover["configurations"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|configuration| {
+            configuration["enabled"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|feature| feature.as_str().unwrap())
+                .collect()
+        })
+        .collect()
+}
+
+// =============================================
+
+#[test]
+fn test_min_configurations() -> Result<(), String> {
+    let snippet = &std::fs::read_to_string(format!("{FOLDER}/exclusive.rs")).unwrap();
+    let (output, _) = run_with_cargo_bin_and_snippet(snippet, &["--min-configurations"])?;
+
+    let cover: serde_json::Value = serde_json::from_str(output.trim()).unwrap();
+    // `a` and `b` are mutually exclusive: two configurations are needed
+    assert_eq!(cover["optimal"], true);
+    assert_eq!(
+        enabled(&cover),
+        vec![vec!["a", "c"], vec!["b"]],
+        "the features not needed are disabled"
+    );
+    assert_eq!(
+        cover["configurations"][1]["name"],
+        "--no-default-features --features b"
+    );
+
+    assert_eq!(cover["artifacts"], 4);
+    let unsatisfiable = cover["unsatisfiable"].as_array().unwrap();
+    assert_eq!(unsatisfiable.len(), 1);
+    assert_eq!(unsatisfiable[0]["ident"], "a_and_b");
+
+    Ok(())
+}
+
+#[test]
+fn test_min_configurations_weighted() -> Result<(), String> {
+    let snippet = &std::fs::read_to_string(format!("{FOLDER}/exclusive.rs")).unwrap();
+    let (output, _) = run_with_cargo_bin_and_snippet(
+        snippet,
+        &["--min-configurations", "--min-configurations-weighted"],
+    )?;
+
+    let cover: serde_json::Value = serde_json::from_str(output.trim()).unwrap();
+    assert_eq!(cover["optimal"], true);
+    // the configuration compiling the heaviest artifact first
+    assert_eq!(enabled(&cover), vec![vec!["b"], vec!["a", "c"]]);
+    assert_eq!(cover["configurations"][0]["weight"], cover["weight"]);
+
+    Ok(())
+}
+
+#[test]
+fn test_min_configurations_other_options() -> Result<(), String> {
+    let snippet = &std::fs::read_to_string(format!("{FOLDER}/options.rs")).unwrap();
+    let (output, _) = run_with_cargo_bin_and_snippet(snippet, &["--min-configurations"])?;
+
+    let cover: serde_json::Value = serde_json::from_str(output.trim()).unwrap();
+    // `unix` cannot be set by the cargo arguments, it is disabled
+    assert_eq!(enabled(&cover), vec![vec!["a", "b"]]);
+    assert_eq!(
+        cover["configurations"][0]["name"],
+        "--no-default-features --features a,b"
+    );
+    let unsatisfiable = cover["unsatisfiable"].as_array().unwrap();
+    assert_eq!(unsatisfiable.len(), 1);
+    assert_eq!(unsatisfiable[0]["ident"], "unix");
+    // the nested artifacts are not counted twice
+    assert_eq!(cover["configurations"][0]["weight"], cover["weight"]);
+
+    Ok(())
+}
+
+#[test]
+fn test_min_configurations_no_time() -> Result<(), String> {
+    let snippet = &std::fs::read_to_string(format!("{FOLDER}/exclusive.rs")).unwrap();
+    let (output, _) = run_with_cargo_bin_and_snippet(
+        snippet,
+        &[
+            "--min-configurations",
+            "--min-configurations-time-limit",
+            "0",
+        ],
+    )?;
+
+    let cover: serde_json::Value = serde_json::from_str(output.trim()).unwrap();
+    // every artifact is still covered, by valid configurations
+    assert_eq!(cover["optimal"], false);
+    let enabled = enabled(&cover);
+    assert!(enabled.len() >= 2);
+    assert!(enabled
+        .iter()
+        .all(|features| !(features.contains(&"a") && features.contains(&"b"))));
+    let covered = cover["configurations"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|configuration| configuration["artifacts"].as_u64().unwrap())
+        .sum::<u64>();
+    assert!(covered >= 4);
+
+    Ok(())
+}
+
+#[test]
+fn test_min_configurations_invalid_time_limit() -> Result<(), String> {
+    let snippet = &std::fs::read_to_string(format!("{FOLDER}/exclusive.rs")).unwrap();
+    for time_limit in ["inf", "NaN", "-1", "1e300"] {
+        let (output, _) = run_with_cargo_bin_and_snippet(
+            snippet,
+            &[
+                "--min-configurations",
+                "--min-configurations-time-limit",
+                time_limit,
+            ],
+        )?;
+        // rejected by the parser of the arguments, before the analysis
+        assert!(output.trim().is_empty(), "{time_limit}: {output}");
+    }
+
+    Ok(())
+}
@@ -0,0 +1,40 @@
+mod utils;
+
+use utils::run_with_cargo_bin_and_snippet;
+use utils::same_line;
+
+const FOLDER: &str = "tests/snippets/mir";
+
+// =============================================
+
+#[test]
+fn test_ast_weights() -> Result<(), String> {
+    let snippet = &std::fs::read_to_string(format!("{FOLDER}/functions.rs")).unwrap();
+    let (output, _) = run_with_cargo_bin_and_snippet(snippet, &["--print-artifacts-tree"])?;
+
+    assert!(same_line(&output, vec!["'straight'", "[!a]", "w3.00"]));
+    assert!(same_line(&output, vec!["'branching'", "[!b]", "w18.00"]));
+    assert!(same_line(&output, vec!["[!c]", "w21.00"]));
+    assert!(same_line(&output, vec!["'disabled'", "[d]", "w6.00"]));
+
+    Ok(())
+}
+
+#[test]
+fn test_mir_weights() -> Result<(), String> {
+    let snippet = &std::fs::read_to_string(format!("{FOLDER}/functions.rs")).unwrap();
+    let (output, _) = run_with_cargo_bin_and_snippet(
+        snippet,
+        &["--print-artifacts-tree", "--weight-mode", "mir"],
+    )?;
+
+    // basic blocks + statements + calls
+    assert!(same_line(&output, vec!["'straight'", "[!a]", "w4.00"]));
+    assert!(same_line(&output, vec!["'branching'", "[!b]", "w33.00"]));
+    // share of the MIR of `main`
+    assert!(same_line(&output, vec!["[!c]", "w3.00"]));
+    // not enabled in the current configuration: no MIR
+    assert!(same_line(&output, vec!["'disabled'", "[d]", "w0.00"]));
+
+    Ok(())
+}
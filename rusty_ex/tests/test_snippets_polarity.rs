@@ -0,0 +1,128 @@
+mod utils;
+
+use utils::run_with_cargo_bin_and_snippet;
+
+const FOLDER: &str = "tests/snippets/polarity";
+
+// =============================================
+
+#[test]
+fn test_polarity_graph() -> Result<(), String> {
+    let snippet = &std::fs::read_to_string(format!("{FOLDER}/negated.rs")).unwrap();
+    let (output, _) = run_with_cargo_bin_and_snippet(
+        snippet,
+        &["--graph-format", "json", "--print-polarity-graph"],
+    )?;
+
+    let graph: serde_json::Value = serde_json::from_str(output.trim()).unwrap();
+    assert_eq!(graph["multigraph"], true);
+
+    // one node for each real feature, global and dummy included
+    let nodes = graph["nodes"].as_array().unwrap();
+    let names = nodes
+        .iter()
+        .map(|node| node["name"].as_str().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec!["__GLOBAL__", "__DUMMY__", "a", "b", "c"]);
+    assert_eq!(nodes[2]["positive"], true);
+    assert_eq!(nodes[2]["negative"], true);
+    assert_eq!(nodes[4]["negative"], false);
+
+    let polarities = |source: u64, target: u64| {
+        let mut polarities = graph["links"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter(|link| link["source"] == source && link["target"] == target)
+            .map(|link| {
+                format!(
+                    "{}{}",
+                    link["source_polarity"].as_str().unwrap(),
+                    link["target_polarity"].as_str().unwrap()
+                )
+            })
+            .collect::<Vec<_>>();
+        polarities.sort();
+        polarities
+    };
+    // `!b` inside `a` and `b` inside `!a`
+    assert_eq!(polarities(3, 2), vec!["+-", "-+"]);
+    assert_eq!(polarities(2, 0), vec!["++", "-+"]);
+    // a single edge from the dummy node, even if both polarities are used
+    assert_eq!(polarities(1, 3), vec!["+-"]);
+
+    Ok(())
+}
+
+#[test]
+fn test_unify_polarity() -> Result<(), String> {
+    let snippet = &std::fs::read_to_string(format!("{FOLDER}/negated.rs")).unwrap();
+    let (output, _) = run_with_cargo_bin_and_snippet(
+        snippet,
+        &["--unify-polarity", "--print-features-centrality"],
+    )?;
+
+    let features: serde_json::Value = serde_json::from_str(output.trim()).unwrap();
+    let features = features.as_array().unwrap();
+    let mut names = features
+        .iter()
+        .map(|feature| feature["name"].as_str().unwrap())
+        .collect::<Vec<_>>();
+    names.sort();
+    assert_eq!(names, vec!["a", "b", "c"]);
+    assert!(features.iter().all(|feature| feature["not"] == false));
+    assert!(features.iter().all(|feature| feature["polarity"].is_null()));
+
+    Ok(())
+}
+
+#[test]
+fn test_polarity_split() -> Result<(), String> {
+    let snippet = &std::fs::read_to_string(format!("{FOLDER}/negated.rs")).unwrap();
+    let (output, _) = run_with_cargo_bin_and_snippet(
+        snippet,
+        &[
+            "--unify-polarity",
+            "--polarity-split",
+            "--rank-by",
+            "in-degree",
+            "--print-features-centrality",
+        ],
+    )?;
+
+    let (features_graph_output, _) = run_with_cargo_bin_and_snippet(
+        snippet,
+        &["--rank-by", "in-degree", "--print-features-centrality"],
+    )?;
+
+    let features: serde_json::Value = serde_json::from_str(output.trim()).unwrap();
+    let features_graph: serde_json::Value =
+        serde_json::from_str(features_graph_output.trim()).unwrap();
+    let find = |features: &serde_json::Value, name: &str, not: bool| {
+        features
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|feature| feature["name"] == name && feature["not"] == not)
+            .cloned()
+    };
+    // the values of the feature and of its negation in the Features Graph
+    for name in ["a", "b", "c"] {
+        let feature = find(&features, name, false).unwrap();
+        for (polarity, not) in [("positive", false), ("negative", true)] {
+            assert_eq!(
+                feature["polarity"][polarity].as_f64(),
+                find(&features_graph, name, not).map(|f| f["in_degree"].as_f64().unwrap()),
+            );
+        }
+    }
+    // only the artifact of `!b` has a weight
+    let b = find(&features, "b", false).unwrap();
+    assert_eq!(b["polarity"]["positive"].as_f64(), Some(0.0));
+    assert!(b["polarity"]["negative"].as_f64().unwrap() > 0.0);
+    // `!c` is not used
+    let c = find(&features, "c", false).unwrap();
+    assert!(c["polarity"]["negative"].is_null());
+
+    Ok(())
+}
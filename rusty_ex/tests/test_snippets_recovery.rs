@@ -0,0 +1,161 @@
+#![feature(rustc_private)]
+
+mod utils;
+
+use rusty_ex::weights::{RecoveryWeight, DEFAULT_RECOVERY_WEIGHT};
+use utils::run_with_cargo_bin_and_snippet;
+use utils::same_line;
+
+const FOLDER: &str = "tests/snippets/recovery";
+
+/// Absolute path of a dependency weights file, the plugin runs in the temporary workspace
+fn dependency_weights_path(file: &str) -> String {
+    std::env::current_dir()
+        .unwrap()
+        .join(FOLDER)
+        .join(file)
+        .display()
+        .to_string()
+}
+
+// =============================================
+
+#[test]
+fn test_recovery_weight_strategies() {
+    let weights = [0.0, 1.0, 3.0, 8.0];
+
+    assert_eq!(RecoveryWeight::Constant(2.0).compute(&weights), 2.0);
+    assert_eq!(RecoveryWeight::Mean.compute(&weights), 3.0);
+    assert_eq!(RecoveryWeight::Median.compute(&weights), 2.0);
+    assert_eq!(RecoveryWeight::Percentile(100.0).compute(&weights), 8.0);
+    assert_eq!(RecoveryWeight::Percentile(0.0).compute(&weights), 0.0);
+    // without resolved functions the default weight is used
+    assert_eq!(RecoveryWeight::Mean.compute(&[]), DEFAULT_RECOVERY_WEIGHT);
+}
+
+#[test]
+fn test_recovery_weight_parse() {
+    assert_eq!("mean".parse(), Ok(RecoveryWeight::Mean));
+    assert_eq!("median".parse(), Ok(RecoveryWeight::Median));
+    assert_eq!("p90".parse(), Ok(RecoveryWeight::Percentile(90.0)));
+    assert_eq!("2.5".parse(), Ok(RecoveryWeight::Constant(2.5)));
+    assert!("p101".parse::<RecoveryWeight>().is_err());
+    assert!("pnan".parse::<RecoveryWeight>().is_err());
+    assert!("nan".parse::<RecoveryWeight>().is_err());
+    assert!("inf".parse::<RecoveryWeight>().is_err());
+    assert!("-1".parse::<RecoveryWeight>().is_err());
+    assert!("average".parse::<RecoveryWeight>().is_err());
+}
+
+#[test]
+fn test_default_recovery_weight() -> Result<(), String> {
+    let snippet = &std::fs::read_to_string(format!("{FOLDER}/external.rs")).unwrap();
+    let (output, _) = run_with_cargo_bin_and_snippet(snippet, &["--print-terms-tree"])?;
+
+    // recovery weight + children
+    assert!(same_line(
+        &output,
+        vec!["Reference(Call)->std::process::exit", "w9.00", "recovered"]
+    ));
+    // only the unresolved terms are recovered
+    assert!(same_line(&output, vec!["Fn", "'mixed'", "w11.00"]));
+    assert!(!same_line(&output, vec!["Fn", "'mixed'", "recovered"]));
+
+    Ok(())
+}
+
+#[test]
+fn test_recovery_in_loop() -> Result<(), String> {
+    let snippet =
+        &std::fs::read_to_string("tests/snippets/call_weights/mutual_recursive.rs").unwrap();
+    let (output, _) =
+        run_with_cargo_bin_and_snippet(snippet, &["--print-terms-tree", "--recovery-weight", "3"])?;
+
+    // `main` -> `a` -> `main` cannot be resolved: the call closing the loop gets the recovery
+    // weight + children, like any unresolved call
+    assert!(same_line(
+        &output,
+        vec!["Reference(Call)->main", "w4.00", "recovered"]
+    ));
+    // the ancestors are weighted normally, so `main` weighs more than the `a` it calls
+    assert!(same_line(&output, vec!["Fn", "'a'", "w4.00"]));
+    assert!(same_line(&output, vec!["Reference(Call)->a", "w5.00"]));
+    assert!(!same_line(&output, vec!["Reference(Call)->a", "recovered"]));
+    assert!(same_line(&output, vec!["Fn", "'main'", "w5.00"]));
+
+    Ok(())
+}
+
+#[test]
+fn test_data_driven_recovery_weight() -> Result<(), String> {
+    let snippet = &std::fs::read_to_string(format!("{FOLDER}/external.rs")).unwrap();
+
+    // functions weights: 0 (main), 1, 3, 8
+    let (output, _) = run_with_cargo_bin_and_snippet(
+        snippet,
+        &["--print-terms-tree", "--recovery-weight", "mean"],
+    )?;
+    assert!(same_line(
+        &output,
+        vec!["Reference(Call)->std::process::exit", "w5.00"]
+    ));
+
+    let (output, _) = run_with_cargo_bin_and_snippet(
+        snippet,
+        &["--print-terms-tree", "--recovery-weight", "median"],
+    )?;
+    assert!(same_line(
+        &output,
+        vec!["Reference(Call)->std::process::exit", "w4.00"]
+    ));
+
+    let (output, _) = run_with_cargo_bin_and_snippet(
+        snippet,
+        &["--print-terms-tree", "--recovery-weight", "p100"],
+    )?;
+    assert!(same_line(
+        &output,
+        vec!["Reference(Call)->std::process::exit", "w10.00"]
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_dependency_weights() -> Result<(), String> {
+    let snippet = &std::fs::read_to_string(format!("{FOLDER}/external.rs")).unwrap();
+    let path = dependency_weights_path("dependencies.toml");
+    let (output, _) = run_with_cargo_bin_and_snippet(
+        snippet,
+        &["--print-terms-tree", "--dependency-weights", &path],
+    )?;
+
+    assert!(same_line(
+        &output,
+        vec!["Reference(Call)->std::process::exit", "w4.50", "recovered"]
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_estimated_weights() -> Result<(), String> {
+    let snippet = &std::fs::read_to_string(format!("{FOLDER}/external.rs")).unwrap();
+    let (output, _) = run_with_cargo_bin_and_snippet(snippet, &["--print-estimated-weights"])?;
+
+    let estimated: serde_json::Value = serde_json::from_str(&output).unwrap();
+    let estimated = estimated.as_array().unwrap();
+    assert_eq!(estimated.len(), 2);
+
+    // only the recovered weight is estimated, not the children of the call
+    assert_eq!(estimated[0]["name"], "a");
+    assert_eq!(estimated[0]["weight"], 9.0);
+    assert_eq!(estimated[0]["estimated_weight"], 7.0);
+
+    // the weight of the resolved call is measured
+    assert_eq!(estimated[1]["name"], "b");
+    assert_eq!(estimated[1]["weight"], 11.0);
+    assert_eq!(estimated[1]["estimated_weight"], 7.0);
+
+    Ok(())
+}
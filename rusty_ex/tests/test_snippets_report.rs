@@ -0,0 +1,29 @@
+mod utils;
+
+use utils::run_with_cargo_bin_and_snippet;
+
+const FOLDER: &str = "tests/snippets/report";
+
+// =============================================
+
+#[test]
+fn test_html_report() -> Result<(), String> {
+    let snippet = &std::fs::read_to_string(format!("{FOLDER}/features.rs")).unwrap();
+    let (output, _) = run_with_cargo_bin_and_snippet(snippet, &["--print-html-report"])?;
+
+    assert!(output.starts_with("<!DOCTYPE html>"));
+    assert!(output.contains("<title>rusty-ex report: temp</title>"));
+    // offline: no external scripts or styles
+    assert!(!output.contains("<script src"));
+    assert!(!output.contains("<link"));
+    assert!(!output.contains("https://"));
+
+    // graphs and centrality are embedded
+    assert!(!output.contains("/*__RUSTY_EX_DATA__*/"));
+    assert!(output.contains(r#""label":"!b","cfgs":["!b"]"#));
+    assert!(output.contains(r#""cfgs":["a","any(a, c)"]"#));
+    assert!(output.contains(r#""label":"second","cfgs":["any(a, c)"]"#));
+    assert!(output.contains(r#""centrality":[{"name":"#));
+
+    Ok(())
+}
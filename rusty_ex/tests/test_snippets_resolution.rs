@@ -0,0 +1,103 @@
+mod utils;
+
+use utils::run_with_cargo_bin_and_snippet;
+use utils::same_line;
+
+const FOLDER: &str = "tests/snippets/resolution";
+
+// =============================================
+
+#[test]
+fn test_calls_without_resolution() -> Result<(), String> {
+    let snippet = &std::fs::read_to_string(format!("{FOLDER}/calls.rs")).unwrap();
+    let (output, _) = run_with_cargo_bin_and_snippet(snippet, &["--print-terms-tree"])?;
+
+    // qualified paths do not match any name: recovery weight + children, like a resolved call
+    assert!(same_line(
+        &output,
+        vec!["Reference(Call)->A::new", "w8.00", "recovered"]
+    ));
+    assert!(same_line(
+        &output,
+        vec!["Reference(Call)->B::new", "w8.00", "recovered"]
+    ));
+    // different functions with the same name are averaged
+    assert!(!same_line(
+        &output,
+        vec!["Reference(Call)->helper", "w1.00"]
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_calls_with_resolution() -> Result<(), String> {
+    let snippet = &std::fs::read_to_string(format!("{FOLDER}/calls.rs")).unwrap();
+    let (output, _) =
+        run_with_cargo_bin_and_snippet(snippet, &["--print-terms-tree", "--resolve-calls"])?;
+
+    // weight of the exact item + children
+    assert!(same_line(&output, vec!["Reference(Call)->A::new", "w3.00"]));
+    assert!(same_line(&output, vec!["Reference(Call)->B::new", "w5.00"]));
+    assert!(same_line(&output, vec!["Reference(Call)->helper", "w1.00"]));
+    assert!(same_line(
+        &output,
+        vec!["Reference(Call)->big::helper", "w4.00"]
+    ));
+    assert!(same_line(
+        &output,
+        vec!["Reference(Call)->small::run", "w2.00"]
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_method_calls_without_resolution() -> Result<(), String> {
+    let snippet = &std::fs::read_to_string(format!("{FOLDER}/methods.rs")).unwrap();
+    let (output, _) = run_with_cargo_bin_and_snippet(snippet, &["--print-terms-tree"])?;
+
+    // methods with the same name are averaged
+    assert!(same_line(
+        &output,
+        vec!["Reference(MethodCall)->size", "w2.50"]
+    ));
+    assert!(same_line(
+        &output,
+        vec!["Reference(MethodCall)->area", "w3.00"]
+    ));
+    assert!(!same_line(
+        &output,
+        vec!["Reference(MethodCall)->area", "w5.00"]
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_method_calls_with_resolution() -> Result<(), String> {
+    let snippet = &std::fs::read_to_string(format!("{FOLDER}/methods.rs")).unwrap();
+    let (output, _) =
+        run_with_cargo_bin_and_snippet(snippet, &["--print-terms-tree", "--resolve-calls"])?;
+
+    // weight of the exact method + children (the receiver)
+    assert!(same_line(
+        &output,
+        vec!["Reference(MethodCall)->size", "w1.00"]
+    ));
+    assert!(same_line(
+        &output,
+        vec!["Reference(MethodCall)->size", "w4.00"]
+    ));
+    // trait methods are resolved to the implementation of the type
+    assert!(same_line(
+        &output,
+        vec!["Reference(MethodCall)->area", "w3.00"]
+    ));
+    assert!(same_line(
+        &output,
+        vec!["Reference(MethodCall)->area", "w5.00"]
+    ));
+
+    Ok(())
+}
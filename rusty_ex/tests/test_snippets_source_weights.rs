@@ -0,0 +1,96 @@
+#![feature(rustc_private)]
+
+mod utils;
+
+use rusty_ex::weights::WeightMode;
+use utils::run_with_cargo_bin_and_snippet;
+use utils::same_line;
+
+const FOLDER: &str = "tests/snippets/source_weights";
+
+// =============================================
+
+#[test]
+fn test_source_weight() {
+    let source = "fn f() {\n    // comment\n\n    x += 1; /* block */\n}";
+
+    assert_eq!(WeightMode::Lines.source_weight(source), Some(5.0));
+    assert_eq!(WeightMode::SourceLines.source_weight(source), Some(3.0));
+    // `+=` is one token
+    assert_eq!(WeightMode::Tokens.source_weight(source), Some(10.0));
+    assert_eq!(WeightMode::Ast.source_weight(source), None);
+    assert_eq!(WeightMode::Mir.source_weight(source), None);
+}
+
+#[test]
+fn test_glued_tokens() {
+    // multi-character operators are one token, as for the parser
+    assert_eq!(WeightMode::Tokens.source_weight("a::b"), Some(3.0));
+    assert_eq!(WeightMode::Tokens.source_weight("fn f() -> T"), Some(6.0));
+    assert_eq!(WeightMode::Tokens.source_weight("x >>= 1..=2"), Some(5.0));
+    // separated characters are not glued
+    assert_eq!(WeightMode::Tokens.source_weight("x + = 1"), Some(4.0));
+    assert_eq!(WeightMode::Tokens.source_weight("a: :b"), Some(4.0));
+}
+
+#[test]
+fn test_lines_weights() -> Result<(), String> {
+    let snippet = &std::fs::read_to_string(format!("{FOLDER}/comments.rs")).unwrap();
+    let (output, _) = run_with_cargo_bin_and_snippet(
+        snippet,
+        &["--print-artifacts-tree", "--weight-mode", "lines"],
+    )?;
+
+    assert!(same_line(&output, vec!["'commented'", "[a]", "w8.00"]));
+    assert!(same_line(&output, vec!["'compact'", "[b]", "w3.00"]));
+    // the global artifact weighs as the artifacts it contains
+    assert!(same_line(&output, vec!["[__GLOBAL__]", "w11.00"]));
+
+    Ok(())
+}
+
+#[test]
+fn test_source_lines_weights() -> Result<(), String> {
+    let snippet = &std::fs::read_to_string(format!("{FOLDER}/comments.rs")).unwrap();
+    let (output, _) = run_with_cargo_bin_and_snippet(
+        snippet,
+        &["--print-artifacts-tree", "--weight-mode", "source-lines"],
+    )?;
+
+    // blank lines and comments are not counted
+    assert!(same_line(&output, vec!["'commented'", "[a]", "w4.00"]));
+    assert!(same_line(&output, vec!["'compact'", "[b]", "w3.00"]));
+
+    Ok(())
+}
+
+#[test]
+fn test_tokens_weights() -> Result<(), String> {
+    let snippet = &std::fs::read_to_string(format!("{FOLDER}/comments.rs")).unwrap();
+    let (output, _) = run_with_cargo_bin_and_snippet(
+        snippet,
+        &["--print-artifacts-tree", "--weight-mode", "tokens"],
+    )?;
+
+    assert!(same_line(&output, vec!["'commented'", "[a]", "w16.00"]));
+    // the rewritten `cfg!` is counted as written in the source code
+    assert!(same_line(&output, vec!["'compact'", "[b]", "w17.00"]));
+
+    Ok(())
+}
+
+#[test]
+fn test_ast_weights_without_features() -> Result<(), String> {
+    let snippet = &std::fs::read_to_string(format!("{FOLDER}/no_features.rs")).unwrap();
+    let (default_output, _) = run_with_cargo_bin_and_snippet(snippet, &["--print-artifacts-tree"])?;
+    let (output, _) = run_with_cargo_bin_and_snippet(
+        snippet,
+        &["--print-artifacts-tree", "--weight-mode", "ast"],
+    )?;
+
+    // no artifact has source code, the AST weights are kept
+    assert_eq!(output, default_output);
+    assert!(!same_line(&output, vec!["[__GLOBAL__]", "w0.00"]));
+
+    Ok(())
+}
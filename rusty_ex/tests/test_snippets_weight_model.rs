@@ -0,0 +1,62 @@
+mod utils;
+
+use utils::run_with_cargo_bin_and_snippet;
+use utils::same_line;
+
+const FOLDER: &str = "tests/snippets/weight_model";
+
+/// Absolute path of a weight model file, the plugin runs in the temporary workspace
+fn weight_model_path(file: &str) -> String {
+    std::env::current_dir()
+        .unwrap()
+        .join(FOLDER)
+        .join(file)
+        .display()
+        .to_string()
+}
+
+// =============================================
+
+#[test]
+fn test_default_weight_model() -> Result<(), String> {
+    let snippet = &std::fs::read_to_string(format!("{FOLDER}/calls.rs")).unwrap();
+    let (output, _) = run_with_cargo_bin_and_snippet(snippet, &["--print-terms-tree"])?;
+
+    assert!(same_line(&output, vec!["Reference(Call)->a", "w2.00"]));
+    assert!(same_line(&output, vec!["Children(Closure)", "w1.00"]));
+    assert!(same_line(&output, vec!["Intrinsic(Lit)", "w1.00"]));
+
+    Ok(())
+}
+
+#[test]
+fn test_toml_weight_model() -> Result<(), String> {
+    let snippet = &std::fs::read_to_string(format!("{FOLDER}/calls.rs")).unwrap();
+    let path = weight_model_path("weights.toml");
+    let (output, _) =
+        run_with_cargo_bin_and_snippet(snippet, &["--print-terms-tree", "--weight-model", &path])?;
+
+    // overridden weight + referenced item + children
+    assert!(same_line(&output, vec!["Reference(Call)->a", "w4.50"]));
+    assert!(same_line(&output, vec!["Children(Closure)", "w3.00"]));
+    assert!(same_line(&output, vec!["Intrinsic(Lit)", "w0.50"]));
+    assert!(same_line(&output, vec!["Children(Fn)", "'a'", "w0.50"]));
+    // kinds not listed keep the default weight
+    assert!(same_line(&output, vec!["Intrinsic(Path)", "w1.00"]));
+
+    Ok(())
+}
+
+#[test]
+fn test_invalid_weight_model() -> Result<(), String> {
+    let snippet = &std::fs::read_to_string(format!("{FOLDER}/calls.rs")).unwrap();
+    let path = weight_model_path("invalid.toml");
+    let (output, _) =
+        run_with_cargo_bin_and_snippet(snippet, &["--print-terms-tree", "--weight-model", &path])?;
+
+    // the default weight model is used
+    assert!(same_line(&output, vec!["Reference(Call)->a", "w2.00"]));
+    assert!(same_line(&output, vec!["Children(Closure)", "w1.00"]));
+
+    Ok(())
+}
@@ -19,6 +19,9 @@ fn test_help_output() -> Result<(), String> {
         "--print-artifacts-tree",
         "--print-features-graph",
         "--print-terms-tree",
+        "--katz-alpha",
+        "--eigenvector-tolerance",
+        "--closeness",
     ] {
         assert!(output.contains(options));
     }
@@ -81,3 +84,180 @@ fn test_simple_feature_weigths_features_graph() -> Result<(), String> {
 
     Ok(())
 }
+
+#[test]
+fn test_output_dir() -> Result<(), String> {
+    let output_dir = std::env::temp_dir().join("rusty-ex-output-dir");
+    let _ = std::fs::remove_dir_all(&output_dir);
+    let (output, _) = run_with_cargo_bin(
+        "workspaces/simple_feature_no_weights",
+        None,
+        &[
+            "--output-dir",
+            output_dir.to_str().unwrap(),
+            "--print-terms-tree",
+            "--print-features-graph",
+            "--print-features-centrality",
+            "--print-metadata",
+        ],
+    )?;
+
+    // nothing is printed, each output has its own file
+    assert_eq!(output, "");
+    let crate_dir = output_dir.join("simple_feature_no_weights-bin");
+    let read = |file_name: &str| std::fs::read_to_string(crate_dir.join(file_name)).unwrap();
+    assert!(read("terms.dot").starts_with("digraph {"));
+    assert!(read("features.dot").contains("0 [ label=\"i0: __GLOBAL__"));
+    assert!(read("centrality.json").starts_with('['));
+    assert!(read("metadata.json").contains("\"term_nodes\""));
+    assert!(read("metadata.json").contains("\"formula_size_after\""));
+    assert!(read("configs.json").starts_with('['));
+    assert!(!crate_dir.join("artifacts.dot").exists());
+
+    // the index lists the written files
+    let index: serde_json::Value = serde_json::from_str(&read("index.json")).unwrap();
+    assert_eq!(index["crate_name"], "simple_feature_no_weights");
+    assert_eq!(index["crate_type"], "bin");
+    assert_eq!(
+        index["files"],
+        serde_json::json!({
+            "centrality": "centrality.json",
+            "configs": "configs.json",
+            "features_graph": "features.dot",
+            "metadata": "metadata.json",
+            "terms_tree": "terms.dot",
+        })
+    );
+
+    let _ = std::fs::remove_dir_all(&output_dir);
+    Ok(())
+}
+
+#[test]
+fn test_print_configs() -> Result<(), String> {
+    let (output, _) = run_with_cargo_bin("workspaces/simple_feature_no_weights", None, &[])?;
+    // the configurations are printed only if requested
+    assert_eq!(output, "");
+
+    let (output, _) = run_with_cargo_bin(
+        "workspaces/simple_feature_no_weights",
+        None,
+        &["--print-configs", "--config-budget", "1"],
+    )?;
+    let configs: Vec<Vec<(u32, bool)>> = serde_json::from_str(&output).unwrap();
+    assert_eq!(configs.len(), 1);
+    assert!(!configs[0].is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn test_dead_code() -> Result<(), String> {
+    let (output, _) = run_with_cargo_bin("workspaces/dead_code", None, &["--print-dead-code"])?;
+
+    assert!(output.contains("'never' [!a] at src/lib.rs:4:5: conflicting cfgs: a, !a\n"));
+    assert!(output.contains(
+        "'contradiction' [all(c, !c)] at src/lib.rs:11:1: conflicting cfgs: all(c, !c)\n"
+    ));
+    assert!(!output.contains("'fine'"));
+    // the manifest is not considered
+    assert!(!output.contains("'disabled_by_manifest'"));
+
+    Ok(())
+}
+
+#[test]
+fn test_dead_code_manifest_constraints() -> Result<(), String> {
+    let (output, _) = run_with_cargo_bin(
+        "workspaces/dead_code",
+        None,
+        &["--print-dead-code", "--manifest-constraints"],
+    )?;
+
+    assert!(output.contains("'never' [!a] at src/lib.rs:4:5: conflicting cfgs: a, !a\n"));
+    assert!(output.contains(
+        "'disabled_by_manifest' [!b] at src/lib.rs:17:5: conflicting cfgs: a, !b (with the features of the manifest)"
+    ));
+    assert!(!output.contains("'fine'"));
+
+    Ok(())
+}
+
+#[test]
+fn test_lint_features() -> Result<(), String> {
+    let (output, _) = run_with_cargo_bin("workspaces/feature_lint", None, &["--lint-features"])?;
+
+    let lints: serde_json::Value = serde_json::from_str(output.trim()).unwrap();
+    assert_eq!(lints["clean"], false);
+    assert_eq!(lints["undeclared"].as_array().unwrap().len(), 1);
+    assert_eq!(lints["undeclared"][0]["name"], "usde");
+    assert_eq!(lints["undeclared"][0]["spans"][0]["lo_line"], 9);
+    // `default` is never reported, `negated` is used (negated) in a `cfg`
+    assert_eq!(lints["unused"], serde_json::json!(["unused"]));
+    assert_eq!(lints["forwarding"][0]["name"], "full");
+    assert_eq!(
+        lints["forwarding"][0]["enables"],
+        serde_json::json!(["used", "negated"])
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_coverage() -> Result<(), String> {
+    let configurations = std::env::current_dir()
+        .unwrap()
+        .join("tests/workspaces/coverage/configs.json");
+    let (output, _) = run_with_cargo_bin(
+        "workspaces/coverage",
+        None,
+        &["--coverage", configurations.to_str().unwrap()],
+    )?;
+
+    let coverage: serde_json::Value = serde_json::from_str(output.trim()).unwrap();
+    // `--features a` enables `a`, `b` (enabled by `a`) and `std` (by `default`)
+    assert_eq!(
+        coverage["configurations"][0]["enabled"],
+        serde_json::json!(["a", "b", "default", "std"])
+    );
+    assert_eq!(coverage["configurations"][0]["artifacts"], 3);
+    // a list of features does not enable the default ones
+    assert_eq!(
+        coverage["configurations"][1]["enabled"],
+        serde_json::json!(["c"])
+    );
+    assert_eq!(coverage["configurations"][1]["artifacts"], 1);
+
+    assert_eq!(coverage["artifacts"], 6);
+    assert_eq!(coverage["covered_artifacts"], 4);
+    let fraction = coverage["covered_fraction"].as_f64().unwrap();
+    assert!(fraction > 0.0 && fraction < 1.0);
+
+    // the heaviest first, `c` is enabled only where `a` is not
+    let uncovered = coverage["uncovered"].as_array().unwrap();
+    assert_eq!(uncovered.len(), 2);
+    assert_eq!(uncovered[0]["ident"], "a_and_c");
+    assert_eq!(uncovered[1]["complex_feature"], "c");
+    let no_std = coverage["covered"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|artifact| artifact["ident"] == "no_std")
+        .unwrap();
+    assert_eq!(no_std["configurations"], serde_json::json!([1]));
+
+    // the nested artifact `c` is not compiled with `a`: its weight is counted once, as uncovered
+    let a = coverage["covered"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|artifact| artifact["ident"] == "a")
+        .unwrap();
+    assert_eq!(a["weight"].as_f64(), Some(0.0));
+    assert_eq!(uncovered[1]["weight"].as_f64(), Some(2.0));
+    assert_eq!(coverage["covered_weight"].as_f64(), Some(4.0));
+    // the weights of all the artifacts sum up to the weight of the crate
+    assert_eq!(coverage["weight"].as_f64(), Some(12.0));
+
+    Ok(())
+}
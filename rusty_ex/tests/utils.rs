@@ -1,7 +1,7 @@
 use std::env;
 use std::fs;
 use std::path::Path;
-use std::process::Command;
+use std::process::{Command, Output};
 use std::sync::Once;
 
 #[allow(unused_macros)] // Due to a bug
@@ -32,6 +32,27 @@ pub fn run_with_cargo_bin(
     expected_outout_name: Option<&str>,
     plugin_args: &[&str],
 ) -> Result<(String, Option<String>), String> {
+    let output = run_plugin(cargo_project_name, plugin_args);
+
+    if let Some(expected_outout_name) = expected_outout_name {
+        let current_dir = Path::new(".").canonicalize().unwrap();
+        let expected_output_path = current_dir
+            .join("tests")
+            .join(cargo_project_name)
+            .join(expected_outout_name);
+        let expected_output = fs::read_to_string(expected_output_path).unwrap();
+        Ok((
+            String::from_utf8(output.stdout).unwrap(),
+            Some(expected_output),
+        ))
+    } else {
+        Ok((String::from_utf8(output.stdout).unwrap(), None))
+    }
+}
+
+/// Install the plugin (only once) and run it with the `cargo` command on the cargo project in the
+/// `tests` directory, see `run_with_cargo_bin`
+fn run_plugin(cargo_project_name: &str, plugin_args: &[&str]) -> Output {
     // Install the plugin
     let root_dir = env::temp_dir().join("rusty-ex");
     let current_dir = Path::new(".").canonicalize().unwrap();
@@ -67,19 +88,8 @@ pub fn run_with_cargo_bin(
     let _ = fs::remove_dir_all(workspace_path.join("target"));
 
     // Run the plugin
-    let output = cargo_cmd.output().unwrap();
+    cargo_cmd.output().unwrap()
     // assert!(output.status.success());  This cannot be true because the plugin is change all `#[cfg(` to `#[my_cfg(` in order to process all the features
-
-    if let Some(expected_outout_name) = expected_outout_name {
-        let expected_output_path = workspace_path.join(expected_outout_name);
-        let expected_output = fs::read_to_string(expected_output_path).unwrap();
-        Ok((
-            String::from_utf8(output.stdout).unwrap(),
-            Some(expected_output),
-        ))
-    } else {
-        Ok((String::from_utf8(output.stdout).unwrap(), None))
-    }
 }
 
 pub fn create_cargo_project_with_snippet(snippet: &str) -> Result<(), String> {
@@ -123,6 +133,21 @@ pub fn run_with_cargo_bin_and_snippet(
     result
 }
 
+#[allow(dead_code)] // TODO: https://github.com/rust-lang/rust/issues/46379
+/// Like `run_with_cargo_bin_and_snippet`, but return the standard output and the standard error
+pub fn run_with_cargo_bin_and_snippet_with_stderr(
+    snippet: &str,
+    plugin_args: &[&str],
+) -> Result<(String, String), String> {
+    create_cargo_project_with_snippet(snippet).unwrap();
+    let output = run_plugin("workspaces/temp", plugin_args);
+    remove_cargo_project_with_snippet().unwrap();
+    Ok((
+        String::from_utf8(output.stdout).unwrap(),
+        String::from_utf8(output.stderr).unwrap(),
+    ))
+}
+
 #[allow(dead_code)] // TODO: https://github.com/rust-lang/rust/issues/46379
 /// Check if at least one line of the output contains all the items (on the same line!)
 pub fn same_line(output: &String, items: Vec<&str>) -> bool {
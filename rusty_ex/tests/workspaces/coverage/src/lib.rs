@@ -0,0 +1,24 @@
+#[cfg(feature = "std")]
+pub fn with_std() {}
+
+#[cfg(feature = "a")]
+pub fn a() {
+    #[cfg(feature = "c")]
+    let _ = 1;
+}
+
+#[cfg(feature = "b")]
+pub fn b() {}
+
+#[cfg(not(feature = "std"))]
+pub fn no_std() {
+    let _ = 1;
+    let _ = 2;
+}
+
+#[cfg(all(feature = "a", feature = "c"))]
+pub fn a_and_c() {
+    let _ = 1;
+    let _ = 2;
+    let _ = 3;
+}
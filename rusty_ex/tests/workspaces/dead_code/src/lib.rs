@@ -0,0 +1,18 @@
+#[cfg(feature = "a")]
+fn outer() {
+    #[cfg(not(feature = "a"))]
+    fn never() {}
+
+    #[cfg(feature = "c")]
+    fn fine() {}
+}
+
+#[cfg(all(feature = "c", not(feature = "c")))]
+fn contradiction() {}
+
+#[cfg(feature = "a")]
+mod gated {
+    // `a` enables `b`
+    #[cfg(not(feature = "b"))]
+    fn disabled_by_manifest() {}
+}
@@ -0,0 +1,9 @@
+#[cfg(feature = "used")]
+fn used() {}
+
+#[cfg(not(feature = "negated"))]
+fn negated() {}
+
+// typo: `used` is declared, `usde` is not
+#[cfg(all(feature = "usde", unix))]
+fn typo() {}